@@ -0,0 +1,32 @@
+//! Ambient sound playback for sound emitters, enabled by the `audio` feature.
+
+use bevy::prelude::*;
+
+use crate::RMeshSoundEmitter;
+
+/// Sounds played by [`RMeshSoundEmitter`]s, indexed by [`RMeshSoundEmitter::sound_index`].
+///
+/// The file only stores the index, the game decides which sound it stands for.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct RMeshSoundTable(pub Vec<Handle<AudioSource>>);
+
+/// Starts a looping spatial sound on every spawned [`RMeshSoundEmitter`].
+pub(crate) fn play_sound_emitters(
+    mut commands: Commands,
+    emitters: Query<(Entity, &RMeshSoundEmitter), Added<RMeshSoundEmitter>>,
+    table: Option<Res<RMeshSoundTable>>,
+) {
+    let Some(table) = table else {
+        return;
+    };
+    for (entity, emitter) in &emitters {
+        let Some(source) = table.0.get(emitter.sound_index as usize) else {
+            warn!("No sound for sound emitter index {}", emitter.sound_index);
+            continue;
+        };
+        commands.entity(entity).insert(AudioBundle {
+            source: source.clone(),
+            settings: PlaybackSettings::LOOP.with_spatial(true),
+        });
+    }
+}
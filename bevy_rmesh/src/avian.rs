@@ -0,0 +1,103 @@
+//! Avian colliders for room collision and trigger boxes, enabled by the `avian` feature.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{RMeshCollider, RMeshTriggerBox};
+
+/// Builds a trimesh [`Collider`] for every spawned [`RMeshCollider`].
+pub(crate) fn insert_colliders(
+    mut commands: Commands,
+    colliders: Query<(Entity, &Handle<Mesh>), Added<RMeshCollider>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (entity, handle) in &colliders {
+        let Some(mesh) = meshes.get(handle) else {
+            continue;
+        };
+        match Collider::trimesh_from_mesh(mesh) {
+            Some(collider) => {
+                commands
+                    .entity(entity)
+                    .insert((collider, RigidBody::Static));
+            }
+            None => warn!("Room collision mesh has no usable triangles"),
+        }
+    }
+}
+
+/// Builds a sensor [`Collider`] for every spawned [`RMeshTriggerBox`], compounding the
+/// convex hull of each of its volumes.
+pub(crate) fn insert_sensors(
+    mut commands: Commands,
+    trigger_boxes: Query<(Entity, &RMeshTriggerBox), Added<RMeshTriggerBox>>,
+) {
+    for (entity, trigger_box) in &trigger_boxes {
+        let mut hulls: Vec<_> = trigger_box
+            .volumes
+            .iter()
+            .filter_map(|points| Collider::convex_hull(points.clone()))
+            .collect();
+        let collider = match hulls.len() {
+            0 => None,
+            1 => hulls.pop(),
+            _ => Some(Collider::compound(
+                hulls
+                    .into_iter()
+                    .map(|hull| (Vec3::ZERO, Quat::IDENTITY, hull))
+                    .collect(),
+            )),
+        };
+        match collider {
+            Some(collider) => {
+                commands.entity(entity).insert((collider, Sensor));
+            }
+            None => warn!("Trigger box {} has no volume", trigger_box.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collision_and_trigger_boxes_get_colliders() {
+        let mut app = App::new();
+        app.init_resource::<Assets<Mesh>>()
+            .add_systems(Update, (insert_colliders, insert_sensors));
+        let mesh = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Cuboid::default());
+        let collision = app.world_mut().spawn((mesh, RMeshCollider)).id();
+        let corners = Cuboid::default().half_size;
+        let trigger_box = app
+            .world_mut()
+            .spawn(RMeshTriggerBox {
+                name: "door".into(),
+                volumes: vec![vec![
+                    -corners,
+                    corners,
+                    corners * Vec3::X,
+                    corners * Vec3::Y,
+                ]],
+            })
+            .id();
+        let empty = app
+            .world_mut()
+            .spawn(RMeshTriggerBox {
+                name: "empty".into(),
+                volumes: vec![],
+            })
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<Collider>(collision).is_some());
+        assert_eq!(world.get::<RigidBody>(collision), Some(&RigidBody::Static));
+        assert!(world.get::<Sensor>(trigger_box).is_some());
+        assert!(world.get::<Collider>(empty).is_none());
+    }
+}
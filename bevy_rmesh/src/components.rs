@@ -0,0 +1,178 @@
+//! Components marking the scene entities spawned for each rmesh entity type.
+//!
+//! Positions end up in the entity's [`Transform`], the components keep the rest of the data.
+
+use crate::lights::mirrored_rotation;
+use bevy::prelude::*;
+use rmesh::{
+    EntityLight, EntityModel, EntityPlayerStart, EntityScreen, EntitySoundEmitter, EntitySpotlight,
+    EntityWaypoint, ThreeTypeString,
+};
+
+fn color(color: &ThreeTypeString) -> Color {
+    let [r, g, b] = rmesh::lighting::linear_color(color);
+    Color::linear_rgb(r, g, b)
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshLight {
+    pub range: f32,
+    pub color: Color,
+    pub intensity: f32,
+}
+
+impl From<&EntityLight> for RMeshLight {
+    fn from(data: &EntityLight) -> Self {
+        Self {
+            range: data.range,
+            color: color(&data.color),
+            intensity: data.intensity,
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshSpotlight {
+    pub range: f32,
+    pub color: Color,
+    pub intensity: f32,
+    /// Pitch, yaw and roll in degrees.
+    pub angles: [f32; 3],
+    pub inner_cone_angle: f32,
+    pub outer_cone_angle: f32,
+}
+
+impl From<&EntitySpotlight> for RMeshSpotlight {
+    fn from(data: &EntitySpotlight) -> Self {
+        Self {
+            range: data.range,
+            color: color(&data.color),
+            intensity: data.intensity,
+            angles: data.angles.0,
+            inner_cone_angle: data.inner_cone_angle,
+            outer_cone_angle: data.outer_cone_angle,
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshScreen {
+    /// Image shown on the screen.
+    pub name: String,
+}
+
+impl From<&EntityScreen> for RMeshScreen {
+    fn from(data: &EntityScreen) -> Self {
+        Self {
+            name: String::from(&data.name),
+        }
+    }
+}
+
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshWaypoint;
+
+impl From<&EntityWaypoint> for RMeshWaypoint {
+    fn from(_: &EntityWaypoint) -> Self {
+        Self
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshSoundEmitter {
+    /// Index of the room ambience sound, see `RMeshSoundTable` with the `audio` feature.
+    pub sound_index: u32,
+    /// Distance the sound carries, in room units.
+    pub range: f32,
+}
+
+impl From<&EntitySoundEmitter> for RMeshSoundEmitter {
+    fn from(data: &EntitySoundEmitter) -> Self {
+        Self {
+            sound_index: data.idk0,
+            range: data.idk1,
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshPlayerStart {
+    /// Where the player spawns, looking along -Z like a Bevy camera.
+    pub transform: Transform,
+}
+
+impl RMeshPlayerStart {
+    pub fn new(data: &EntityPlayerStart, scale: f32) -> Self {
+        let transform = data.transform(scale);
+        let [x, y, z] = transform.translation;
+        Self {
+            // Blitz3D cameras look along +Z, which the room mirroring turns into -Z
+            transform: Transform::from_translation(Vec3::new(x, y, -z))
+                .with_rotation(mirrored_rotation(transform.rotation)),
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RMeshModel {
+    /// Model file, relative to the props directory.
+    pub name: String,
+    /// Pitch, yaw and roll in degrees.
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl From<&EntityModel> for RMeshModel {
+    fn from(data: &EntityModel) -> Self {
+        Self {
+            name: String::from(&data.name),
+            rotation: data.rotation,
+            scale: data.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_starts_look_along_the_mirrored_forward() {
+        for angles in [[0.0; 3], [0.0, 90.0, 0.0], [-30.0, 200.0, 0.0]] {
+            let data = EntityPlayerStart {
+                position: [100.0, 20.0, 50.0],
+                angles: angles.into(),
+            };
+
+            let start = RMeshPlayerStart::new(&data, 0.5);
+
+            assert_eq!(start.transform.translation, Vec3::new(50.0, 10.0, -25.0));
+            let [x, y, z] = data.angles.forward();
+            let forward = start.transform.forward();
+            assert!(
+                forward.abs_diff_eq(Vec3::new(x, y, -z), 1e-5),
+                "{angles:?}: {forward:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn light_colors_become_linear() {
+        let light = RMeshLight::from(&EntityLight {
+            position: [0.0; 3],
+            range: 10.0,
+            color: [255, 0, 0].into(),
+            intensity: 0.5,
+        });
+
+        assert_eq!(light.color, Color::linear_rgb(1.0, 0.0, 0.0));
+        assert_eq!(light.range, 10.0);
+    }
+}
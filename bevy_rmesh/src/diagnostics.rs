@@ -0,0 +1,128 @@
+//! What happened while loading a room, for loading screens and tracking down asset problems.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::Instant;
+
+use crate::Room;
+
+/// Sent once a room and its textures have loaded, also kept on [`Room::diagnostics`].
+#[derive(Event, Debug, Clone, Default)]
+pub struct RoomLoadDiagnostics {
+    /// The loaded room, left as the default id on [`Room::diagnostics`].
+    pub room: AssetId<Room>,
+    /// Texture and lightmap paths that couldn't be found, drawn magenta or left unlit.
+    pub missing_textures: Vec<String>,
+    /// Names of entities left out of the scene, see [`Name`].
+    pub skipped_entities: Vec<String>,
+    /// Time spent in each phase of the loader, in order.
+    pub timings: Vec<(&'static str, Duration)>,
+}
+
+/// Times the phases of a load one after the other.
+pub(crate) struct PhaseTimer {
+    start: Instant,
+    timings: Vec<(&'static str, Duration)>,
+}
+
+impl PhaseTimer {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            timings: vec![],
+        }
+    }
+
+    /// Records the time since the previous phase ended.
+    pub fn finish(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.timings.push((phase, now - self.start));
+        self.start = now;
+    }
+
+    pub fn into_timings(self) -> Vec<(&'static str, Duration)> {
+        self.timings
+    }
+}
+
+pub(crate) fn send_load_diagnostics(
+    mut asset_events: EventReader<AssetEvent<Room>>,
+    rooms: Res<Assets<Room>>,
+    mut diagnostics: EventWriter<RoomLoadDiagnostics>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::LoadedWithDependencies { id } = event else {
+            continue;
+        };
+        if let Some(room) = rooms.get(*id) {
+            diagnostics.send(RoomLoadDiagnostics {
+                room: *id,
+                ..room.diagnostics.clone()
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(diagnostics: RoomLoadDiagnostics) -> Room {
+        Room {
+            scene: Handle::default(),
+            meshes: vec![],
+            entity_meshes: vec![],
+            entities: vec![],
+            colliders: vec![],
+            trigger_boxes: vec![],
+            diagnostics,
+            scale: 1.0,
+            props: Default::default(),
+            #[cfg(feature = "navmesh")]
+            navmesh: Handle::default(),
+        }
+    }
+
+    #[test]
+    fn phases_are_timed_in_order() {
+        let mut timer = PhaseTimer::new();
+        timer.finish("parse");
+        timer.finish("meshes");
+
+        let phases: Vec<_> = timer
+            .into_timings()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(phases, ["parse", "meshes"]);
+    }
+
+    #[test]
+    fn loaded_rooms_send_their_diagnostics() {
+        let mut app = App::new();
+        app.add_event::<AssetEvent<Room>>()
+            .add_event::<RoomLoadDiagnostics>()
+            .init_resource::<Assets<Room>>()
+            .add_systems(Update, send_load_diagnostics);
+        let id = app
+            .world_mut()
+            .resource_mut::<Assets<Room>>()
+            .add(room(RoomLoadDiagnostics {
+                missing_textures: vec!["wall.jpg".into()],
+                ..Default::default()
+            }))
+            .id();
+        app.world_mut().send_event(AssetEvent::Added { id });
+        app.world_mut()
+            .send_event(AssetEvent::LoadedWithDependencies { id });
+
+        app.update();
+
+        let events = app.world().resource::<Events<RoomLoadDiagnostics>>();
+        let sent: Vec<_> = events.iter_current_update_events().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].room, id);
+        assert_eq!(sent[0].missing_textures, ["wall.jpg"]);
+    }
+}
@@ -0,0 +1,286 @@
+//! Turns spawned rooms back into rmesh files, for level editors built on Bevy.
+//!
+//! Rooms are expected at the default [`ROOM_SCALE`] with propagated [`GlobalTransform`]s.
+
+use bevy::pbr::Lightmap;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy::render::render_resource::PrimitiveTopology;
+use rmesh::{
+    AnglesString, ComplexMesh, EntityData, EntityLight, EntityModel, EntityPlayerStart,
+    EntityScreen, EntitySoundEmitter, EntitySpotlight, EntityType, EntityWaypoint, Header,
+    SimpleMesh, TextureBlendType, TriggerBox, Vertex, ROOM_SCALE,
+};
+
+use crate::{
+    RMeshCollider, RMeshLight, RMeshModel, RMeshPlayerStart, RMeshScreen, RMeshSoundEmitter,
+    RMeshSpotlight, RMeshTriggerBox, RMeshWaypoint,
+};
+
+/// Builds a room from `root` and its descendants.
+///
+/// Meshes with a [`StandardMaterial`] become room meshes, textures are written by file name
+/// like rooms store them. Trigger boxes and the rmesh entity components are written back,
+/// the merged [`RMeshCollider`] is skipped since the room meshes already collide.
+pub fn room_from_world(world: &World, root: Entity) -> Header {
+    let mut header = Header::default();
+    let root_transform = world
+        .get::<GlobalTransform>(root)
+        .copied()
+        .unwrap_or_default();
+    let meshes = world.resource::<Assets<Mesh>>();
+    let materials = world.resource::<Assets<StandardMaterial>>();
+    let asset_server = world.resource::<AssetServer>();
+    let texture_name = |image: &Handle<Image>| {
+        let path = asset_server.get_path(image.id())?;
+        let name = path.path().file_name()?.to_str()?.to_string();
+        Some(name)
+    };
+
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        let entity_ref = world.entity(entity);
+        // Prop parts are written as the model
+        if !entity_ref.contains::<RMeshModel>() {
+            if let Some(children) = entity_ref.get::<Children>() {
+                stack.extend(children.iter().rev());
+            }
+        }
+
+        let transform = entity_ref
+            .get::<GlobalTransform>()
+            .map(|global| global.reparented_to(&root_transform))
+            .unwrap_or_default();
+        let position = to_room(transform.translation, ROOM_SCALE);
+
+        if let Some(entity_type) = entity_type(&entity_ref, position, transform.rotation) {
+            header.entities.push(EntityData::new(entity_type));
+            continue;
+        }
+        if entity_ref.contains::<RMeshCollider>() {
+            continue;
+        }
+        let Some(mesh) = entity_ref
+            .get::<Handle<Mesh>>()
+            .and_then(|handle| meshes.get(handle))
+        else {
+            continue;
+        };
+
+        if let Some(trigger_box) = entity_ref.get::<RMeshTriggerBox>() {
+            if let Some(complex_mesh) = complex_mesh_from_mesh(mesh, &transform, ROOM_SCALE) {
+                let vertices = complex_mesh.vertices.iter().map(|v| v.position).collect();
+                header.trigger_boxes.push(TriggerBox {
+                    meshes: vec![SimpleMesh::new(vertices, complex_mesh.triangles)],
+                    name: trigger_box.name.as_str().into(),
+                });
+            }
+            continue;
+        }
+
+        let Some(material) = entity_ref
+            .get::<Handle<StandardMaterial>>()
+            .and_then(|handle| materials.get(handle))
+        else {
+            continue;
+        };
+        let Some(mut complex_mesh) = complex_mesh_from_mesh(mesh, &transform, ROOM_SCALE) else {
+            continue;
+        };
+        if let Some(name) = material.base_color_texture.as_ref().and_then(texture_name) {
+            complex_mesh.textures[1].blend_type = match material.alpha_mode {
+                AlphaMode::Opaque => TextureBlendType::Visible,
+                _ => TextureBlendType::Transparent,
+            };
+            complex_mesh.textures[1].path = Some(name.into());
+        }
+        let lightmap = entity_ref.get::<Lightmap>();
+        if let Some(name) = lightmap.and_then(|lightmap| texture_name(&lightmap.image)) {
+            complex_mesh.textures[0].blend_type = TextureBlendType::Lightmap;
+            complex_mesh.textures[0].path = Some(name.into());
+        }
+        header.meshes.push(complex_mesh);
+    }
+    header
+}
+
+/// Converts a triangle mesh, placed by `transform`, to a room mesh in room units.
+///
+/// Returns `None` when the mesh has no positions or isn't a triangle list.
+pub fn complex_mesh_from_mesh(
+    mesh: &Mesh,
+    transform: &Transform,
+    scale: f32,
+) -> Option<ComplexMesh> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let uvs = |attribute| match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => uvs.clone(),
+        _ => vec![[0.0; 2]; positions.len()],
+    };
+    let (uvs0, uvs1) = (uvs(Mesh::ATTRIBUTE_UV_0), uvs(Mesh::ATTRIBUTE_UV_1));
+    let colors: Vec<_> = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => colors
+            .iter()
+            .map(|&color| rmesh::color::linear_to_color(color))
+            .collect(),
+        _ => vec![[255; 3]; positions.len()],
+    };
+
+    let vertices = positions
+        .iter()
+        .enumerate()
+        .map(|(i, &position)| Vertex {
+            position: to_room(transform.transform_point(Vec3::from(position)), scale),
+            tex_coords: [uvs0[i], uvs1[i]],
+            color: colors[i],
+        })
+        .collect();
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.iter().map(|&i| i as u32).collect(),
+        Some(Indices::U32(indices)) => indices.clone(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    // Reversed back for the mirrored Z
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|triangle| [triangle[2], triangle[1], triangle[0]])
+        .collect();
+
+    Some(ComplexMesh {
+        vertices,
+        triangles,
+        ..Default::default()
+    })
+}
+
+/// Converts a position relative to the room to room units.
+fn to_room(position: Vec3, scale: f32) -> [f32; 3] {
+    let position = position / scale;
+    [position.x, position.y, -position.z]
+}
+
+/// Pitch, yaw and roll in degrees of a rotation, the inverse of
+/// [`spot_light_rotation`](crate::spot_light_rotation).
+fn to_angles(rotation: Quat) -> [f32; 3] {
+    let (yaw, pitch, roll) = rotation.to_euler(EulerRot::YXZ);
+    [-pitch, -yaw, roll].map(f32::to_degrees)
+}
+
+fn entity_type(entity: &EntityRef, position: [f32; 3], rotation: Quat) -> Option<EntityType> {
+    let color = |color: Color| {
+        let [r, g, b, _] = color.to_srgba().to_u8_array();
+        [r, g, b].into()
+    };
+    if let Some(light) = entity.get::<RMeshLight>() {
+        return Some(EntityType::Light(EntityLight {
+            position,
+            range: light.range,
+            color: color(light.color),
+            intensity: light.intensity,
+        }));
+    }
+    if let Some(light) = entity.get::<RMeshSpotlight>() {
+        return Some(EntityType::SpotLight(EntitySpotlight {
+            position,
+            range: light.range,
+            color: color(light.color),
+            intensity: light.intensity,
+            angles: AnglesString::from(light.angles),
+            inner_cone_angle: light.inner_cone_angle,
+            outer_cone_angle: light.outer_cone_angle,
+        }));
+    }
+    if let Some(screen) = entity.get::<RMeshScreen>() {
+        return Some(EntityType::Screen(EntityScreen {
+            position,
+            name: screen.name.as_str().into(),
+        }));
+    }
+    if entity.contains::<RMeshWaypoint>() {
+        return Some(EntityType::WayPoint(EntityWaypoint { position }));
+    }
+    if let Some(emitter) = entity.get::<RMeshSoundEmitter>() {
+        return Some(EntityType::SoundEmitter(EntitySoundEmitter {
+            position,
+            idk0: emitter.sound_index,
+            idk1: emitter.range,
+        }));
+    }
+    if entity.contains::<RMeshPlayerStart>() {
+        return Some(EntityType::PlayerStart(EntityPlayerStart {
+            position,
+            angles: AnglesString::from(to_angles(rotation)),
+        }));
+    }
+    if let Some(model) = entity.get::<RMeshModel>() {
+        return Some(EntityType::Model(EntityModel {
+            name: model.name.as_str().into(),
+            position,
+            rotation: model.rotation,
+            scale: model.scale,
+        }));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::render_asset::RenderAssetUsages;
+
+    #[test]
+    fn meshes_return_to_room_units() {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all());
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, -2.0]],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.5, 0.5]; 3]);
+        mesh.insert_indices(Indices::U16(vec![0, 1, 2]));
+        let transform = Transform::from_xyz(0.0, 2.0, 0.0);
+
+        let complex_mesh = complex_mesh_from_mesh(&mesh, &transform, 2.0).unwrap();
+
+        let positions: Vec<_> = complex_mesh.vertices.iter().map(|v| v.position).collect();
+        assert_eq!(
+            positions,
+            [[0.0, 1.0, 0.0], [1.0, 1.0, 0.0], [0.0, 2.0, 1.0]]
+        );
+        assert_eq!(complex_mesh.triangles, [[2, 1, 0]]);
+        assert_eq!(
+            complex_mesh.vertices[0].tex_coords,
+            [[0.5, 0.5], [0.0, 0.0]]
+        );
+        assert_eq!(complex_mesh.vertices[0].color, [255; 3]);
+
+        let lines = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::all());
+        assert!(complex_mesh_from_mesh(&lines, &transform, 2.0).is_none());
+    }
+
+    #[test]
+    fn angles_invert_the_spot_light_rotation() {
+        for angles in [[30.0, 45.0, 10.0], [-60.0, 170.0, 0.0], [0.0; 3]] {
+            let light = EntitySpotlight {
+                position: [0.0; 3],
+                range: 1.0,
+                color: [255; 3].into(),
+                intensity: 1.0,
+                angles: AnglesString::from(angles),
+                inner_cone_angle: 0.0,
+                outer_cone_angle: 90.0,
+            };
+
+            let result = to_angles(crate::spot_light_rotation(&light));
+            for (result, angle) in result.into_iter().zip(angles) {
+                assert!((result - angle).abs() < 1e-3, "{result} != {angle}");
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Assembles many rooms into one world, like the facility built from a map seed.
+
+use std::collections::HashMap;
+
+use bevy::asset::AssetPath;
+use bevy::pbr::Lightmap;
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::scene::SceneInstance;
+
+use crate::{RMeshLoaderSettings, Room};
+
+/// How [`spawn_facility`] loads and assembles rooms.
+#[derive(Default)]
+pub struct FacilitySettings {
+    /// Used for every room. Set [`RMeshLoaderSettings::texture_root`] so rooms in different
+    /// directories resolve textures to the same files, each file is then loaded once.
+    pub loader: RMeshLoaderSettings,
+    /// Merges the room meshes of neighbouring rooms into grid cells of this size in room units,
+    /// once every room has spawned. Only meshes sharing a texture and lightmap are merged, so
+    /// lightmapped rooms mostly merge with themselves.
+    pub merge_chunk_size: Option<f32>,
+}
+
+/// A room spawned by [`spawn_facility`].
+#[derive(Debug, Clone)]
+pub struct FacilityRoom {
+    pub room: Handle<Room>,
+    /// Child of the facility the room scene is spawned on.
+    pub entity: Entity,
+    pub transform: Transform,
+}
+
+/// Root of the rooms spawned by [`spawn_facility`].
+#[derive(Component, Debug)]
+pub struct RMeshFacility {
+    pub rooms: Vec<FacilityRoom>,
+    /// Size in world units of the cells meshes are merged into.
+    cell_size: Option<f32>,
+}
+
+/// Spawns each room scene at its transform under a single root entity, which is returned.
+///
+/// Textures are asset dependencies of the rooms, so rooms sharing a texture share the image.
+/// Merged meshes are spawned under the root as `Chunk[x, y, z]`, the room meshes they replace
+/// are hidden but left in place.
+pub fn spawn_facility<'a>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    rooms: impl IntoIterator<Item = (impl Into<AssetPath<'a>>, Transform)>,
+    settings: FacilitySettings,
+) -> Entity {
+    let scale = f32::from(settings.loader.scale);
+    let root = commands
+        .spawn((SpatialBundle::default(), Name::new("Facility")))
+        .id();
+    let rooms = rooms
+        .into_iter()
+        .map(|(path, transform)| {
+            let path = path.into().into_owned();
+            let loader = settings.loader.clone();
+            let room = asset_server.load_with_settings(path.clone(), move |s| *s = loader.clone());
+            let entity = commands
+                .spawn(SceneBundle {
+                    scene: asset_server.load(path.clone().with_label("Scene")),
+                    transform,
+                    ..Default::default()
+                })
+                .insert(Name::new(path.to_string()))
+                .set_parent(root)
+                .id();
+            FacilityRoom {
+                room,
+                entity,
+                transform,
+            }
+        })
+        .collect();
+    commands.entity(root).insert(RMeshFacility {
+        rooms,
+        cell_size: settings.merge_chunk_size.map(|size| size * scale),
+    });
+    root
+}
+
+/// What room meshes need in common to be merged.
+#[derive(PartialEq)]
+struct ChunkKey {
+    cell: IVec3,
+    texture: Option<AssetId<Image>>,
+    lightmap: Option<AssetId<Image>>,
+    alpha_mode: AlphaMode,
+}
+
+struct Chunk {
+    key: ChunkKey,
+    material: Handle<StandardMaterial>,
+    lightmap: Option<Lightmap>,
+    parts: Vec<(Entity, Handle<Mesh>, Transform)>,
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn merge_facility_chunks(
+    mut commands: Commands,
+    mut facilities: Query<(Entity, &mut RMeshFacility)>,
+    instances: Query<&SceneInstance>,
+    scene_spawner: Res<SceneSpawner>,
+    children: Query<&Children>,
+    mut mesh_entities: Query<(
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+        Option<&Lightmap>,
+        &mut Visibility,
+    )>,
+    rooms: Res<Assets<Room>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    for (root, mut facility) in &mut facilities {
+        let Some(cell_size) = facility.cell_size else {
+            continue;
+        };
+        let spawned = facility.rooms.iter().all(|room| {
+            rooms.contains(&room.room)
+                && instances
+                    .get(room.entity)
+                    .is_ok_and(|instance| scene_spawner.instance_is_ready(**instance))
+        });
+        if !spawned {
+            continue;
+        }
+        facility.cell_size = None;
+
+        let mut chunks: Vec<Chunk> = vec![];
+        for facility_room in &facility.rooms {
+            let Some(room) = rooms.get(&facility_room.room) else {
+                continue;
+            };
+            let room_meshes: HashMap<_, _> = room
+                .meshes
+                .iter()
+                .map(|room_mesh| (room_mesh.mesh.id(), room_mesh))
+                .collect();
+            for entity in children.iter_descendants(facility_room.entity) {
+                let Ok((mesh, material, lightmap, visibility)) = mesh_entities.get(entity) else {
+                    continue;
+                };
+                // Invisible collision stays hidden, render-only meshes can't be merged
+                if *visibility == Visibility::Hidden || !meshes.contains(mesh) {
+                    continue;
+                }
+                let (Some(room_mesh), Some(standard_material)) =
+                    (room_meshes.get(&mesh.id()), materials.get(material))
+                else {
+                    continue;
+                };
+                let center = facility_room
+                    .transform
+                    .transform_point(room_mesh.aabb.center.into());
+                let key = ChunkKey {
+                    cell: (center / cell_size).floor().as_ivec3(),
+                    texture: standard_material
+                        .base_color_texture
+                        .as_ref()
+                        .map(Handle::id),
+                    lightmap: lightmap.map(|lightmap| lightmap.image.id()),
+                    alpha_mode: standard_material.alpha_mode,
+                };
+                let part = (entity, mesh.clone(), facility_room.transform);
+                match chunks.iter_mut().find(|chunk| chunk.key == key) {
+                    Some(chunk) => chunk.parts.push(part),
+                    None => chunks.push(Chunk {
+                        key,
+                        material: material.clone(),
+                        lightmap: lightmap.cloned(),
+                        parts: vec![part],
+                    }),
+                }
+            }
+        }
+
+        for chunk in chunks.into_iter().filter(|chunk| chunk.parts.len() > 1) {
+            let mut merged: Option<Mesh> = None;
+            for (_, mesh, transform) in &chunk.parts {
+                let Some(part) = meshes.get(mesh) else {
+                    continue;
+                };
+                let part = part.clone().transformed_by(*transform);
+                match &mut merged {
+                    Some(merged) => merged.merge(&part),
+                    None => merged = Some(with_u32_indices(part)),
+                }
+            }
+            let Some(merged) = merged else {
+                continue;
+            };
+            let mut chunk_entity = commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(merged),
+                    material: chunk.material,
+                    ..Default::default()
+                },
+                Name::new(format!("Chunk{}", chunk.key.cell)),
+            ));
+            if let Some(lightmap) = chunk.lightmap {
+                chunk_entity.insert(lightmap);
+            }
+            chunk_entity.set_parent(root);
+            for (entity, ..) in &chunk.parts {
+                if let Ok((.., mut visibility)) = mesh_entities.get_mut(*entity) {
+                    *visibility = Visibility::Hidden;
+                }
+            }
+        }
+    }
+}
+
+/// Widens the indices so the merged parts can't overflow them.
+fn with_u32_indices(mut mesh: Mesh) -> Mesh {
+    if let Some(Indices::U16(indices)) = mesh.indices() {
+        let indices = indices.iter().map(|&i| i as u32).collect();
+        mesh.insert_indices(Indices::U32(indices));
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+    use bevy::render::render_asset::RenderAssetUsages;
+
+    fn triangle() -> Mesh {
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all())
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            )
+            .with_inserted_indices(Indices::U16(vec![0, 1, 2]))
+    }
+
+    #[test]
+    fn merged_parts_get_wide_indices() {
+        let mut merged = with_u32_indices(triangle());
+        merged.merge(&triangle().transformed_by(Transform::from_xyz(10.0, 0.0, 0.0)));
+
+        let Some(Indices::U32(indices)) = merged.indices() else {
+            panic!("expected u32 indices");
+        };
+        assert_eq!(indices, &[0, 1, 2, 3, 4, 5]);
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            merged.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("expected positions");
+        };
+        assert_eq!(positions[4], [11.0, 0.0, 0.0]);
+    }
+}
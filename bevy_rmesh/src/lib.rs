@@ -1,13 +1,36 @@
+#[cfg(feature = "audio")]
+pub use audio::RMeshSoundTable;
+pub use components::*;
+pub use diagnostics::RoomLoadDiagnostics;
+pub use facility::{spawn_facility, FacilityRoom, FacilitySettings, RMeshFacility};
+pub use lights::{spot_light_rotation, LightSettings};
 pub use loader::*;
+pub use material::{RMeshMaterial, RMeshTextures};
+#[cfg(feature = "navmesh")]
+pub use navmesh::RoomNavMesh;
+pub use processor::{RMeshProcessor, RMeshProcessorSettings};
 pub use rmesh;
 
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(feature = "avian")]
+mod avian;
+mod components;
+mod diagnostics;
+pub mod export;
+mod facility;
+mod lights;
 mod loader;
+mod material;
+#[cfg(feature = "navmesh")]
+mod navmesh;
+mod processor;
+#[cfg(feature = "rapier")]
+mod rapier;
 
-use bevy::{
-    prelude::*,
-    reflect::TypePath,
-    render::{renderer::RenderDevice, texture::CompressedImageFormats},
-};
+use std::collections::HashMap;
+
+use bevy::{pbr::Lightmap, prelude::*, reflect::TypePath, render::primitives::Aabb};
 
 #[derive(Default)]
 pub struct RMeshPlugin;
@@ -16,16 +39,44 @@ impl Plugin for RMeshPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<Room>()
             .init_asset::<RoomMesh>()
-            .preregister_asset_loader::<RMeshLoader>(&["rmesh"]);
+            // Bevy doesn't register it, but the room scenes carry it
+            .register_type::<Lightmap>()
+            .register_type::<RMeshCollider>()
+            .register_type::<RMeshTriggerBox>()
+            .register_type::<RMeshLight>()
+            .register_type::<RMeshSpotlight>()
+            .register_type::<RMeshScreen>()
+            .register_type::<RMeshWaypoint>()
+            .register_type::<RMeshSoundEmitter>()
+            .register_type::<RMeshPlayerStart>()
+            .register_type::<RMeshModel>()
+            // Prop stand-ins are nested scenes
+            .register_type::<Handle<Scene>>()
+            .preregister_asset_loader::<RMeshLoader>(&["rmesh"])
+            .register_asset_processor(RMeshProcessor)
+            .add_event::<RoomLoadDiagnostics>()
+            .add_systems(Update, facility::merge_facility_chunks)
+            .add_systems(PostUpdate, diagnostics::send_load_diagnostics);
+
+        #[cfg(feature = "navmesh")]
+        app.init_asset::<RoomNavMesh>();
+        #[cfg(feature = "rapier")]
+        app.add_systems(
+            PreUpdate,
+            (rapier::insert_colliders, rapier::insert_sensors),
+        );
+        #[cfg(feature = "avian")]
+        app.add_systems(PreUpdate, (avian::insert_colliders, avian::insert_sensors));
+        #[cfg(feature = "audio")]
+        app.add_systems(PreUpdate, audio::play_sound_emitters);
     }
 
     fn finish(&self, app: &mut App) {
-        let supported_compressed_formats = match app.world().get_resource::<RenderDevice>() {
-            Some(render_device) => CompressedImageFormats::from_features(render_device.features()),
-            None => CompressedImageFormats::NONE,
-        };
+        let custom_material = app.world().get_resource::<RMeshMaterial>().cloned();
+        let asset_server = app.world().resource::<AssetServer>().clone();
         app.register_asset_loader(RMeshLoader {
-            supported_compressed_formats,
+            custom_material,
+            asset_server,
         });
     }
 }
@@ -35,10 +86,61 @@ pub struct Room {
     pub scene: Handle<Scene>,
     pub meshes: Vec<RoomMesh>,
     pub entity_meshes: Vec<Handle<Mesh>>,
+    /// Entities as read from the file, in room space.
+    pub entities: Vec<rmesh::EntityData>,
+    /// Explicit collision meshes, in room space.
+    pub colliders: Vec<rmesh::SimpleMesh>,
+    /// Named trigger volumes, in room space.
+    pub trigger_boxes: Vec<rmesh::TriggerBox>,
+    pub diagnostics: RoomLoadDiagnostics,
+    /// Scale the room was loaded with, from room units to world units.
+    pub scale: f32,
+    /// Textured parts of each prop model by name, one per texture.
+    pub props: HashMap<String, Vec<RoomMesh>>,
+    #[cfg(feature = "navmesh")]
+    pub navmesh: Handle<RoomNavMesh>,
+}
+
+impl Room {
+    /// Transform of the first player start, in world space relative to the room.
+    pub fn player_start(&self) -> Option<Transform> {
+        self.entities
+            .iter()
+            .find_map(|entity| match &entity.entity_type {
+                Some(rmesh::EntityType::PlayerStart(data)) => {
+                    Some(RMeshPlayerStart::new(data, self.scale).transform)
+                }
+                _ => None,
+            })
+    }
 }
 
 #[derive(Asset, Debug, TypePath)]
 pub struct RoomMesh {
     pub mesh: Handle<Mesh>,
+    /// Shared with the other meshes using the same texture.
     pub material: Handle<StandardMaterial>,
+    pub lightmap: Option<Handle<Image>>,
+    /// Index of the rmesh mesh or prop part this was built from, chunks share it.
+    pub source: usize,
+    /// Bounds of the mesh, in world space relative to the room.
+    pub aabb: Aabb,
+}
+
+/// Room collision geometry, held by the [`Handle<Mesh>`] on the same entity.
+///
+/// The `rapier` and `avian` features turn it into a trimesh collider once the scene is spawned.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct RMeshCollider;
+
+/// A named trigger volume, held by the [`Handle<Mesh>`] on the same entity.
+///
+/// With a physics feature the entity also gets a sensor collider, one convex hull per volume.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct RMeshTriggerBox {
+    pub name: String,
+    /// Points of each mesh the trigger box is made of, relative to the entity.
+    pub volumes: Vec<Vec<Vec3>>,
 }
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use rmesh::{lighting, EntityLight, EntitySpotlight, ThreeTypeString};
+use serde::{Deserialize, Serialize};
+
+/// How Blitz3D lights are converted to Bevy lights.
+///
+/// Converted with [`rmesh::lighting`]: the range is in room units and scaled like positions, and
+/// the unitless intensity becomes lumens.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LightSettings {
+    /// Luminous power in lumens of a light with an intensity of 1.
+    pub lumens_per_intensity: f32,
+    /// Multiplies the light range once it's scaled to world units.
+    pub range_multiplier: f32,
+    pub shadows_enabled: bool,
+    /// Skips point and spot lights when lightmaps are loaded, the lighting is baked into them.
+    pub skip_when_lightmapped: bool,
+}
+
+impl Default for LightSettings {
+    fn default() -> Self {
+        Self {
+            lumens_per_intensity: lighting::LUMENS_PER_INTENSITY,
+            range_multiplier: 1.0,
+            shadows_enabled: true,
+            skip_when_lightmapped: false,
+        }
+    }
+}
+
+impl LightSettings {
+    fn color(color: &ThreeTypeString) -> Color {
+        let [r, g, b] = lighting::linear_color(color);
+        Color::linear_rgb(r, g, b)
+    }
+
+    pub fn point_light(&self, data: &EntityLight, scale: f32) -> PointLight {
+        PointLight {
+            range: lighting::radius(data.range, scale) * self.range_multiplier,
+            intensity: lighting::lumens(data.intensity, self.lumens_per_intensity),
+            color: Self::color(&data.color),
+            shadows_enabled: self.shadows_enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Cone angles are clamped by [`EntitySpotlight::cone_angles`].
+    pub fn spot_light(&self, data: &EntitySpotlight, scale: f32) -> SpotLight {
+        let [inner_angle, outer_angle] = data.cone_angles();
+        SpotLight {
+            range: lighting::radius(data.range, scale) * self.range_multiplier,
+            intensity: lighting::lumens(data.intensity, self.lumens_per_intensity),
+            color: Self::color(&data.color),
+            shadows_enabled: self.shadows_enabled,
+            inner_angle,
+            outer_angle,
+            ..Default::default()
+        }
+    }
+}
+
+/// Orientation of a spot light, which shines along Z in Blitz3D and along -Z in Bevy.
+///
+/// This is [`EntitySpotlight::rotation`] in the room's mirrored Z.
+pub fn spot_light_rotation(data: &EntitySpotlight) -> Quat {
+    mirrored_rotation(data.rotation())
+}
+
+/// Turns a room space rotation `[x, y, z, w]` into the mirrored Z of the room in Bevy, which
+/// flips the sign of X and Y.
+pub(crate) fn mirrored_rotation([x, y, z, w]: [f32; 4]) -> Quat {
+    Quat::from_xyzw(-x, -y, z, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::AnglesString;
+
+    fn spotlight(angles: [f32; 3]) -> EntitySpotlight {
+        EntitySpotlight {
+            position: [0.0; 3],
+            range: 1000.0,
+            color: [255, 255, 255].into(),
+            intensity: 1.0,
+            angles: AnglesString::from(angles),
+            inner_cone_angle: 30.0,
+            outer_cone_angle: 60.0,
+        }
+    }
+
+    #[test]
+    fn spot_lights_shine_along_the_mirrored_direction() {
+        for angles in [
+            [0.0; 3],
+            [30.0, 0.0, 0.0],
+            [-20.0, 135.0, 10.0],
+            [89.0, 270.0, 0.0],
+        ] {
+            let data = spotlight(angles);
+            let [x, y, z] = data.direction();
+
+            let shines = spot_light_rotation(&data) * Vec3::NEG_Z;
+
+            assert!(shines.abs_diff_eq(Vec3::new(x, y, -z), 1e-5), "{angles:?}");
+        }
+    }
+
+    #[test]
+    fn ranges_scale_and_cones_clamp() {
+        let mut data = spotlight([0.0; 3]);
+        data.outer_cone_angle = 270.0;
+        let settings = LightSettings {
+            range_multiplier: 2.0,
+            ..Default::default()
+        };
+
+        let light = settings.spot_light(&data, 0.5);
+
+        assert_eq!(light.range, lighting::radius(1000.0, 0.5) * 2.0);
+        assert_eq!(light.outer_angle, std::f32::consts::FRAC_PI_2);
+        assert_eq!(light.inner_angle, 15.0f32.to_radians());
+    }
+}
@@ -1,33 +1,120 @@
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::{Room, RoomMesh};
+use crate::diagnostics::PhaseTimer;
+use crate::{
+    spot_light_rotation, LightSettings, RMeshCollider, RMeshLight, RMeshMaterial, RMeshModel,
+    RMeshPlayerStart, RMeshScreen, RMeshSoundEmitter, RMeshSpotlight, RMeshTextures,
+    RMeshTriggerBox, RMeshWaypoint, Room, RoomLoadDiagnostics, RoomMesh,
+};
 use anyhow::Result;
-use bevy::asset::io::Reader;
+use bevy::asset::io::{ErasedAssetReader, Reader};
 use bevy::asset::AsyncReadExt;
-use bevy::asset::{AssetLoader, LoadContext};
+use bevy::asset::{AssetLoader, AssetServerMode, LoadContext};
+use bevy::pbr::Lightmap;
 use bevy::prelude::*;
 use bevy::render::primitives::Aabb;
 use bevy::render::render_asset::RenderAssetUsages;
-use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::render::texture::{
+    ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor,
+};
 use bevy::render::{
     mesh::{Indices, Mesh},
     render_resource::PrimitiveTopology,
 };
-use directx_mesh::read_directx_mesh;
-use rmesh::{read_rmesh, ExtMesh, ROOM_SCALE};
+use bevy::tasks::futures_lite::StreamExt;
+use bevy::utils::tracing::Instrument;
+use rmesh::b3d::read_b3d;
+use rmesh::convert::Scale;
+use rmesh::x::read_x;
+use rmesh::{read_rmesh, ComplexMesh, EntityModel, ExtMesh};
 use serde::{Deserialize, Serialize};
 
+/// Loads `.rmesh` rooms as a [`Room`].
+///
+/// Sub-assets are labelled `Scene`, `Mesh{i}` per room mesh, `Collision` for the merged room
+/// collision, `Collider{i}` per explicit collider, `TriggerBox{name}` per trigger box,
+/// `Trigger{name}/Mesh{i}` per trigger box volume and `ScreenMesh` and `ScreenMaterial{name}`
+/// for screens, so `asset_server.load("room.rmesh#Collider0")` works.
+/// `Material{i}` is shared between meshes and labelled with the index of the first mesh using
+/// it, [`Room::meshes`] has the handles of every mesh. Textures and lightmaps aren't labelled,
+/// they're loaded from their own files as dependencies of the room.
+/// Prop models are labelled `EntityMesh{name}` as a whole and `EntityMesh{name}/{j}` and
+/// `EntityMaterial{name}/{j}` per texture, see [`Room::props`].
 pub struct RMeshLoader {
-    pub(crate) supported_compressed_formats: CompressedImageFormats,
+    pub(crate) custom_material: Option<RMeshMaterial>,
+    /// Used to list directories when texture names don't match the files exactly.
+    pub(crate) asset_server: AssetServer,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Maps an asset path the loader is about to read to the one it should read instead.
+pub type PathResolver = Arc<dyn Fn(&Path) -> PathBuf + Send + Sync>;
+
+/// Stands out so missing textures get noticed.
+const MISSING_TEXTURE_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct RMeshLoaderSettings {
     pub load_meshes: RenderAssetUsages,
     pub load_materials: RenderAssetUsages,
     pub load_entities: bool,
     pub load_lights: bool,
+    /// Conversion of the room lights.
+    pub lights: LightSettings,
     pub load_xmeshes: bool,
+    /// Scale applied to room units.
+    pub scale: Scale,
+    /// Directory texture paths are relative to, relative to the asset root. Defaults to the
+    /// room's directory. Lightmaps are always read next to the room, see [`rmesh::lightmaps`].
+    pub texture_root: Option<String>,
+    /// Directory prop models are read from, relative to the asset root. Defaults to
+    /// `props` next to the room.
+    pub props_root: Option<String>,
+    /// Directory screen images are read from, relative to the asset root, usually
+    /// [`SCREENS_DIR`](rmesh::resolve::SCREENS_DIR) in a game directory. Defaults to the texture
+    /// root.
+    pub screens_root: Option<String>,
+    /// Extension, with an optional label, of stand-ins loaded through the asset server for props
+    /// that can't be read, e.g. `glb#Scene0` spawns `props/chair.glb#Scene0` for `props/chair.x`.
+    pub prop_stand_in: Option<String>,
+    /// Remaps every texture and prop path, after the roots are applied, before it's read.
+    #[serde(skip)]
+    pub path_resolver: Option<PathResolver>,
+    /// Spawns the room collision as an [`RMeshCollider`], labelled `Collision`.
+    pub load_colliders: bool,
+    /// Adds the visible room meshes to the `Collision` mesh. The game collides with them too,
+    /// the file itself only marks the explicit colliders and untextured meshes as collision.
+    pub collide_with_visible_meshes: bool,
+    /// Spawns everything at the top of the scene instead of under a single root entity.
+    pub flatten_hierarchy: bool,
+    /// Attaches the baked lightmap of each mesh as a [`Lightmap`], when it exists.
+    pub load_lightmaps: bool,
+    /// Brightness of the lightmaps, see [`StandardMaterial::lightmap_exposure`].
+    pub lightmap_exposure: f32,
+    /// Adds the vertex colors as [`Mesh::ATTRIBUTE_COLOR`], which [`StandardMaterial`]
+    /// multiplies into the base color. Some rooms are lit by them instead of lightmaps.
+    pub vertex_colors: bool,
+    /// Multiplies the average vertex color of each room mesh into the base color of its material
+    /// instead of adding [`Mesh::ATTRIBUTE_COLOR`], for materials ignoring the attribute. Meshes
+    /// filled with one color, see [`ComplexMesh::fill_vertex_color`], look the same either way.
+    pub premultiply_vertex_colors: bool,
+    /// Generates [`Mesh::ATTRIBUTE_TANGENT`] for room and prop meshes, needed by normal maps.
+    pub generate_tangents: bool,
+    /// Steepest slope in degrees the `NavMesh` still walks on.
+    #[cfg(feature = "navmesh")]
+    pub navmesh_max_slope: f32,
+    /// Splits room meshes into grid cells of this size in room units, so big rooms can be
+    /// frustum culled piece by piece. Chunks are labelled `Mesh{i}/{c}` instead of `Mesh{i}`.
+    pub chunk_size: Option<f32>,
+    /// Alpha tests transparent textures at this cutoff instead of blending them.
+    pub alpha_cutoff: Option<f32>,
+    /// Sampler of the room textures, repeating with linear filtering by default.
+    pub sampler: ImageSamplerDescriptor,
+    /// Width and height in room units of the quads screens are shown on. The quads face +Z,
+    /// the file doesn't store which way a screen faces.
+    pub screen_size: [f32; 2],
 }
 
 impl Default for RMeshLoaderSettings {
@@ -37,7 +124,42 @@ impl Default for RMeshLoaderSettings {
             load_materials: RenderAssetUsages::default(),
             load_entities: true,
             load_lights: true,
+            lights: LightSettings::default(),
             load_xmeshes: true,
+            scale: Scale::ROOM,
+            texture_root: None,
+            props_root: None,
+            screens_root: None,
+            prop_stand_in: None,
+            path_resolver: None,
+            load_colliders: true,
+            collide_with_visible_meshes: false,
+            flatten_hierarchy: true,
+            load_lightmaps: true,
+            lightmap_exposure: 250.0,
+            vertex_colors: false,
+            premultiply_vertex_colors: false,
+            generate_tangents: false,
+            #[cfg(feature = "navmesh")]
+            navmesh_max_slope: 45.0,
+            chunk_size: None,
+            alpha_cutoff: None,
+            sampler: ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                address_mode_w: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::linear()
+            },
+            screen_size: [32.0, 24.0],
+        }
+    }
+}
+
+impl RMeshLoaderSettings {
+    fn resolve(&self, path: PathBuf) -> PathBuf {
+        match &self.path_resolver {
+            Some(resolver) => resolver(&path),
+            None => path,
         }
     }
 }
@@ -55,7 +177,10 @@ impl AssetLoader for RMeshLoader {
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        load_rmesh(self, &bytes, load_context, settings).await
+        let span = info_span!("load_rmesh", path = %load_context.path().display());
+        load_rmesh(self, &bytes, load_context, settings)
+            .instrument(span)
+            .await
     }
 
     fn extensions(&self) -> &[&str] {
@@ -70,261 +195,1150 @@ async fn load_rmesh<'a, 'b, 'c>(
     load_context: &'b mut LoadContext<'c>,
     settings: &'b RMeshLoaderSettings,
 ) -> Result<Room> {
+    let mut timer = PhaseTimer::new();
     let header = read_rmesh(bytes)?;
+    let scale = f32::from(settings.scale);
+    check_indices(&header)?;
+    timer.finish("parse");
 
     let mut meshes = vec![];
     let mut entity_meshes = vec![];
+    let mut custom_materials = vec![];
+    let mut mesh_labels = vec![];
+    let mut missing_textures = vec![];
+    let mut skipped_entities = vec![];
+    let mut textures = HashMap::new();
+    let mut lightmaps = HashMap::new();
+    let lightmap_indices = header.lightmap_indices();
+    let mut materials = HashMap::new();
 
     for (i, complex_mesh) in header.meshes.iter().enumerate() {
-        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, settings.load_meshes);
+        // Rooms reuse textures across many meshes, load each file once
+        let diffuse_path = complex_mesh.textures[1].path.as_ref().map(String::from);
+        let base_color_texture = match &diffuse_path {
+            Some(path) if !textures.contains_key(path) => {
+                let texture = load_texture(
+                    loader,
+                    path,
+                    load_context,
+                    settings,
+                    settings.sampler.clone(),
+                )
+                .await;
+                let texture = match texture {
+                    Ok(texture) => Some(texture),
+                    Err(error) => {
+                        warn!("Missing texture {path}: {error}");
+                        missing_textures.push(path.clone());
+                        None
+                    }
+                };
+                textures.insert(path.clone(), texture.clone());
+                texture
+            }
+            Some(path) => textures[path].clone(),
+            None => None,
+        };
+        let diffuse_missing = diffuse_path.is_some() && base_color_texture.is_none();
 
-        let positions: Vec<_> = complex_mesh
-            .vertices
-            .iter()
-            .map(|v| {
-                [
-                    v.position[0] * ROOM_SCALE,
-                    v.position[1] * ROOM_SCALE,
-                    -v.position[2] * ROOM_SCALE,
-                ]
-            })
-            .collect();
-        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        let mut lightmap = None;
+        if settings.load_lightmaps {
+            if let Some(path) = complex_mesh.textures[0].path.as_ref().map(String::from) {
+                lightmap = match lightmaps.get(&path) {
+                    Some(lightmap) => Option::clone(lightmap),
+                    None => {
+                        // Lightmaps are atlases, repeating would bleed the opposite edge in
+                        let sampler = ImageSamplerDescriptor {
+                            address_mode_u: ImageAddressMode::ClampToEdge,
+                            address_mode_v: ImageAddressMode::ClampToEdge,
+                            address_mode_w: ImageAddressMode::ClampToEdge,
+                            ..settings.sampler.clone()
+                        };
+                        let candidates = rmesh::lightmaps::lightmap_candidates(
+                            load_context.path(),
+                            &path,
+                            lightmap_indices[i].unwrap_or(1),
+                        );
+                        let image =
+                            load_lightmap(loader, &candidates, load_context, settings, sampler)
+                                .await;
+                        let image = match image {
+                            Ok(image) => Some(image),
+                            Err(error) => {
+                                warn!("Missing lightmap {path}: {error}");
+                                missing_textures.push(path.clone());
+                                None
+                            }
+                        };
+                        lightmaps.insert(path, image.clone());
+                        image
+                    }
+                };
+            }
+        }
 
-        let tex_uvs: Vec<_> = complex_mesh
-            .vertices
-            .iter()
-            .map(|v| [v.tex_coords[0][0], v.tex_coords[0][1]])
-            .collect();
-        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, tex_uvs);
+        if let Some(custom_material) = &loader.custom_material {
+            let textures = RMeshTextures {
+                textures: &complex_mesh.textures,
+                lightmap: lightmap.clone(),
+                diffuse: base_color_texture.clone(),
+            };
+            let label = format!("CustomMaterial{0}", i);
+            custom_materials.push(Some(custom_material.0.add_material(
+                &textures,
+                label,
+                load_context,
+            )));
+        } else {
+            custom_materials.push(None);
+        }
 
-        let lightmaps_uvs: Vec<_> = complex_mesh
-            .vertices
-            .iter()
-            .map(|v| [v.tex_coords[1][0], v.tex_coords[1][1]])
-            .collect();
-        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, lightmaps_uvs);
+        let blend_type = complex_mesh.textures[1].blend_type;
+        let tint = if settings.premultiply_vertex_colors {
+            mean_vertex_color(complex_mesh)
+        } else {
+            [1.0; 4]
+        };
+        let key = (diffuse_path, blend_type, tint.map(f32::to_bits));
+        let material = match materials.get(&key) {
+            Some(material) => Handle::clone(material),
+            None => {
+                let material = load_context.add_labeled_asset(
+                    format!("Material{0}", i),
+                    StandardMaterial {
+                        base_color_texture,
+                        base_color: if diffuse_missing {
+                            MISSING_TEXTURE_COLOR
+                        } else {
+                            Color::LinearRgba(LinearRgba::from_f32_array(tint))
+                        },
+                        lightmap_exposure: settings.lightmap_exposure,
+                        ..blend_material(blend_type, settings)
+                    },
+                );
+                materials.insert(key, material.clone());
+                material
+            }
+        };
 
+        // Normals are computed before splitting so chunk edges stay smooth
         let normals = complex_mesh.calculate_normals();
-        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-
-        let indices = complex_mesh
-            .triangles
-            .iter()
-            .flat_map(|strip| strip.iter().rev().copied())
-            .collect();
-        mesh.insert_indices(Indices::U32(indices));
+        let chunks = match settings.chunk_size {
+            Some(size) => chunk_triangles(complex_mesh, size),
+            None => vec![complex_mesh.triangles.clone()],
+        };
+        for (c, triangles) in chunks.iter().enumerate() {
+            let label = match settings.chunk_size {
+                Some(_) => format!("Mesh{i}/{c}"),
+                None => format!("Mesh{i}"),
+            };
+            let (mesh, aabb) = room_mesh(complex_mesh, &normals, triangles, scale, settings);
+            meshes.push(RoomMesh {
+                mesh: load_context.add_labeled_asset(label.clone(), mesh),
+                material: material.clone(),
+                lightmap: lightmap.clone(),
+                source: i,
+                aabb,
+            });
+            mesh_labels.push(label);
+        }
+    }
 
-        let mesh = load_context.add_labeled_asset(format!("Mesh{0}", i), mesh);
+    timer.finish("meshes");
 
-        // TODO: double_sided and crap
-        let base_color_texture = if let Some(path) = &complex_mesh.textures[1].path {
-            let texture = load_texture(
-                &String::from(path),
-                load_context,
-                loader.supported_compressed_formats,
-                settings.load_materials,
-            )
-            .await?;
-            Some(load_context.add_labeled_asset(format!("Texture{0}", i), texture))
-        } else {
-            None
+    let mut props = HashMap::new();
+    let mut prop_stand_ins = HashMap::new();
+    if settings.load_xmeshes {
+        let props_dir = match &settings.props_root {
+            Some(path) => PathBuf::from(path),
+            None => rmesh::resolve::props_dir(load_context.path()),
         };
+        let mut prop_materials = HashMap::new();
+        for entity in &header.entities {
+            let Some(rmesh::EntityType::Model(data)) = &entity.entity_type else {
+                continue;
+            };
+            let name = String::from(&data.name);
+            if props.contains_key(&name) || prop_stand_ins.contains_key(&name) {
+                continue;
+            }
+            let candidates: Vec<_> = rmesh::resolve::model_candidates(&name, &[&props_dir])
+                .into_iter()
+                .map(|candidate| settings.resolve(candidate))
+                .collect();
+            let mut found = None;
+            for candidate in &candidates {
+                if let Ok(bytes) = load_context.read_asset_bytes(candidate.clone()).await {
+                    found = Some((candidate.clone(), bytes));
+                    break;
+                }
+            }
+            if found.is_none() {
+                if let Some(path) = find_case_insensitive(loader, load_context, &candidates).await {
+                    let bytes = load_context.read_asset_bytes(path.clone()).await;
+                    found = bytes.ok().map(|bytes| (path, bytes));
+                }
+            }
+            let model_path = match &found {
+                Some((path, _)) => path.clone(),
+                None => candidates[0].clone(),
+            };
+            let surfaces = match found {
+                Some((path, bytes)) => read_prop(&path, &bytes),
+                None => Err(anyhow::anyhow!("no file matching {}", model_path.display())),
+            };
+            let surfaces = match surfaces {
+                Ok(surfaces) => surfaces,
+                Err(error) => {
+                    // One missing prop shouldn't stop the room from loading
+                    warn!("Skipping prop {}: {error}", model_path.display());
+                    if let Some(stand_in) = &settings.prop_stand_in {
+                        let path =
+                            format!("{}.{stand_in}", model_path.with_extension("").display());
+                        prop_stand_ins.insert(name, load_context.load::<Scene>(path));
+                    } else {
+                        skipped_entities.push(format!("Model:{name}"));
+                    }
+                    continue;
+                }
+            };
 
-        let material = load_context.add_labeled_asset(
-            format!("Material{0}", i),
-            StandardMaterial {
-                base_color_texture,
-                ..Default::default()
-            },
-        );
+            let entity_mesh = prop_mesh(surfaces.iter().cloned());
+            let mesh = load_context.add_labeled_asset(format!("EntityMesh{0}", name), entity_mesh);
+            entity_meshes.push(mesh);
+
+            // Props have one surface per brush, merge the ones sharing a texture
+            let mut groups: Vec<(&rmesh::Texture, Vec<ComplexMesh>)> = vec![];
+            for surface in &surfaces {
+                let texture = &surface.textures[1];
+                match groups.iter_mut().find(|(other, _)| {
+                    other.path == texture.path && other.blend_type == texture.blend_type
+                }) {
+                    Some((_, group)) => group.push(surface.clone()),
+                    None => groups.push((texture, vec![surface.clone()])),
+                }
+            }
 
-        meshes.push(RoomMesh { mesh, material });
+            let prop_dir = model_path.parent().unwrap_or(Path::new("")).to_path_buf();
+            let mut parts = vec![];
+            for (j, (texture, group)) in groups.into_iter().enumerate() {
+                let mut mesh = prop_mesh(group);
+                if settings.generate_tangents {
+                    generate_tangents(&mut mesh);
+                }
+                let aabb = mesh.compute_aabb().unwrap_or_default();
+                let mesh = load_context.add_labeled_asset(format!("EntityMesh{name}/{j}"), mesh);
+                let diffuse_path = texture
+                    .path
+                    .as_ref()
+                    .map(|path| prop_dir.join(String::from(path).replace('\\', "/")));
+                let key = (diffuse_path.clone(), texture.blend_type);
+                let material = match prop_materials.get(&key) {
+                    Some(material) => Handle::clone(material),
+                    None => {
+                        let mut diffuse_missing = false;
+                        let base_color_texture = match &diffuse_path {
+                            Some(path) => {
+                                let image = load_texture_in(
+                                    loader,
+                                    path,
+                                    load_context,
+                                    settings,
+                                    settings.sampler.clone(),
+                                )
+                                .await;
+                                match image {
+                                    Ok(image) => Some(image),
+                                    Err(error) => {
+                                        let path = path.to_string_lossy();
+                                        warn!("Missing prop texture {path}: {error}");
+                                        missing_textures.push(path.into_owned());
+                                        diffuse_missing = true;
+                                        None
+                                    }
+                                }
+                            }
+                            None => None,
+                        };
+                        let material = load_context.add_labeled_asset(
+                            format!("EntityMaterial{name}/{j}"),
+                            StandardMaterial {
+                                base_color_texture,
+                                base_color: if diffuse_missing {
+                                    MISSING_TEXTURE_COLOR
+                                } else {
+                                    Color::WHITE
+                                },
+                                ..blend_material(texture.blend_type, settings)
+                            },
+                        );
+                        prop_materials.insert(key, material.clone());
+                        material
+                    }
+                };
+                parts.push(RoomMesh {
+                    mesh,
+                    material,
+                    lightmap: None,
+                    source: j,
+                    aabb,
+                });
+            }
+            props.insert(name, parts);
+        }
     }
 
-    // TODO: add setting if we want to load models with "x"
-    if settings.load_xmeshes {
+    timer.finish("props");
+
+    // Screen images fall back on other extensions and case like room textures
+    let mut screen_materials = HashMap::new();
+    if settings.load_entities {
         for entity in &header.entities {
-            if let Some(rmesh::EntityType::Model(data)) = &entity.entity_type {
-                let name = &String::from(data.name.clone());
-                let parent = load_context.path().parent().unwrap();
-                let image_path = parent.join("props").join(name);
-                let bytes = load_context.read_asset_bytes(image_path.clone()).await?;
-                let content =
-                    std::str::from_utf8(&bytes)?;
-
-                let mesh = load_context
-                    .add_labeled_asset(format!("EntityMesh{0}", name), load_x_mesh(content)?);
-                entity_meshes.push(mesh);
+            let Some(rmesh::EntityType::Screen(data)) = &entity.entity_type else {
+                continue;
+            };
+            let name = String::from(&data.name);
+            if screen_materials.contains_key(&name) {
+                continue;
             }
+            let root = match &settings.screens_root {
+                Some(root) => PathBuf::from(root),
+                None => texture_root(load_context, settings),
+            };
+            let candidates = rmesh::resolve::screen_candidates(&name, &[root]);
+            let image = load_texture_in(
+                loader,
+                &candidates[0],
+                load_context,
+                settings,
+                settings.sampler.clone(),
+            )
+            .await;
+            let base_color_texture = match image {
+                Ok(image) => Some(image),
+                Err(error) => {
+                    warn!("Missing screen image {name}: {error}");
+                    missing_textures.push(name.clone());
+                    None
+                }
+            };
+            let material = load_context.add_labeled_asset(
+                format!("ScreenMaterial{0}", name),
+                StandardMaterial {
+                    base_color: if base_color_texture.is_some() {
+                        Color::WHITE
+                    } else {
+                        MISSING_TEXTURE_COLOR
+                    },
+                    base_color_texture,
+                    unlit: true,
+                    double_sided: true,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+            );
+            screen_materials.insert(name, material);
+        }
+        if !screen_materials.is_empty() {
+            let [width, height] = settings.screen_size;
+            let quad = Rectangle::new(width * scale, height * scale);
+            load_context.add_labeled_asset("ScreenMesh".to_string(), Mesh::from(quad));
         }
     }
 
+    timer.finish("screens");
+
+    #[cfg(feature = "navmesh")]
+    let navmesh = {
+        let navmesh = crate::navmesh::build_navmesh(&header, scale, settings.navmesh_max_slope);
+        load_context.add_labeled_asset("NavMesh".to_string(), navmesh)
+    };
+
+    if settings.load_colliders {
+        load_context.add_labeled_asset(
+            "Collision".to_string(),
+            collision_mesh(&header, scale, settings.collide_with_visible_meshes),
+        );
+        for (i, collider) in header.colliders.iter().enumerate() {
+            let surface = (collider.vertices.clone(), collider.triangles.as_slice());
+            let mesh = physics_mesh(std::iter::once(surface), scale);
+            load_context.add_labeled_asset(format!("Collider{0}", i), mesh);
+        }
+    }
+    if settings.load_entities {
+        for trigger_box in &header.trigger_boxes {
+            let name = String::from(&trigger_box.name);
+            let mesh = trigger_box_mesh(trigger_box, scale);
+            load_context.add_labeled_asset(format!("TriggerBox{0}", name), mesh);
+            // The volumes on their own, for custom physics
+            for (i, volume) in trigger_box.meshes.iter().enumerate() {
+                let surface = (volume.vertices.clone(), volume.triangles.as_slice());
+                let mesh = physics_mesh(std::iter::once(surface), scale);
+                load_context.add_labeled_asset(format!("Trigger{name}/Mesh{i}"), mesh);
+            }
+        }
+    }
+
+    timer.finish("collision");
+
     let scene = {
+        let _span = info_span!("build_scene").entered();
         let mut world = World::default();
         let mut scene_load_context = load_context.begin_labeled_asset();
 
-        world
+        let root = world
             .spawn(SpatialBundle::INHERITED_IDENTITY)
             .with_children(|parent| {
+                if settings.load_colliders {
+                    parent.spawn((
+                        scene_load_context.get_label_handle::<Mesh>("Collision"),
+                        SpatialBundle::HIDDEN_IDENTITY,
+                        RMeshCollider,
+                        Name::new("Collision"),
+                    ));
+                }
                 if settings.load_entities {
-                    for i in 0..header.meshes.len() {
-                        let mesh_label = format!("Mesh{0}", i);
-                        let mat_label = format!("Material{0}", i);
+                    for (room_mesh, label) in meshes.iter().zip(&mesh_labels) {
+                        let complex_mesh = &header.meshes[room_mesh.source];
                         let mut mesh_entity = parent.spawn(PbrBundle {
-                            mesh: scene_load_context.get_label_handle(&mesh_label),
-                            material: scene_load_context.get_label_handle(&mat_label),
+                            mesh: room_mesh.mesh.clone(),
+                            material: room_mesh.material.clone(),
+                            // Untextured meshes are invisible collision
+                            visibility: if complex_mesh.is_invisible() {
+                                Visibility::Hidden
+                            } else {
+                                Visibility::Inherited
+                            },
                             ..Default::default()
                         });
-                        let complex_mesh = &header.meshes[i];
-                        let bounds = complex_mesh.bounding_box();
-                        mesh_entity.insert(Aabb::from_min_max(
-                            Vec3::from_slice(&bounds.min),
-                            Vec3::from_slice(&bounds.max),
+                        mesh_entity.insert(Name::new(label.clone()));
+                        if let Some(insert_material) = &custom_materials[room_mesh.source] {
+                            insert_material(&mut mesh_entity);
+                        }
+                        if let Some(lightmap) = &room_mesh.lightmap {
+                            mesh_entity.insert(Lightmap {
+                                image: lightmap.clone(),
+                                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+                            });
+                        }
+                        mesh_entity.insert(room_mesh.aabb);
+                    }
+                    let translation = |position: [f32; 3]| {
+                        Vec3::new(position[0], position[1], -position[2]) * scale
+                    };
+                    for trigger_box in &header.trigger_boxes {
+                        let name = String::from(&trigger_box.name);
+                        let bounds = trigger_box.bounding_box();
+                        parent.spawn((
+                            scene_load_context
+                                .get_label_handle::<Mesh>(format!("TriggerBox{0}", name)),
+                            SpatialBundle::HIDDEN_IDENTITY,
+                            Aabb::from_min_max(
+                                Vec3::new(bounds.min[0], bounds.min[1], -bounds.max[2]) * scale,
+                                Vec3::new(bounds.max[0], bounds.max[1], -bounds.min[2]) * scale,
+                            ),
+                            Name::new(format!("TriggerBox:{0}", name)),
+                            RMeshTriggerBox {
+                                name,
+                                volumes: trigger_box
+                                    .meshes
+                                    .iter()
+                                    .map(|mesh| {
+                                        mesh.vertices.iter().map(|p| translation(*p)).collect()
+                                    })
+                                    .collect(),
+                            },
                         ));
                     }
-                    for entity in header.entities {
-                        if let Some(entity_type) = entity.entity_type {
+                    let marker = |position: [f32; 3]| {
+                        SpatialBundle::from_transform(Transform::from_translation(translation(
+                            position,
+                        )))
+                    };
+                    let load_lights = settings.load_lights
+                        && !(settings.load_lightmaps && settings.lights.skip_when_lightmapped);
+                    let mut counts = HashMap::new();
+                    for entity in &header.entities {
+                        if let Some(entity_type) = &entity.entity_type {
+                            let count = counts.entry(entity_type.name()).or_insert(0);
+                            let name = entity_name(entity_type, *count);
+                            *count += 1;
                             match entity_type {
                                 rmesh::EntityType::Light(data) => {
-                                    if !settings.load_lights {
-                                        return;
+                                    if !load_lights {
+                                        skipped_entities.push(name.as_str().to_string());
+                                        continue;
                                     }
 
-                                    parent.spawn(PointLightBundle {
-                                        transform: Transform::from_translation(Vec3::new(
-                                            data.position[0] * ROOM_SCALE,
-                                            data.position[1] * ROOM_SCALE,
-                                            -data.position[2] * ROOM_SCALE,
-                                        )),
-                                        point_light: PointLight {
-                                            range: data.range,
-                                            shadows_enabled: true,
-                                            intensity: (data.intensity * 0.8).min(1.) * 60_00.,
-                                            color: Color::srgb_u8(
-                                                data.color.0[0],
-                                                data.color.0[1],
-                                                data.color.0[2],
-                                            ),
+                                    parent.spawn((
+                                        PointLightBundle {
+                                            transform: Transform::from_translation(translation(
+                                                data.position,
+                                            )),
+                                            point_light: settings.lights.point_light(data, scale),
                                             ..Default::default()
                                         },
-                                        ..Default::default()
-                                    });
+                                        RMeshLight::from(data),
+                                        name,
+                                    ));
                                 }
                                 rmesh::EntityType::SpotLight(data) => {
-                                    if !settings.load_lights {
-                                        return;
+                                    if !load_lights {
+                                        skipped_entities.push(name.as_str().to_string());
+                                        continue;
                                     }
 
-                                    parent.spawn(SpotLightBundle {
-                                        transform: Transform::from_translation(Vec3::new(
-                                            data.position[0] * ROOM_SCALE,
-                                            data.position[1] * ROOM_SCALE,
-                                            -data.position[2] * ROOM_SCALE,
-                                        )),
-                                        spot_light: SpotLight {
-                                            range: data.range,
-                                            shadows_enabled: true,
-                                            intensity: (data.intensity * 0.8).min(1.) * 60_00.,
-                                            color: Color::srgb_u8(
-                                                data.color.0[0],
-                                                data.color.0[1],
-                                                data.color.0[2],
-                                            ),
-                                            inner_angle: data.inner_cone_angle,
-                                            outer_angle: data.outer_cone_angle,
+                                    parent.spawn((
+                                        SpotLightBundle {
+                                            transform: Transform {
+                                                translation: translation(data.position),
+                                                rotation: spot_light_rotation(data),
+                                                ..Default::default()
+                                            },
+                                            spot_light: settings.lights.spot_light(data, scale),
                                             ..Default::default()
                                         },
-                                        ..Default::default()
-                                    });
+                                        RMeshSpotlight::from(data),
+                                        name,
+                                    ));
                                 }
                                 rmesh::EntityType::Model(data) => {
-                                    let name = &String::from(data.name.clone());
-                                    let mesh_label = format!("EntityMesh{0}", name);
-
-                                    parent.spawn(PbrBundle {
-                                        transform: Transform {
-                                            translation: (
-                                                data.position[0] * ROOM_SCALE,
-                                                data.position[1] * ROOM_SCALE,
-                                                -data.position[2] * ROOM_SCALE,
-                                            )
-                                                .into(),
-                                            rotation: Quat::from_euler(
-                                                EulerRot::XYZ,
-                                                data.rotation[0],
-                                                data.rotation[1],
-                                                data.rotation[2],
-                                            ),
-                                            scale: (
-                                                data.scale[0] * ROOM_SCALE,
-                                                -data.scale[1] * ROOM_SCALE,
-                                                data.scale[2] * ROOM_SCALE,
-                                            )
-                                                .into(),
+                                    let model = RMeshModel::from(data);
+                                    let mut model_entity = parent.spawn((
+                                        SpatialBundle::from_transform(model_transform(data, scale)),
+                                        name,
+                                    ));
+                                    if let Some(parts) = props.get(&model.name) {
+                                        model_entity.with_children(|model_entity| {
+                                            for (j, part) in parts.iter().enumerate() {
+                                                model_entity.spawn((
+                                                    PbrBundle {
+                                                        mesh: part.mesh.clone(),
+                                                        material: part.material.clone(),
+                                                        ..Default::default()
+                                                    },
+                                                    Name::new(format!("Part{j}")),
+                                                ));
+                                            }
+                                        });
+                                    } else if let Some(scene) = prop_stand_ins.get(&model.name) {
+                                        model_entity.with_children(|model_entity| {
+                                            model_entity.spawn(SceneBundle {
+                                                scene: scene.clone(),
+                                                ..Default::default()
+                                            });
+                                        });
+                                    }
+                                    model_entity.insert(model);
+                                }
+                                rmesh::EntityType::Screen(data) => {
+                                    let screen = RMeshScreen::from(data);
+                                    parent.spawn((
+                                        PbrBundle {
+                                            transform: Transform::from_translation(translation(
+                                                data.position,
+                                            )),
+                                            mesh: scene_load_context.get_label_handle("ScreenMesh"),
+                                            material: screen_materials[&screen.name].clone(),
+                                            ..Default::default()
                                         },
-                                        mesh: scene_load_context.get_label_handle(&mesh_label),
-                                        ..Default::default()
-                                    });
+                                        screen,
+                                        name,
+                                    ));
+                                }
+                                rmesh::EntityType::WayPoint(data) => {
+                                    parent.spawn((
+                                        marker(data.position),
+                                        RMeshWaypoint::from(data),
+                                        name,
+                                    ));
+                                }
+                                rmesh::EntityType::SoundEmitter(data) => {
+                                    parent.spawn((
+                                        marker(data.position),
+                                        RMeshSoundEmitter::from(data),
+                                        name,
+                                    ));
+                                }
+                                rmesh::EntityType::PlayerStart(data) => {
+                                    let start = RMeshPlayerStart::new(data, scale);
+                                    parent.spawn((
+                                        SpatialBundle::from_transform(start.transform),
+                                        start,
+                                        name,
+                                    ));
                                 }
-                                _ => (),
                             }
                         }
                     }
                 }
-            });
+            })
+            .id();
+
+        if settings.flatten_hierarchy {
+            let children = world
+                .get::<Children>(root)
+                .map(|children| children.to_vec())
+                .unwrap_or_default();
+            for child in children {
+                world.entity_mut(child).remove_parent();
+            }
+            world.despawn(root);
+        }
 
         let loaded_scene = scene_load_context.finish(Scene::new(world), None);
         load_context.add_loaded_labeled_asset("Scene", loaded_scene)
     };
 
+    timer.finish("scene");
+
     Ok(Room {
         scene,
         entity_meshes,
         meshes,
+        entities: header.entities,
+        colliders: header.colliders,
+        trigger_boxes: header.trigger_boxes,
+        diagnostics: RoomLoadDiagnostics {
+            missing_textures,
+            skipped_entities,
+            timings: timer.into_timings(),
+            ..Default::default()
+        },
+        scale,
+        props,
+        #[cfg(feature = "navmesh")]
+        navmesh,
     })
 }
 
-/// Loads an entire x file.
-fn load_x_mesh<'a>(content: &'a str) -> Result<Mesh> {
-    let header = read_directx_mesh(content)?;
+/// Fails on room meshes with triangles out of range, building the render meshes looks their
+/// vertices up by index.
+fn check_indices(header: &rmesh::Header) -> Result<()> {
+    for (i, complex_mesh) in header.meshes.iter().enumerate() {
+        complex_mesh
+            .check_indices()
+            .map_err(|error| anyhow::anyhow!("Room mesh {i}: {error}"))?;
+    }
+    Ok(())
+}
+
+/// Material defaults for the blend type of a mesh's diffuse texture.
+fn blend_material(
+    blend_type: rmesh::TextureBlendType,
+    settings: &RMeshLoaderSettings,
+) -> StandardMaterial {
+    match blend_type {
+        // Glass and fences are seen from both sides
+        rmesh::TextureBlendType::Transparent => StandardMaterial {
+            alpha_mode: match settings.alpha_cutoff {
+                Some(cutoff) => AlphaMode::Mask(cutoff),
+                None => AlphaMode::Blend,
+            },
+            double_sided: true,
+            cull_mode: None,
+            ..Default::default()
+        },
+        _ => StandardMaterial::default(),
+    }
+}
+
+/// Builds the render mesh of some triangles of a room mesh, keeping only the vertices they use.
+fn room_mesh(
+    complex_mesh: &ComplexMesh,
+    normals: &[[f32; 3]],
+    triangles: &[[u32; 3]],
+    scale: f32,
+    settings: &RMeshLoaderSettings,
+) -> (Mesh, Aabb) {
+    let mut remap = vec![u32::MAX; complex_mesh.vertices.len()];
+    let mut used = vec![];
+    let mut indices = vec![];
+    for triangle in triangles {
+        // Reversed for the mirrored Z
+        for &index in triangle.iter().rev() {
+            if remap[index as usize] == u32::MAX {
+                remap[index as usize] = used.len() as u32;
+                used.push(index as usize);
+            }
+            indices.push(remap[index as usize]);
+        }
+    }
+
+    let positions: Vec<_> = used
+        .iter()
+        .map(|&i| {
+            let [x, y, z] = complex_mesh.vertices[i].position;
+            [x * scale, y * scale, -z * scale]
+        })
+        .collect();
+    let aabb =
+        Aabb::enclosing(positions.iter().map(|&position| Vec3::from(position))).unwrap_or_default();
+    let tex_uvs: Vec<_> = used
+        .iter()
+        .map(|&i| complex_mesh.vertices[i].tex_coords[0])
+        .collect();
+    let lightmap_uvs: Vec<_> = used
+        .iter()
+        .map(|&i| complex_mesh.vertices[i].tex_coords[1])
+        .collect();
+    let normals: Vec<_> = used.iter().map(|&i| normals[i]).collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, settings.load_meshes);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, tex_uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, lightmap_uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    if settings.vertex_colors && !settings.premultiply_vertex_colors {
+        let colors: Vec<_> = used
+            .iter()
+            .map(|&i| complex_mesh.vertices[i].linear_color())
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    mesh.insert_indices(compact_indices(indices, used.len()));
+    if settings.generate_tangents {
+        generate_tangents(&mut mesh);
+    }
+    (mesh, aabb)
+}
+
+/// Average linear color of the vertices, white without any.
+fn mean_vertex_color(complex_mesh: &ComplexMesh) -> [f32; 4] {
+    if complex_mesh.vertices.is_empty() {
+        return [1.0; 4];
+    }
+    let sum = complex_mesh
+        .vertices
+        .iter()
+        .map(|vertex| vertex.linear_color())
+        .fold([0.0; 4], |sum, color| {
+            [0, 1, 2, 3].map(|c| sum[c] + color[c])
+        });
+    sum.map(|c| c / complex_mesh.vertices.len() as f32)
+}
+
+/// Uses 16 bit indices when the mesh has few enough vertices, halving the index buffer.
+fn compact_indices(indices: Vec<u32>, vertex_count: usize) -> Indices {
+    if vertex_count <= u16::MAX as usize {
+        Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+    } else {
+        Indices::U32(indices)
+    }
+}
+
+/// Adds tangents for normal mapped materials, which silently render wrong without them.
+fn generate_tangents(mesh: &mut Mesh) {
+    if let Err(error) = mesh.generate_tangents() {
+        warn!("Couldn't generate tangents: {error}");
+    }
+}
+
+/// Groups the triangles of a mesh by the grid cell of `size` their centroid falls in.
+fn chunk_triangles(complex_mesh: &ComplexMesh, size: f32) -> Vec<Vec<[u32; 3]>> {
+    let mut cells = BTreeMap::<[i32; 3], Vec<[u32; 3]>>::new();
+    for triangle in &complex_mesh.triangles {
+        let centroid = [0, 1, 2].map(|axis| {
+            triangle
+                .iter()
+                .map(|&i| complex_mesh.vertices[i as usize].position[axis])
+                .sum::<f32>()
+                / 3.0
+        });
+        let cell = centroid.map(|v| (v / size).floor() as i32);
+        cells.entry(cell).or_default().push(*triangle);
+    }
+    cells.into_values().collect()
+}
+
+/// Stable `Name` for a room entity, `index` counting the entities of its type.
+fn entity_name(entity_type: &rmesh::EntityType, index: usize) -> Name {
+    match entity_type {
+        rmesh::EntityType::Screen(data) => {
+            Name::new(format!("Screen:{}", String::from(&data.name)))
+        }
+        rmesh::EntityType::Model(data) => Name::new(format!("Model:{}", String::from(&data.name))),
+        rmesh::EntityType::Light(_) => Name::new(format!("Light{index}")),
+        rmesh::EntityType::SpotLight(_) => Name::new(format!("Spotlight{index}")),
+        rmesh::EntityType::WayPoint(_) => Name::new(format!("Waypoint{index}")),
+        rmesh::EntityType::SoundEmitter(_) => Name::new(format!("SoundEmitter{index}")),
+        rmesh::EntityType::PlayerStart(_) => Name::new(format!("PlayerStart{index}")),
+    }
+}
+
+/// Merges everything the player collides with into one mesh kept in the main world.
+///
+/// That's the [`collision_surfaces`](rmesh::Header::collision_surfaces) of the room, the explicit
+/// colliders and untextured meshes, plus the visible meshes when `visible` is set.
+fn collision_mesh(header: &rmesh::Header, scale: f32, visible: bool) -> Mesh {
+    let surfaces = header.collision_surfaces();
+    let surfaces = surfaces
+        .iter()
+        .map(|surface| (surface.positions(), surface.triangles()));
+    let visible_meshes = header
+        .meshes
+        .iter()
+        .filter(|mesh| visible && !mesh.is_invisible())
+        .map(|mesh| (mesh.positions(), mesh.triangles.as_slice()));
+    physics_mesh(surfaces.chain(visible_meshes), scale)
+}
+
+/// Merges the volumes of a trigger box into one mesh kept in the main world.
+fn trigger_box_mesh(trigger_box: &rmesh::TriggerBox, scale: f32) -> Mesh {
+    let meshes = trigger_box
+        .meshes
+        .iter()
+        .map(|mesh| (mesh.vertices.clone(), mesh.triangles.as_slice()));
+    physics_mesh(meshes, scale)
+}
+
+/// Position only mesh for physics, never uploaded to the GPU.
+///
+/// Triangles referencing a vertex out of range are left out, the physics engines assume they
+/// don't exist.
+fn physics_mesh<'a>(
+    surfaces: impl Iterator<Item = (Vec<[f32; 3]>, &'a [[u32; 3]])>,
+    scale: f32,
+) -> Mesh {
+    let mut positions = vec![];
+    let mut indices = vec![];
+    for (surface_positions, triangles) in surfaces {
+        let offset = positions.len() as u32;
+        let vertex_count = surface_positions.len() as u32;
+        positions.extend(
+            surface_positions
+                .into_iter()
+                .map(|[x, y, z]| [x * scale, y * scale, -z * scale]),
+        );
+        indices.extend(
+            triangles
+                .iter()
+                .filter(|triangle| triangle.iter().all(|i| *i < vertex_count))
+                .flat_map(|triangle| triangle.iter().rev().map(|i| i + offset)),
+        );
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh
+}
+
+/// Places a prop model mesh loaded from the room's `EntityMesh` labels.
+///
+/// Prop meshes are stored with Y negated, a half turn around X brings them into the room's
+/// mirrored Z. Blitz3D applies yaw, then pitch, then roll, in degrees.
+pub fn model_transform(model: &EntityModel, scale: f32) -> Transform {
+    let [x, y, z] = model.position;
+    let [pitch, yaw, roll] = model.rotation.map(f32::to_radians);
+    Transform {
+        translation: Vec3::new(x, y, -z) * scale,
+        rotation: Quat::from_euler(EulerRot::YXZ, -yaw, -pitch, roll) * Quat::from_rotation_x(PI),
+        scale: Vec3::from(model.scale) * scale,
+    }
+}
+
+/// Reads a prop model, b3d or any flavour of x, as one mesh per surface.
+fn read_prop(path: &Path, bytes: &[u8]) -> Result<Vec<ComplexMesh>> {
+    let is_b3d = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("b3d"));
+    let surfaces = if is_b3d {
+        read_b3d(bytes)?.to_complex_meshes()
+    } else {
+        read_x(bytes)?.to_complex_meshes()?
+    };
+    for surface in &surfaces {
+        surface.check_indices()?;
+    }
+    Ok(surfaces)
+}
+
+/// Merges the surfaces of a prop model into a single mesh.
+fn prop_mesh(complex_meshes: impl IntoIterator<Item = ComplexMesh>) -> Mesh {
+    let mut merged = ComplexMesh::default();
+    for complex_mesh in complex_meshes {
+        merged.append(complex_mesh);
+    }
 
     let mut mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
         RenderAssetUsages::default(),
     );
 
-    let positions: Vec<_> = header.vertices.iter().map(|v| [v.0, -v.1, v.2]).collect();
+    // Mirrored like the x meshes, see `model_transform`
+    let positions: Vec<_> = merged
+        .vertices
+        .iter()
+        .map(|v| [v.position[0], -v.position[1], v.position[2]])
+        .collect();
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
 
-    let indices: Vec<u32> = header.faces.iter().flatten().cloned().collect();
-    mesh.insert_indices(Indices::U32(indices));
+    let tex_uvs = merged.uv0();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, tex_uvs);
 
-    let normals: Vec<_> = header.normals.iter().map(|v| [v.0, v.1, v.2]).collect();
+    let normals: Vec<_> = merged
+        .calculate_normals()
+        .into_iter()
+        .map(|n| [n[0], -n[1], n[2]])
+        .collect();
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
 
-    Ok(mesh)
+    let indices: Vec<u32> = merged.triangles.iter().flatten().copied().collect();
+    mesh.insert_indices(compact_indices(indices, merged.vertices.len()));
+
+    mesh
 }
 
-async fn load_texture<'a>(
+/// Loads a texture from the texture root.
+///
+/// Rooms often name textures with the wrong case or extension, so other common extensions and
+/// case-insensitive matches in the directory are tried as well. The file found is loaded as a
+/// dependency through the image loader, so editing it hot reloads the image on its own.
+async fn load_texture(
+    loader: &RMeshLoader,
     path: &str,
-    load_context: &mut LoadContext<'a>,
-    supported_compressed_formats: CompressedImageFormats,
-    render_asset_usages: RenderAssetUsages,
-) -> Result<Image> {
-    let parent = load_context.path().parent().unwrap();
-    let image_path = parent.join(path);
-    let bytes = load_context.read_asset_bytes(image_path.clone()).await?;
-
-    let extension = Path::new(path).extension().unwrap().to_str().unwrap();
-    let image_type = ImageType::Extension(extension);
-
-    Ok(Image::from_buffer(
-        &bytes,
-        image_type,
-        supported_compressed_formats,
-        true,
-        ImageSampler::Default,
-        render_asset_usages,
-    )?)
+    load_context: &mut LoadContext<'_>,
+    settings: &RMeshLoaderSettings,
+    sampler: ImageSamplerDescriptor,
+) -> Result<Handle<Image>> {
+    let path = texture_root(load_context, settings).join(rmesh::resolve::normalize(path));
+    load_texture_in(loader, &path, load_context, settings, sampler).await
+}
+
+/// Loads the first of the lightmap candidates that exists.
+async fn load_lightmap(
+    loader: &RMeshLoader,
+    candidates: &[PathBuf],
+    load_context: &mut LoadContext<'_>,
+    settings: &RMeshLoaderSettings,
+    sampler: ImageSamplerDescriptor,
+) -> Result<Handle<Image>> {
+    let mut first_error = None;
+    for candidate in candidates {
+        let image = load_texture_in(loader, candidate, load_context, settings, sampler.clone());
+        match image.await {
+            Ok(image) => return Ok(image),
+            // Reports the path as stored
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| anyhow::anyhow!("no lightmap candidates")))
+}
+
+fn texture_root(load_context: &LoadContext<'_>, settings: &RMeshLoaderSettings) -> PathBuf {
+    match &settings.texture_root {
+        Some(root) => PathBuf::from(root),
+        None => load_context.path().parent().unwrap().to_path_buf(),
+    }
+}
+
+/// Loads a texture at `path`, relative to the asset root, see [`load_texture`].
+async fn load_texture_in(
+    loader: &RMeshLoader,
+    path: &Path,
+    load_context: &mut LoadContext<'_>,
+    settings: &RMeshLoaderSettings,
+    sampler: ImageSamplerDescriptor,
+) -> Result<Handle<Image>> {
+    let mut candidates = vec![settings.resolve(path.to_path_buf())];
+    for extension in ["jpg", "png", "dds"] {
+        let alternate = settings.resolve(path.with_extension(extension));
+        if !candidates.contains(&alternate) {
+            candidates.push(alternate);
+        }
+    }
+
+    let reader = asset_reader(loader, load_context)?;
+    let mut found = None;
+    for candidate in &candidates {
+        // Only checks the file is there, reading it here would reload the whole room on edits
+        if reader.read(candidate).await.is_ok() {
+            found = Some(candidate.clone());
+            break;
+        }
+    }
+    if found.is_none() {
+        found = find_case_insensitive(loader, load_context, &candidates).await;
+    }
+    let Some(found_path) = found else {
+        anyhow::bail!("no file matching {}", candidates[0].display());
+    };
+
+    let asset_usage = settings.load_materials;
+    Ok(load_context
+        .loader()
+        .with_settings(move |image_settings: &mut ImageLoaderSettings| {
+            image_settings.sampler = ImageSampler::Descriptor(sampler.clone());
+            image_settings.asset_usage = asset_usage;
+        })
+        .load(found_path))
+}
+
+/// The reader the asset server loads through, so probes see the same files the image loader
+/// will: processed assets in [`AssetServerMode::Processed`], when the source has them.
+fn asset_reader<'a>(
+    loader: &'a RMeshLoader,
+    load_context: &LoadContext<'_>,
+) -> Result<&'a dyn ErasedAssetReader> {
+    let source = loader
+        .asset_server
+        .get_source(load_context.asset_path().source().clone_owned())?;
+    Ok(match loader.asset_server.mode() {
+        AssetServerMode::Processed => source.processed_reader().unwrap_or(source.reader()),
+        AssetServerMode::Unprocessed => source.reader(),
+    })
+}
+
+/// Lists the directory of the candidates and returns the first entry matching any of them,
+/// ignoring case.
+async fn find_case_insensitive(
+    loader: &RMeshLoader,
+    load_context: &LoadContext<'_>,
+    candidates: &[PathBuf],
+) -> Option<PathBuf> {
+    let reader = asset_reader(loader, load_context).ok()?;
+    let dir = candidates[0].parent().unwrap_or(Path::new(""));
+    let mut entries = reader.read_directory(dir).await.ok()?;
+    while let Some(entry) = entries.next().await {
+        let Some(name) = entry.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let matches = candidates.iter().any(|candidate| {
+            candidate
+                .file_name()
+                .and_then(|candidate| candidate.to_str())
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+        });
+        if matches {
+            return Some(entry);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::Vec3A;
+    use bevy::render::mesh::VertexAttributeValues;
+    use rmesh::{Header, Vertex};
+
+    /// Two floor tiles 10 units apart along X.
+    fn tiles() -> ComplexMesh {
+        let vertex = |x: f32, z: f32| Vertex {
+            position: [x, 0.0, z],
+            color: [255, 128, 0],
+            ..Default::default()
+        };
+        ComplexMesh {
+            vertices: [0.0, 10.0]
+                .into_iter()
+                .flat_map(|x| {
+                    [
+                        vertex(x, 0.0),
+                        vertex(x + 1.0, 0.0),
+                        vertex(x + 1.0, 1.0),
+                        vertex(x, 1.0),
+                    ]
+                })
+                .collect(),
+            triangles: vec![[0, 1, 2], [0, 2, 3], [4, 5, 6], [4, 6, 7]],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn chunks_keep_only_their_vertices() {
+        let mesh = tiles();
+
+        let chunks = chunk_triangles(&mesh, 8.0);
+        assert_eq!(
+            chunks,
+            [vec![[0, 1, 2], [0, 2, 3]], vec![[4, 5, 6], [4, 6, 7]]]
+        );
+
+        let normals = mesh.calculate_normals();
+        let settings = RMeshLoaderSettings::default();
+        let (far, aabb) = room_mesh(&mesh, &normals, &chunks[1], 2.0, &settings);
+        assert_eq!(far.count_vertices(), 4);
+        assert!(matches!(far.indices(), Some(Indices::U16(_))));
+        // Mirrored along Z and scaled
+        assert_eq!(aabb.min(), Vec3A::new(20.0, 0.0, -2.0));
+        assert_eq!(aabb.max(), Vec3A::new(22.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn out_of_range_triangles_fail_the_load() {
+        let mut header = Header {
+            meshes: vec![tiles(), tiles()],
+            ..Default::default()
+        };
+        assert!(check_indices(&header).is_ok());
+
+        header.meshes[1].triangles.push([4, 5, 8]);
+        let error = check_indices(&header).unwrap_err();
+        assert!(error.to_string().starts_with("Room mesh 1"), "{error}");
+    }
+
+    #[test]
+    fn collision_comes_from_the_collision_surfaces() {
+        let invisible = ComplexMesh {
+            vertices: vec![Vertex::default(); 3],
+            triangles: vec![[0, 1, 2]],
+            ..Default::default()
+        };
+        let mut visible = tiles();
+        visible.textures[1].blend_type = rmesh::TextureBlendType::Visible;
+        let collider = rmesh::SimpleMesh::new(vec![[0.0; 3]; 4], vec![[0, 1, 2], [1, 2, 4]]);
+        let header = Header {
+            meshes: vec![visible, invisible],
+            colliders: vec![collider],
+            ..Default::default()
+        };
+
+        // The broken collider triangle is dropped
+        let mesh = collision_mesh(&header, 1.0, false);
+        assert_eq!(mesh.count_vertices(), 3 + 4);
+        assert_eq!(mesh.indices().unwrap().len(), 3 + 3);
+
+        let mesh = collision_mesh(&header, 1.0, true);
+        assert_eq!(mesh.count_vertices(), 3 + 4 + 8);
+        assert_eq!(mesh.indices().unwrap().len(), 3 + 3 + 12);
+    }
+
+    #[test]
+    fn vertex_colors_match_the_core_conversion() {
+        let mesh = tiles();
+        let normals = mesh.calculate_normals();
+        let linear = rmesh::color::color_to_linear([255, 128, 0]);
+
+        let settings = RMeshLoaderSettings {
+            vertex_colors: true,
+            ..Default::default()
+        };
+        let (colored, _) = room_mesh(&mesh, &normals, &mesh.triangles, 1.0, &settings);
+        let Some(VertexAttributeValues::Float32x4(colors)) =
+            colored.attribute(Mesh::ATTRIBUTE_COLOR)
+        else {
+            panic!("no vertex colors");
+        };
+        assert!(colors.iter().all(|color| *color == linear));
+
+        // Premultiplied colors go into the material instead
+        let settings = RMeshLoaderSettings {
+            premultiply_vertex_colors: true,
+            ..settings
+        };
+        let (plain, _) = room_mesh(&mesh, &normals, &mesh.triangles, 1.0, &settings);
+        assert!(plain.attribute(Mesh::ATTRIBUTE_COLOR).is_none());
+        let mean = mean_vertex_color(&mesh);
+        assert!(mean.iter().zip(linear).all(|(a, b)| (a - b).abs() < 1e-6));
+        assert_eq!(mean_vertex_color(&ComplexMesh::default()), [1.0; 4]);
+    }
 }
@@ -0,0 +1,67 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use bevy::asset::LoadContext;
+use bevy::prelude::*;
+
+/// Textures of a room mesh, handed to a [`RMeshMaterial`] builder.
+pub struct RMeshTextures<'a> {
+    /// Lightmap and diffuse texture as stored in the file.
+    pub textures: &'a [rmesh::Texture; 2],
+    /// Loaded lightmap, when lightmaps are enabled.
+    pub lightmap: Option<Handle<Image>>,
+    /// Loaded diffuse texture.
+    pub diffuse: Option<Handle<Image>>,
+}
+
+/// Replaces the [`StandardMaterial`] of room meshes in spawned scenes with a custom material.
+///
+/// Insert it as a resource before the app finishes building, next to the [`MaterialPlugin`] of
+/// the material. The custom materials are labelled `CustomMaterial{i}`, `Material{i}` stays a
+/// [`StandardMaterial`].
+#[derive(Resource, Clone)]
+pub struct RMeshMaterial(pub(crate) Arc<dyn MaterialBuilder>);
+
+impl RMeshMaterial {
+    pub fn new<M, F>(build: F) -> Self
+    where
+        M: Material,
+        F: Fn(&RMeshTextures) -> M + Send + Sync + 'static,
+    {
+        Self(Arc::new(MaterialFn(build, PhantomData)))
+    }
+}
+
+/// Inserts the handle of a custom material on a spawned mesh entity.
+pub(crate) type InsertMaterial = Box<dyn Fn(&mut EntityWorldMut) + Send + Sync>;
+
+pub(crate) trait MaterialBuilder: Send + Sync {
+    fn add_material(
+        &self,
+        textures: &RMeshTextures,
+        label: String,
+        load_context: &mut LoadContext,
+    ) -> InsertMaterial;
+}
+
+struct MaterialFn<M, F>(F, PhantomData<fn() -> M>);
+
+impl<M, F> MaterialBuilder for MaterialFn<M, F>
+where
+    M: Material,
+    F: Fn(&RMeshTextures) -> M + Send + Sync,
+{
+    fn add_material(
+        &self,
+        textures: &RMeshTextures,
+        label: String,
+        load_context: &mut LoadContext,
+    ) -> InsertMaterial {
+        let handle = load_context.add_labeled_asset(label, (self.0)(textures));
+        Box::new(move |entity| {
+            entity
+                .remove::<Handle<StandardMaterial>>()
+                .insert(handle.clone());
+        })
+    }
+}
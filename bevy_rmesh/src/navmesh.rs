@@ -0,0 +1,198 @@
+//! Walkable surfaces of a room for AI navigation, enabled by the `navmesh` feature.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+
+/// Walkable triangles of a room with their neighbours, labelled `NavMesh`.
+///
+/// Built from the room meshes and colliders, keeping the triangles that face up
+/// within [`RMeshLoaderSettings::navmesh_max_slope`](crate::RMeshLoaderSettings). It's meant as
+/// input for a pathfinding crate, or for a plain A* over the triangles.
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct RoomNavMesh {
+    /// Welded vertices, in world space relative to the room.
+    pub vertices: Vec<Vec3>,
+    /// Counter-clockwise seen from above.
+    pub triangles: Vec<[u32; 3]>,
+    /// Triangle across each edge, edge `k` going from corner `k` to corner `k + 1`.
+    pub neighbours: Vec<[Option<u32>; 3]>,
+}
+
+impl RoomNavMesh {
+    /// Corners of a triangle, `None` when it or one of its vertices is out of range.
+    pub fn corners(&self, triangle: usize) -> Option<[Vec3; 3]> {
+        let [a, b, c] = self.triangles.get(triangle)?;
+        Some([
+            *self.vertices.get(*a as usize)?,
+            *self.vertices.get(*b as usize)?,
+            *self.vertices.get(*c as usize)?,
+        ])
+    }
+
+    /// Centroid of a triangle, `None` when it or one of its vertices is out of range.
+    pub fn triangle_center(&self, triangle: usize) -> Option<Vec3> {
+        let [a, b, c] = self.corners(triangle)?;
+        Some((a + b + c) / 3.0)
+    }
+
+    /// Triangle right below `point`, if any. Triangles with vertices out of range are skipped.
+    pub fn find_triangle(&self, point: Vec3) -> Option<usize> {
+        let mut best = None;
+        for i in 0..self.triangles.len() {
+            let Some([a, b, c]) = self.corners(i) else {
+                continue;
+            };
+            let center = (a + b + c) / 3.0;
+            let (p, a, b, c) = (point.xz(), a.xz(), b.xz(), c.xz());
+            // Counter-clockwise from above is clockwise in the XZ plane
+            let inside = [(a, b), (b, c), (c, a)]
+                .iter()
+                .all(|(from, to)| (*to - *from).perp_dot(p - *from) <= 0.0);
+            if !inside {
+                continue;
+            }
+            let height = point.y - center.y;
+            if height >= 0.0 && best.is_none_or(|(_, best_height)| height < best_height) {
+                best = Some((i, height));
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+}
+
+/// Keeps the triangles facing up within `max_slope` degrees, welding vertices to link them.
+///
+/// Triangles referencing a vertex out of range are skipped.
+pub(crate) fn build_navmesh(header: &rmesh::Header, scale: f32, max_slope: f32) -> RoomNavMesh {
+    let min_up = max_slope.to_radians().cos();
+    let meshes = header.meshes.iter().map(|mesh| {
+        let positions = mesh.positions();
+        (positions, mesh.triangles.as_slice())
+    });
+    let colliders = header
+        .colliders
+        .iter()
+        .map(|collider| (collider.vertices.clone(), collider.triangles.as_slice()));
+
+    let mut navmesh = RoomNavMesh::default();
+    let mut welded = HashMap::new();
+    for (positions, triangles) in meshes.chain(colliders) {
+        for triangle in triangles {
+            // Mirrored like the room meshes
+            let [Some(c0), Some(c1), Some(c2)] =
+                [triangle[2], triangle[1], triangle[0]].map(|i| positions.get(i as usize))
+            else {
+                continue;
+            };
+            let corners = [c0, c1, c2].map(|&[x, y, z]| Vec3::new(x, y, -z) * scale);
+            let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+            if normal.normalize_or_zero().y < min_up {
+                continue;
+            }
+            let indices = corners.map(|corner| {
+                // Corners within a millimetre are the same, so shared edges link up
+                let key = (corner * 1000.0).round().as_ivec3();
+                *welded.entry(key).or_insert_with(|| {
+                    navmesh.vertices.push(corner);
+                    navmesh.vertices.len() as u32 - 1
+                })
+            });
+            if indices[0] != indices[1] && indices[1] != indices[2] && indices[2] != indices[0] {
+                navmesh.triangles.push(indices);
+            }
+        }
+    }
+
+    let mut edges = HashMap::new();
+    for (i, triangle) in navmesh.triangles.iter().enumerate() {
+        for k in 0..3 {
+            let (from, to) = (triangle[k], triangle[(k + 1) % 3]);
+            edges.insert((from, to), i as u32);
+        }
+    }
+    navmesh.neighbours = navmesh
+        .triangles
+        .iter()
+        .map(|triangle| {
+            [0, 1, 2].map(|k| {
+                // A neighbour walks the shared edge the other way
+                let (from, to) = (triangle[k], triangle[(k + 1) % 3]);
+                edges.get(&(to, from)).copied()
+            })
+        })
+        .collect();
+    navmesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{Header, SimpleMesh};
+
+    /// A 4 by 4 floor with a wall along its far edge, as colliders.
+    fn room(extra_triangle: Option<[u32; 3]>) -> Header {
+        let floor = SimpleMesh::new(
+            vec![
+                [0.0, 0.0, 0.0],
+                [4.0, 0.0, 0.0],
+                [4.0, 0.0, 4.0],
+                [0.0, 0.0, 4.0],
+            ],
+            [[0, 2, 1], [0, 3, 2]]
+                .into_iter()
+                .chain(extra_triangle)
+                .collect(),
+        );
+        let wall = SimpleMesh::new(
+            vec![
+                [0.0, 0.0, 4.0],
+                [4.0, 0.0, 4.0],
+                [4.0, 3.0, 4.0],
+                [0.0, 3.0, 4.0],
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+        );
+        Header {
+            colliders: vec![floor, wall],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn walls_are_left_out_and_the_floor_links_up() {
+        let navmesh = build_navmesh(&room(None), 0.5, 45.0);
+
+        assert_eq!(navmesh.triangles.len(), 2);
+        assert_eq!(navmesh.vertices.len(), 4);
+        let linked: Vec<_> = navmesh
+            .neighbours
+            .iter()
+            .map(|edges| edges.iter().flatten().count())
+            .collect();
+        assert_eq!(linked, [1, 1]);
+
+        // Scaled and mirrored along Z
+        let below = navmesh.find_triangle(Vec3::new(1.5, 1.0, -0.5)).unwrap();
+        let center = navmesh.triangle_center(below).unwrap();
+        assert_eq!(center.y, 0.0);
+        assert!(center.z < 0.0);
+        assert_eq!(navmesh.find_triangle(Vec3::new(1.5, -1.0, -0.5)), None);
+    }
+
+    #[test]
+    fn out_of_range_indices_are_skipped() {
+        let navmesh = build_navmesh(&room(Some([0, 2, 7])), 1.0, 45.0);
+        assert_eq!(navmesh.triangles.len(), 2);
+
+        let mut broken = navmesh.clone();
+        broken.triangles.push([0, 1, 9]);
+        assert_eq!(broken.triangle_center(2), None);
+        assert_eq!(broken.triangle_center(3), None);
+        assert_eq!(
+            broken.find_triangle(Vec3::new(1.5, 1.0, -0.5)),
+            navmesh.find_triangle(Vec3::new(1.5, 1.0, -0.5))
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Asset processing for rooms, used when the app runs Bevy's asset processor.
+
+use bevy::asset::io::Writer;
+use bevy::asset::meta::{AssetAction, AssetMeta};
+use bevy::asset::processor::{Process, ProcessContext, ProcessError};
+use bevy::asset::AsyncWriteExt;
+use rmesh::{read_rmesh, write_rmesh};
+use serde::{Deserialize, Serialize};
+
+use crate::{RMeshLoader, RMeshLoaderSettings};
+
+/// Settings of [`RMeshProcessor`], stored in the `.meta` file of each room.
+#[derive(Serialize, Deserialize)]
+pub struct RMeshProcessorSettings {
+    /// Merges room mesh vertices closer than this, in room units.
+    pub weld_tolerance: Option<f32>,
+    /// Reorders triangles and vertices for the GPU caches.
+    pub optimize: bool,
+    /// Used to load the processed room.
+    pub loader: RMeshLoaderSettings,
+}
+
+impl Default for RMeshProcessorSettings {
+    fn default() -> Self {
+        Self {
+            weld_tolerance: Some(0.0),
+            optimize: true,
+            loader: RMeshLoaderSettings::default(),
+        }
+    }
+}
+
+/// Cleans up room meshes once at processing time and writes them back as rmesh, so the
+/// processed rooms load as fast as the originals while rendering faster.
+///
+/// Only welding and optimization run here. The output is still an rmesh file, which has no
+/// place for cooked physics shapes, so the rapier and avian colliders are built from the
+/// `Collision` mesh when the room spawns. Lightmaps are ordinary images the room only names,
+/// they go through the image processor of Bevy, like `CompressedImageSaver`, not this one.
+///
+/// Registered by the [`RMeshPlugin`](crate::RMeshPlugin) but not the default processor, opt in
+/// per room by naming it in the `.meta` file.
+pub struct RMeshProcessor;
+
+impl Process for RMeshProcessor {
+    type Settings = RMeshProcessorSettings;
+    type OutputLoader = RMeshLoader;
+
+    async fn process<'a>(
+        &'a self,
+        context: &'a mut ProcessContext<'_>,
+        meta: AssetMeta<(), Self>,
+        writer: &'a mut Writer,
+    ) -> Result<RMeshLoaderSettings, ProcessError> {
+        let AssetAction::Process { settings, .. } = meta.asset else {
+            return Err(ProcessError::WrongMetaType);
+        };
+
+        let mut header = read_rmesh(context.asset_bytes())
+            .map_err(|error| ProcessError::AssetTransformError(error.into()))?;
+        for mesh in &mut header.meshes {
+            if let Some(tolerance) = settings.weld_tolerance {
+                mesh.weld(tolerance)
+                    .map_err(|error| ProcessError::AssetTransformError(error.into()))?;
+            }
+            if settings.optimize {
+                mesh.optimize_vertex_cache();
+                mesh.optimize_vertex_fetch();
+            }
+        }
+
+        let bytes =
+            write_rmesh(&header).map_err(|error| ProcessError::AssetSaveError(error.into()))?;
+        writer
+            .write_all(&bytes)
+            .await
+            .map_err(|error| ProcessError::AssetSaveError(error.into()))?;
+        Ok(settings.loader)
+    }
+}
@@ -6,9 +6,7 @@ fn main() -> Result<(), RMeshError> {
     let bytes = std::fs::read(args.next().expect("No rmesh file provided")).unwrap();
     let rmesh = read_rmesh(&bytes)?;
 
-    let mut index = 0;
-
-    for mesh in rmesh.meshes {
+    for (index, mesh) in rmesh.meshes.into_iter().enumerate() {
         println!("Mesh {}", index);
         for texture in mesh.textures {
             if let Some(path) = texture.path {
@@ -19,7 +17,6 @@ fn main() -> Result<(), RMeshError> {
                 );
             }
         }
-        index += 1;
     }
 
     Ok(())
@@ -0,0 +1,134 @@
+use crate::{ComplexMesh, Vertex};
+
+/// The vertex attributes of a mesh as one contiguous array each, built once by
+/// [`ComplexMesh::vertex_arrays`] and handed out as slices.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VertexArrays {
+    positions: Vec<[f32; 3]>,
+    uv0: Vec<[f32; 2]>,
+    uv1: Vec<[f32; 2]>,
+    colors: Vec<[u8; 3]>,
+}
+
+impl VertexArrays {
+    pub fn positions(&self) -> &[[f32; 3]] {
+        &self.positions
+    }
+
+    /// Diffuse texture coordinates.
+    pub fn uv0(&self) -> &[[f32; 2]] {
+        &self.uv0
+    }
+
+    /// Lightmap texture coordinates.
+    pub fn uv1(&self) -> &[[f32; 2]] {
+        &self.uv1
+    }
+
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    /// Number of vertices, the length of every array.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Interleaves the arrays back into vertices.
+    pub fn into_vertices(self) -> Vec<Vertex> {
+        (0..self.len())
+            .map(|i| Vertex {
+                position: self.positions[i],
+                tex_coords: [self.uv0[i], self.uv1[i]],
+                color: self.colors[i],
+            })
+            .collect()
+    }
+}
+
+impl ComplexMesh {
+    /// Splits the vertices into one array per attribute in a single pass.
+    ///
+    /// Build it once and keep it around for several passes over the geometry, it doesn't
+    /// follow later changes to the vertices.
+    pub fn vertex_arrays(&self) -> VertexArrays {
+        let mut arrays = VertexArrays {
+            positions: Vec::with_capacity(self.vertices.len()),
+            uv0: Vec::with_capacity(self.vertices.len()),
+            uv1: Vec::with_capacity(self.vertices.len()),
+            colors: Vec::with_capacity(self.vertices.len()),
+        };
+        for vertex in &self.vertices {
+            arrays.positions.push(vertex.position);
+            arrays.uv0.push(vertex.tex_coords[0]);
+            arrays.uv1.push(vertex.tex_coords[1]);
+            arrays.colors.push(vertex.color);
+        }
+        arrays
+    }
+
+    /// Copies the positions out, for passes needing a single attribute. Use
+    /// [`ComplexMesh::vertex_arrays`] when several are needed or read repeatedly.
+    pub fn positions(&self) -> Vec<[f32; 3]> {
+        self.vertices.iter().map(|v| v.position).collect()
+    }
+
+    /// Copies the diffuse texture coordinates out, see [`ComplexMesh::positions`].
+    pub fn uv0(&self) -> Vec<[f32; 2]> {
+        self.vertices.iter().map(|v| v.tex_coords[0]).collect()
+    }
+
+    /// Copies the lightmap texture coordinates out, see [`ComplexMesh::positions`].
+    pub fn uv1(&self) -> Vec<[f32; 2]> {
+        self.vertices.iter().map(|v| v.tex_coords[1]).collect()
+    }
+
+    /// Copies the colors out, see [`ComplexMesh::positions`].
+    pub fn colors(&self) -> Vec<[u8; 3]> {
+        self.vertices.iter().map(|v| v.color).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrays_line_up_and_interleave_back() {
+        let mesh = ComplexMesh {
+            vertices: (0..5)
+                .map(|i| {
+                    let f = i as f32;
+                    Vertex {
+                        position: [f, f * 2.0, -f],
+                        tex_coords: [[f / 4.0, 0.0], [0.0, 1.0 - f / 4.0]],
+                        color: [i as u8 * 50, 0, 255],
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let arrays = mesh.vertex_arrays();
+
+        assert_eq!(arrays.len(), 5);
+        assert_eq!(arrays.positions()[3], [3.0, 6.0, -3.0]);
+        assert_eq!(arrays.uv0()[2], [0.5, 0.0]);
+        assert_eq!(arrays.uv1()[4], [0.0, 0.0]);
+        assert_eq!(arrays.colors()[1], [50, 0, 255]);
+        assert_eq!(arrays.positions(), mesh.positions());
+
+        let vertices = arrays.into_vertices();
+        for (a, b) in vertices.iter().zip(&mesh.vertices) {
+            assert_eq!(
+                (a.position, a.tex_coords, a.color),
+                (b.position, b.tex_coords, b.color)
+            );
+        }
+        assert!(ComplexMesh::default().vertex_arrays().is_empty());
+    }
+}
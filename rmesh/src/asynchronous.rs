@@ -0,0 +1,24 @@
+//! Reading rooms from async readers, enabled by the `async` feature.
+
+use std::io::SeekFrom;
+
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::{read_rmesh, Header, RMeshError};
+
+/// Reads a .rmesh file from an async reader, starting at its current position.
+///
+/// Uses the `futures-io` traits, tokio readers work through `tokio-util`'s compat layer. The
+/// file is read without blocking, then parsed at once since parsing doesn't wait on anything.
+pub async fn read_rmesh_async<R>(reader: &mut R) -> Result<Header, RMeshError>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let start = reader.seek(SeekFrom::Current(0)).await?;
+    let end = reader.seek(SeekFrom::End(0)).await?;
+    reader.seek(SeekFrom::Start(start)).await?;
+
+    let mut bytes = Vec::with_capacity(end.saturating_sub(start) as usize);
+    reader.read_to_end(&mut bytes).await?;
+    read_rmesh(&bytes)
+}
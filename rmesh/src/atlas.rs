@@ -0,0 +1,192 @@
+//! Packs diffuse textures into one atlas so rooms render with fewer draw calls.
+
+use image::{GenericImage, RgbaImage};
+
+use crate::{Header, Texture, TextureBlendType};
+
+/// How [`Header::build_atlas`] packs textures.
+#[derive(Debug, Clone)]
+pub struct AtlasOptions {
+    /// Largest width and height of the atlas, textures that don't fit are left out.
+    pub max_size: u32,
+    /// Pixels of repeated edge around each texture, keeping mipmaps from bleeding neighbours in.
+    pub padding: u32,
+}
+
+impl Default for AtlasOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 4096,
+            padding: 4,
+        }
+    }
+}
+
+/// Where a texture ended up in an atlas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasRegion {
+    /// Texture path as written in the room before packing.
+    pub path: String,
+    /// Top left corner in pixels, padding excluded.
+    pub offset: [u32; 2],
+    pub size: [u32; 2],
+}
+
+/// An atlas built by [`Header::build_atlas`].
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    pub image: RgbaImage,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl Header {
+    /// Packs the diffuse textures into an atlas, then points the meshes using them at
+    /// `atlas_path` with their UV0 remapped into their region.
+    ///
+    /// `load` returns the image of a texture path as written in the room. Textures it can't
+    /// load, and textures of meshes with UV0 outside `0.0..=1.0`, which tile and can't share an
+    /// atlas, are left as they are. Returns `None` when nothing was packed.
+    pub fn build_atlas(
+        &mut self,
+        atlas_path: &str,
+        options: &AtlasOptions,
+        mut load: impl FnMut(&str) -> Option<RgbaImage>,
+    ) -> Option<Atlas> {
+        // Textures tiled by any mesh are out
+        let mut tiled = vec![];
+        let mut paths: Vec<String> = vec![];
+        for mesh in &self.meshes {
+            let Some(path) = diffuse_path(&mesh.textures[1]) else {
+                continue;
+            };
+            let tiles = mesh.vertices.iter().any(|vertex| {
+                vertex.tex_coords[0]
+                    .iter()
+                    .any(|&c| !(0.0..=1.0).contains(&c))
+            });
+            if tiles {
+                tiled.push(path);
+            } else if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths.retain(|path| !tiled.contains(path));
+
+        let mut images: Vec<_> = paths
+            .into_iter()
+            .filter_map(|path| Some((load(&path)?, path)))
+            .filter(|(image, _)| image.width() > 0 && image.height() > 0)
+            .collect();
+        // Tallest first packs shelves tightest
+        images.sort_by_key(|(image, _)| std::cmp::Reverse(image.height()));
+
+        let (size, placed) = pack(&images, options);
+        if placed.is_empty() {
+            return None;
+        }
+        let mut atlas = RgbaImage::new(size[0], size[1]);
+        let mut regions = vec![];
+        for (index, [x, y]) in placed {
+            let (image, path) = &images[index];
+            blit_padded(&mut atlas, image, [x, y], options.padding);
+            regions.push(AtlasRegion {
+                path: path.clone(),
+                offset: [x, y],
+                size: [image.width(), image.height()],
+            });
+        }
+
+        for mesh in &mut self.meshes {
+            let Some(path) = diffuse_path(&mesh.textures[1]) else {
+                continue;
+            };
+            let Some(region) = regions.iter().find(|region| region.path == path) else {
+                continue;
+            };
+            let scale = [0, 1].map(|i| region.size[i] as f32 / size[i] as f32);
+            let offset = [0, 1].map(|i| region.offset[i] as f32 / size[i] as f32);
+            for vertex in &mut mesh.vertices {
+                let uv = &mut vertex.tex_coords[0];
+                *uv = [0, 1].map(|i| offset[i] + uv[i] * scale[i]);
+            }
+            mesh.textures[1].path = Some(atlas_path.into());
+        }
+
+        Some(Atlas {
+            image: atlas,
+            regions,
+        })
+    }
+}
+
+fn diffuse_path(texture: &Texture) -> Option<String> {
+    match texture.blend_type {
+        TextureBlendType::Visible | TextureBlendType::Transparent => {
+            texture.path.as_ref().map(String::from)
+        }
+        _ => None,
+    }
+}
+
+/// Shelf packs the images in order, returning the atlas size and the top left corner of each
+/// image that fit, padding excluded.
+fn pack(
+    images: &[(RgbaImage, String)],
+    options: &AtlasOptions,
+) -> ([u32; 2], Vec<(usize, [u32; 2])>) {
+    let padding = options.padding;
+    let padded = |image: &RgbaImage| [image.width(), image.height()].map(|s| s + padding * 2);
+    let area: u64 = images
+        .iter()
+        .map(|(image, _)| padded(image).iter().map(|&s| s as u64).product::<u64>())
+        .sum();
+    let widest = images
+        .iter()
+        .map(|(image, _)| padded(image)[0])
+        .max()
+        .unwrap_or(1);
+    let width = ((area as f64).sqrt().ceil() as u32)
+        .max(widest)
+        .next_power_of_two()
+        .min(options.max_size);
+
+    let mut placed = vec![];
+    let [mut x, mut y, mut shelf_height] = [0, 0, 0];
+    for (index, (image, _)) in images.iter().enumerate() {
+        let [w, h] = padded(image);
+        if x + w > width {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        if x + w > width || y + h > options.max_size {
+            continue;
+        }
+        placed.push((index, [x + padding, y + padding]));
+        x += w;
+        shelf_height = shelf_height.max(h);
+    }
+    let height = (y + shelf_height).next_power_of_two().min(options.max_size);
+    ([width, height], placed)
+}
+
+/// Copies an image into the atlas, repeating its edge pixels into the padding.
+fn blit_padded(atlas: &mut RgbaImage, image: &RgbaImage, [x, y]: [u32; 2], padding: u32) {
+    let (w, h) = image.dimensions();
+    let _ = atlas.copy_from(image, x, y);
+    for dy in 0..h + padding * 2 {
+        for dx in 0..w + padding * 2 {
+            let inside =
+                (padding..padding + w).contains(&dx) && (padding..padding + h).contains(&dy);
+            if inside {
+                continue;
+            }
+            let sx = dx.saturating_sub(padding).min(w - 1);
+            let sy = dy.saturating_sub(padding).min(h - 1);
+            let (ax, ay) = (x + dx - padding, y + dy - padding);
+            if ax < atlas.width() && ay < atlas.height() {
+                atlas.put_pixel(ax, ay, *image.get_pixel(sx, sy));
+            }
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Reader for Blitz3D `.b3d` models, used by many props referenced from rooms.
+//!
+//! Only the static geometry is read. Bones, keys and animations are skipped.
+
+use std::io::Cursor;
+
+use binrw::{BinReaderExt, BinResult, NullString};
+
+use crate::math::{self, Mat4};
+use crate::{ComplexMesh, RMeshError, Texture, TextureBlendType, Vertex};
+
+/// Brush fx flag that enables alpha blending.
+const FX_FORCE_ALPHA: i32 = 32;
+/// Texture flag that enables alpha blending.
+const TEXTURE_ALPHA: i32 = 2;
+/// Most texture coordinate sets a `VRTS` chunk may hold.
+const MAX_TEXTURE_SETS: u32 = 8;
+/// Deepest nesting of `NODE` chunks accepted, real models stay well below it.
+const MAX_DEPTH: usize = 64;
+
+/// A parsed `.b3d` file.
+#[derive(Debug, Clone, Default)]
+pub struct B3d {
+    pub version: u32,
+    pub textures: Vec<B3dTexture>,
+    pub brushes: Vec<B3dBrush>,
+    pub node: Option<B3dNode>,
+}
+
+/// Entry of the `TEXS` chunk.
+#[derive(Debug, Clone, Default)]
+pub struct B3dTexture {
+    /// Texture path, relative to the model.
+    pub file: String,
+    pub flags: i32,
+    pub blend: i32,
+    pub position: [f32; 2],
+    pub scale: [f32; 2],
+    pub rotation: f32,
+}
+
+/// Entry of the `BRUS` chunk.
+#[derive(Debug, Clone, Default)]
+pub struct B3dBrush {
+    pub name: String,
+    /// Red, green, blue and alpha, in `0.0..=1.0`.
+    pub color: [f32; 4],
+    pub shininess: f32,
+    pub blend: i32,
+    pub fx: i32,
+    /// Indices into [`B3d::textures`], `-1` for an empty slot.
+    pub texture_ids: Vec<i32>,
+}
+
+/// A `NODE` chunk with its children.
+#[derive(Debug, Clone, Default)]
+pub struct B3dNode {
+    pub name: String,
+    pub position: [f32; 3],
+    pub scale: [f32; 3],
+    /// Rotation quaternion as `[x, y, z, w]` like the rest of the crate, the file stores it as
+    /// `[w, x, y, z]`.
+    pub rotation: [f32; 4],
+    pub mesh: Option<B3dMesh>,
+    pub children: Vec<B3dNode>,
+}
+
+/// A `MESH` chunk.
+#[derive(Debug, Clone, Default)]
+pub struct B3dMesh {
+    /// Index into [`B3d::brushes`] used by surfaces without their own brush, or `-1`.
+    pub brush_id: i32,
+    pub positions: Vec<[f32; 3]>,
+    /// Empty when the file stores no normals.
+    pub normals: Vec<[f32; 3]>,
+    /// Empty when the file stores no vertex colors.
+    pub colors: Vec<[f32; 4]>,
+    /// One list per texture coordinate set.
+    pub tex_coords: Vec<Vec<[f32; 2]>>,
+    pub surfaces: Vec<B3dSurface>,
+}
+
+/// A `TRIS` chunk, the triangles drawn with one brush.
+#[derive(Debug, Clone, Default)]
+pub struct B3dSurface {
+    /// Index into [`B3d::brushes`], or `-1` to use the mesh brush.
+    pub brush_id: i32,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+/// Reads a .b3d file.
+pub fn read_b3d(bytes: &[u8]) -> Result<B3d, RMeshError> {
+    let mut cursor = Cursor::new(bytes);
+    let (tag, end) = read_chunk_header(&mut cursor)?;
+    if &tag != b"BB3D" {
+        return Err(binrw::Error::BadMagic {
+            pos: 0,
+            found: Box::new(tag),
+        }
+        .into());
+    }
+
+    let mut b3d = B3d {
+        version: cursor.read_le()?,
+        ..Default::default()
+    };
+
+    while cursor.position() < end {
+        let (tag, chunk_end) = read_chunk_header(&mut cursor)?;
+        match &tag {
+            b"TEXS" => {
+                while cursor.position() < chunk_end {
+                    b3d.textures.push(B3dTexture {
+                        file: read_string(&mut cursor)?,
+                        flags: cursor.read_le()?,
+                        blend: cursor.read_le()?,
+                        position: cursor.read_le()?,
+                        scale: cursor.read_le()?,
+                        rotation: cursor.read_le()?,
+                    });
+                }
+            }
+            b"BRUS" => {
+                let texture_count: u32 = cursor.read_le()?;
+                while cursor.position() < chunk_end {
+                    b3d.brushes.push(B3dBrush {
+                        name: read_string(&mut cursor)?,
+                        color: cursor.read_le()?,
+                        shininess: cursor.read_le()?,
+                        blend: cursor.read_le()?,
+                        fx: cursor.read_le()?,
+                        texture_ids: (0..texture_count)
+                            .map(|_| cursor.read_le())
+                            .collect::<BinResult<_>>()?,
+                    });
+                }
+            }
+            b"NODE" => b3d.node = Some(read_node(&mut cursor, chunk_end, 0)?),
+            _ => (),
+        }
+        cursor.set_position(chunk_end);
+    }
+
+    Ok(b3d)
+}
+
+/// Reads a chunk tag and length, returning the tag and the position the chunk ends at.
+fn read_chunk_header(cursor: &mut Cursor<&[u8]>) -> BinResult<([u8; 4], u64)> {
+    let tag: [u8; 4] = cursor.read_le()?;
+    let length: u32 = cursor.read_le()?;
+    Ok((tag, cursor.position() + length as u64))
+}
+
+fn read_string(cursor: &mut Cursor<&[u8]>) -> BinResult<String> {
+    let string: NullString = cursor.read_le()?;
+    Ok(string.to_string())
+}
+
+/// Reads a node nested `depth` nodes deep and its children.
+fn read_node(cursor: &mut Cursor<&[u8]>, end: u64, depth: usize) -> BinResult<B3dNode> {
+    if depth >= MAX_DEPTH {
+        return Err(binrw::Error::AssertFail {
+            pos: cursor.position(),
+            message: format!("nodes nested deeper than {MAX_DEPTH}"),
+        });
+    }
+    let mut node = B3dNode {
+        name: read_string(cursor)?,
+        position: cursor.read_le()?,
+        scale: cursor.read_le()?,
+        rotation: {
+            let [w, x, y, z]: [f32; 4] = cursor.read_le()?;
+            [x, y, z, w]
+        },
+        ..Default::default()
+    };
+
+    while cursor.position() < end {
+        let (tag, chunk_end) = read_chunk_header(cursor)?;
+        match &tag {
+            b"MESH" => node.mesh = Some(read_mesh(cursor, chunk_end)?),
+            b"NODE" => node.children.push(read_node(cursor, chunk_end, depth + 1)?),
+            _ => (),
+        }
+        cursor.set_position(chunk_end);
+    }
+
+    Ok(node)
+}
+
+fn read_mesh(cursor: &mut Cursor<&[u8]>, end: u64) -> BinResult<B3dMesh> {
+    let mut mesh = B3dMesh {
+        brush_id: cursor.read_le()?,
+        ..Default::default()
+    };
+
+    while cursor.position() < end {
+        let (tag, chunk_end) = read_chunk_header(cursor)?;
+        match &tag {
+            b"VRTS" => {
+                let flags: i32 = cursor.read_le()?;
+                let set_count: u32 = cursor.read_le()?;
+                let set_size: u32 = cursor.read_le()?;
+                if set_count > MAX_TEXTURE_SETS {
+                    return Err(binrw::Error::AssertFail {
+                        pos: cursor.position(),
+                        message: format!(
+                            "{set_count} texture coordinate sets, at most {MAX_TEXTURE_SETS} are allowed"
+                        ),
+                    });
+                }
+                mesh.tex_coords = vec![vec![]; set_count as usize];
+
+                while cursor.position() < chunk_end {
+                    mesh.positions.push(cursor.read_le()?);
+                    if flags & 1 != 0 {
+                        mesh.normals.push(cursor.read_le()?);
+                    }
+                    if flags & 2 != 0 {
+                        mesh.colors.push(cursor.read_le()?);
+                    }
+                    for set in &mut mesh.tex_coords {
+                        let mut coords = [0.0; 2];
+                        for i in 0..set_size as usize {
+                            let value: f32 = cursor.read_le()?;
+                            if let Some(coord) = coords.get_mut(i) {
+                                *coord = value;
+                            }
+                        }
+                        set.push(coords);
+                    }
+                }
+            }
+            b"TRIS" => {
+                let mut surface = B3dSurface {
+                    brush_id: cursor.read_le()?,
+                    ..Default::default()
+                };
+                while cursor.position() < chunk_end {
+                    surface.triangles.push(cursor.read_le()?);
+                }
+                mesh.surfaces.push(surface);
+            }
+            _ => (),
+        }
+        cursor.set_position(chunk_end);
+    }
+
+    Ok(mesh)
+}
+
+impl B3d {
+    /// Flattens the node hierarchy into one [`ComplexMesh`] per surface, in model space.
+    ///
+    /// The first texture of each brush fills the diffuse slot, texture coordinate sets
+    /// map to [`Vertex::tex_coords`] and vertex colors are multiplied with the brush color.
+    pub fn to_complex_meshes(&self) -> Vec<ComplexMesh> {
+        let mut meshes = vec![];
+        if let Some(node) = &self.node {
+            self.collect_meshes(node, &math::IDENTITY, &mut meshes);
+        }
+        meshes
+    }
+
+    fn collect_meshes(&self, node: &B3dNode, parent: &Mat4, meshes: &mut Vec<ComplexMesh>) {
+        let matrix = math::mul_mat4(
+            parent,
+            &math::trs_matrix(node.position, node.rotation, node.scale),
+        );
+
+        if let Some(mesh) = &node.mesh {
+            for surface in &mesh.surfaces {
+                let brush_id = if surface.brush_id >= 0 {
+                    surface.brush_id
+                } else {
+                    mesh.brush_id
+                };
+                let brush = usize::try_from(brush_id)
+                    .ok()
+                    .and_then(|i| self.brushes.get(i));
+
+                let mut complex_mesh = self.surface_mesh(mesh, surface, brush);
+                complex_mesh.transform(&matrix);
+                meshes.push(complex_mesh);
+            }
+        }
+
+        for child in &node.children {
+            self.collect_meshes(child, &matrix, meshes);
+        }
+    }
+
+    fn surface_mesh(
+        &self,
+        mesh: &B3dMesh,
+        surface: &B3dSurface,
+        brush: Option<&B3dBrush>,
+    ) -> ComplexMesh {
+        let brush_color = brush.map_or([1.0; 4], |b| b.color);
+        let texture = brush
+            .and_then(|b| b.texture_ids.first())
+            .and_then(|id| usize::try_from(*id).ok())
+            .and_then(|id| self.textures.get(id));
+
+        let mut complex_mesh = ComplexMesh::default();
+        if let Some(texture) = texture {
+            let transparent = brush_color[3] < 1.0
+                || brush.is_some_and(|b| b.fx & FX_FORCE_ALPHA != 0)
+                || texture.flags & TEXTURE_ALPHA != 0;
+            complex_mesh.textures[1] = Texture {
+                blend_type: if transparent {
+                    TextureBlendType::Transparent
+                } else {
+                    TextureBlendType::Visible
+                },
+                path: Some(texture.file.as_str().into()),
+            };
+        }
+
+        // Only keep the vertices this surface uses
+        let mut remap = vec![u32::MAX; mesh.positions.len()];
+        for triangle in &surface.triangles {
+            if triangle.iter().any(|i| *i as usize >= mesh.positions.len()) {
+                continue;
+            }
+
+            let triangle = triangle.map(|i| {
+                let i = i as usize;
+                if remap[i] == u32::MAX {
+                    remap[i] = complex_mesh.vertices.len() as u32;
+                    let color = mesh.colors.get(i).copied().unwrap_or([1.0; 4]);
+                    complex_mesh.vertices.push(Vertex {
+                        position: mesh.positions[i],
+                        tex_coords: [0, 1].map(|set| {
+                            mesh.tex_coords
+                                .get(set)
+                                .and_then(|coords| coords.get(i))
+                                .copied()
+                                .unwrap_or_default()
+                        }),
+                        color: [0, 1, 2].map(|c| {
+                            (color[c] * brush_color[c] * 255.0)
+                                .round()
+                                .clamp(0.0, 255.0) as u8
+                        }),
+                    });
+                }
+                remap[i]
+            });
+            complex_mesh.triangles.push(triangle);
+        }
+
+        complex_mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(tag: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = tag.to_vec();
+        bytes.extend((body.len() as u32).to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn floats(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// Name, position, scale and rotation of a node placed at `x` along X.
+    fn node_fields(x: f32) -> Vec<u8> {
+        let mut bytes = b"node\0".to_vec();
+        bytes.extend(floats(&[x, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0]));
+        bytes
+    }
+
+    fn file(node: Vec<u8>) -> Vec<u8> {
+        let mut body = 1u32.to_le_bytes().to_vec();
+        body.extend(node);
+        chunk(b"BB3D", &body)
+    }
+
+    fn vrts(set_count: u32) -> Vec<u8> {
+        let mut body = [
+            0i32.to_le_bytes(),
+            set_count.to_le_bytes(),
+            2u32.to_le_bytes(),
+        ]
+        .concat();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)] {
+            body.extend(floats(&[x, y, 0.0]));
+            for _ in 0..set_count {
+                body.extend(floats(&[x, y]));
+            }
+        }
+        chunk(b"VRTS", &body)
+    }
+
+    fn mesh(vrts: Vec<u8>) -> Vec<u8> {
+        let tris = [
+            (-1i32).to_le_bytes(),
+            0u32.to_le_bytes(),
+            2u32.to_le_bytes(),
+            1u32.to_le_bytes(),
+        ]
+        .concat();
+        chunk(
+            b"MESH",
+            &[(-1i32).to_le_bytes().to_vec(), vrts, chunk(b"TRIS", &tris)].concat(),
+        )
+    }
+
+    #[test]
+    fn node_mesh_becomes_a_placed_complex_mesh() {
+        let bytes = file(chunk(b"NODE", &[node_fields(10.0), mesh(vrts(1))].concat()));
+
+        let b3d = read_b3d(&bytes).unwrap();
+        let meshes = b3d.to_complex_meshes();
+
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].triangles, [[0, 1, 2]]);
+        assert_eq!(meshes[0].vertices[1].position, [10.0, 1.0, 0.0]);
+        assert_eq!(meshes[0].vertices[1].tex_coords[0], [0.0, 1.0]);
+    }
+
+    #[test]
+    fn node_rotation_is_stored_w_first() {
+        // Quarter turn around Z, written in the file's [w, x, y, z] order.
+        let half = std::f32::consts::FRAC_PI_4;
+        let mut fields = b"node\0".to_vec();
+        fields.extend(floats(&[
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            1.0,
+            1.0,
+            half.cos(),
+            0.0,
+            0.0,
+            half.sin(),
+        ]));
+        let bytes = file(chunk(b"NODE", &[fields, mesh(vrts(1))].concat()));
+
+        let b3d = read_b3d(&bytes).unwrap();
+        let node = b3d.node.as_ref().unwrap();
+        assert_eq!(node.rotation, [0.0, 0.0, half.sin(), half.cos()]);
+
+        let meshes = b3d.to_complex_meshes();
+        let turned = meshes[0]
+            .vertices
+            .iter()
+            .any(|v| (v.position[0] + 1.0).abs() < 1e-5 && v.position[1].abs() < 1e-5);
+        assert!(turned, "(0, 1, 0) should land on (-1, 0, 0)");
+    }
+
+    #[test]
+    fn too_many_texture_sets_fail() {
+        let bytes = file(chunk(b"NODE", &[node_fields(0.0), mesh(vrts(9))].concat()));
+
+        assert!(read_b3d(&bytes).is_err());
+    }
+
+    #[test]
+    fn deep_nesting_fails_instead_of_overflowing() {
+        let mut node = chunk(b"NODE", &node_fields(0.0));
+        for _ in 0..MAX_DEPTH {
+            node = chunk(b"NODE", &[node_fields(0.0), node].concat());
+        }
+
+        let Err(RMeshError::BinRwError(binrw::Error::AssertFail { message, .. })) =
+            read_b3d(&file(node))
+        else {
+            panic!("expected the nesting to be rejected");
+        };
+        assert!(message.contains("nested"));
+    }
+}
@@ -0,0 +1,344 @@
+//! Direct lighting baker writing one lightmap per mesh.
+
+use std::path::{Path, PathBuf};
+
+use crate::spatial::Bvh;
+use crate::{lightmaps, math};
+use crate::{EntityType, Header, RMeshError, Texture, TextureBlendType};
+
+/// Tunes the baker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BakeOptions {
+    /// Lightmap texels per room unit.
+    pub texel_density: f32,
+    /// Light added to every texel, in `0.0..=1.0`.
+    pub ambient: [f32; 3],
+    /// Cast shadow rays against the room geometry.
+    pub shadows: bool,
+}
+
+impl Default for BakeOptions {
+    fn default() -> Self {
+        Self {
+            texel_density: 1.0 / 16.0,
+            ambient: [0.1, 0.1, 0.1],
+            shadows: true,
+        }
+    }
+}
+
+/// An RGB lightmap image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BakedLightmap {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major pixels, starting at the top left.
+    pub pixels: Vec<[u8; 3]>,
+}
+
+struct BakeLight {
+    position: [f32; 3],
+    range: f32,
+    color: [f32; 3],
+    /// Direction and cosines of the inner and outer cone for spotlights.
+    cone: Option<([f32; 3], f32, f32)>,
+}
+
+fn lights(header: &Header) -> Vec<BakeLight> {
+    let color = |c: &[u8], intensity: f32| {
+        [0, 1, 2].map(|i| c.get(i).copied().unwrap_or(255) as f32 / 255.0 * intensity)
+    };
+
+    header
+        .entities
+        .iter()
+        .filter_map(|entity| match entity.entity_type.as_ref()? {
+            EntityType::Light(data) => Some(BakeLight {
+                position: data.position,
+                range: data.range,
+                color: color(&data.color.0, data.intensity),
+                cone: None,
+            }),
+            EntityType::SpotLight(data) => {
+                // Blitz3D spotlights shine along their local +Z axis
+                let rotation = math::euler_to_matrix(data.angles.0);
+                let direction = [rotation[0][2], rotation[1][2], rotation[2][2]];
+                Some(BakeLight {
+                    position: data.position,
+                    range: data.range,
+                    color: color(&data.color.0, data.intensity),
+                    cone: Some((
+                        direction,
+                        (data.inner_cone_angle * 0.5).to_radians().cos(),
+                        (data.outer_cone_angle * 0.5).to_radians().cos(),
+                    )),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Bakes direct lighting for every mesh of the room.
+///
+/// Lightmap coordinates are regenerated and written to the second UV channel, so the
+/// returned images line up with the modified meshes. Fails without touching the room when a
+/// triangle references a vertex out of range.
+pub fn bake(header: &mut Header, options: &BakeOptions) -> Result<Vec<BakedLightmap>, RMeshError> {
+    for mesh in &header.meshes {
+        mesh.check_indices()?;
+    }
+    let lights = lights(header);
+    let bvh = options.shadows.then(|| header.bvh());
+
+    let mut lightmaps = Vec::with_capacity(header.meshes.len());
+    for mesh in &mut header.meshes {
+        let layout = mesh.generate_lightmap_uvs(options.texel_density)?;
+        let (width, height) = (layout.width as usize, layout.height as usize);
+        let mut texels: Vec<Option<[f32; 3]>> = vec![None; width * height];
+
+        for triangle in &mesh.triangles {
+            let corners = triangle.map(|i| &mesh.vertices[i as usize]);
+            let uv = corners.map(|v| {
+                [
+                    v.tex_coords[1][0] * width as f32,
+                    v.tex_coords[1][1] * height as f32,
+                ]
+            });
+            let [a, b, c] = corners.map(|v| v.position);
+            let normal = math::normalize(math::cross(math::sub(b, a), math::sub(c, a)));
+
+            let area = edge(uv[0], uv[1], uv[2]);
+            if area.abs() < f32::EPSILON {
+                continue;
+            }
+
+            let min_x = uv
+                .iter()
+                .map(|p| p[0])
+                .fold(f32::INFINITY, f32::min)
+                .floor()
+                .max(0.0) as usize;
+            let max_x = (uv.iter().map(|p| p[0]).fold(0.0, f32::max).ceil() as usize).min(width);
+            let min_y = uv
+                .iter()
+                .map(|p| p[1])
+                .fold(f32::INFINITY, f32::min)
+                .floor()
+                .max(0.0) as usize;
+            let max_y = (uv.iter().map(|p| p[1]).fold(0.0, f32::max).ceil() as usize).min(height);
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let p = [x as f32 + 0.5, y as f32 + 0.5];
+                    let w = [
+                        edge(uv[1], uv[2], p) / area,
+                        edge(uv[2], uv[0], p) / area,
+                        edge(uv[0], uv[1], p) / area,
+                    ];
+                    if w.iter().any(|w| *w < 0.0) {
+                        continue;
+                    }
+
+                    let position = math::add(
+                        math::add(math::scale(a, w[0]), math::scale(b, w[1])),
+                        math::scale(c, w[2]),
+                    );
+                    texels[y * width + x] =
+                        Some(shade(position, normal, &lights, bvh.as_ref(), options));
+                }
+            }
+        }
+
+        dilate(&mut texels, width, height);
+
+        let pixels = texels
+            .into_iter()
+            .map(|texel| {
+                texel
+                    .unwrap_or(options.ambient)
+                    .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect();
+        lightmaps.push(BakedLightmap {
+            width: layout.width,
+            height: layout.height,
+            pixels,
+        });
+    }
+
+    Ok(lightmaps)
+}
+
+/// Bakes like [`bake`], then points each mesh at its lightmap named after the room at
+/// `room_path`, see [`lightmaps`](crate::lightmaps). Returns where each image belongs.
+pub fn bake_room(
+    header: &mut Header,
+    room_path: &Path,
+    options: &BakeOptions,
+) -> Result<Vec<(PathBuf, BakedLightmap)>, RMeshError> {
+    let baked = bake(header, options)?;
+    for (i, mesh) in header.meshes.iter_mut().enumerate() {
+        mesh.textures[0] = Texture {
+            blend_type: TextureBlendType::Lightmap,
+            path: Some(lightmaps::lightmap_name(room_path, i + 1).as_str().into()),
+        };
+    }
+    Ok(baked
+        .into_iter()
+        .enumerate()
+        .map(|(i, lightmap)| (lightmaps::lightmap_path(room_path, i + 1), lightmap))
+        .collect())
+}
+
+/// Twice the signed area of the 2D triangle `abp`.
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+fn shade(
+    position: [f32; 3],
+    normal: [f32; 3],
+    lights: &[BakeLight],
+    bvh: Option<&Bvh>,
+    options: &BakeOptions,
+) -> [f32; 3] {
+    let mut color = options.ambient;
+
+    for light in lights {
+        let to_light = math::sub(light.position, position);
+        let distance = math::length(to_light);
+        if distance >= light.range || distance == 0.0 {
+            continue;
+        }
+        let direction = math::scale(to_light, 1.0 / distance);
+
+        let lambert = math::dot(normal, direction);
+        if lambert <= 0.0 {
+            continue;
+        }
+
+        let mut factor = lambert * (1.0 - distance / light.range);
+        if let Some((axis, inner, outer)) = light.cone {
+            let cos = -math::dot(direction, axis);
+            if cos <= outer {
+                continue;
+            }
+            factor *= ((cos - outer) / (inner - outer).max(f32::EPSILON)).min(1.0);
+        }
+
+        if let Some(bvh) = bvh {
+            // Start slightly off the surface so it doesn't shadow itself
+            let origin = math::add(position, math::scale(normal, 0.5));
+            let length = math::length(math::sub(light.position, origin));
+            if bvh
+                .raycast(origin, math::sub(light.position, origin), length)
+                .is_some()
+            {
+                continue;
+            }
+        }
+
+        color = [0, 1, 2].map(|i| color[i] + light.color[i] * factor);
+    }
+
+    color
+}
+
+/// Fills empty texels from lit neighbours so filtering doesn't pull in black borders.
+fn dilate(texels: &mut [Option<[f32; 3]>], width: usize, height: usize) {
+    for _ in 0..2 {
+        let source = texels.to_vec();
+        for y in 0..height {
+            for x in 0..width {
+                if source[y * width + x].is_some() {
+                    continue;
+                }
+                let neighbours =
+                    [(0, -1), (0, 1), (-1, 0), (1, 0)]
+                        .iter()
+                        .filter_map(|(dx, dy)| {
+                            let nx = x.checked_add_signed(*dx).filter(|nx| *nx < width)?;
+                            let ny = y.checked_add_signed(*dy).filter(|ny| *ny < height)?;
+                            source[ny * width + nx]
+                        });
+                let (sum, count) = neighbours.fold(([0.0; 3], 0), |(sum, count), c| {
+                    ([0, 1, 2].map(|i| sum[i] + c[i]), count + 1)
+                });
+                if count > 0 {
+                    texels[y * width + x] = Some(sum.map(|c| c / count as f32));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComplexMesh, EntityLight, EntityType, Vertex};
+
+    fn lit_floor() -> Header {
+        let floor = ComplexMesh {
+            vertices: [[0.0, 0.0], [256.0, 0.0], [256.0, 256.0], [0.0, 256.0]]
+                .map(|[x, z]| Vertex {
+                    position: [x, 0.0, z],
+                    ..Default::default()
+                })
+                .to_vec(),
+            triangles: vec![[0, 2, 1], [0, 3, 2]],
+            ..Default::default()
+        };
+        Header {
+            meshes: vec![floor],
+            entities: vec![EntityType::Light(EntityLight {
+                position: [32.0, 64.0, 32.0],
+                range: 200.0,
+                color: [255, 255, 255].into(),
+                intensity: 1.0,
+            })
+            .into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn light_brightens_the_floor_below_it() {
+        let mut header = lit_floor();
+        let options = BakeOptions {
+            shadows: false,
+            ..Default::default()
+        };
+
+        let lightmaps = bake(&mut header, &options).unwrap();
+
+        assert_eq!(lightmaps.len(), 1);
+        let lightmap = &lightmaps[0];
+        assert_eq!(
+            lightmap.pixels.len(),
+            (lightmap.width * lightmap.height) as usize
+        );
+        let brightest = lightmap.pixels.iter().map(|p| p[0]).max().unwrap();
+        let darkest = lightmap.pixels.iter().map(|p| p[0]).min().unwrap();
+        assert!(brightest > darkest);
+    }
+
+    #[test]
+    fn bad_index_fails_before_changing_the_room() {
+        let mut header = lit_floor();
+        header.meshes[0].triangles.push([0, 1, 4]);
+
+        let result = bake_room(
+            &mut header,
+            Path::new("map/room.rmesh"),
+            &BakeOptions::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(RMeshError::InvalidIndex { index: 4, .. })
+        ));
+        assert_eq!(header.meshes[0].vertices.len(), 4);
+        assert!(header.meshes[0].textures[0].path.is_none());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{ExtMesh, Header};
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Bounds {
+    /// Bounds containing nothing, the identity for [`Bounds::union`].
+    pub const EMPTY: Self = Self {
+        min: [f32::INFINITY; 3],
+        max: [f32::NEG_INFINITY; 3],
+    };
+
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest bounds containing all points.
+    pub fn from_points(points: impl IntoIterator<Item = [f32; 3]>) -> Self {
+        points
+            .into_iter()
+            .fold(Self::EMPTY, |bounds, point| bounds.extend(point))
+    }
+
+    /// Whether the bounds contain no points at all.
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|i| self.min[i] > self.max[i])
+    }
+
+    pub fn center(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| (self.min[i] + self.max[i]) * 0.5)
+    }
+
+    pub fn half_extents(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| (self.max[i] - self.min[i]) * 0.5)
+    }
+
+    pub fn size(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| self.max[i] - self.min[i])
+    }
+
+    /// Smallest bounds containing both boxes.
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds::new(
+            [0, 1, 2].map(|i| self.min[i].min(other.min[i])),
+            [0, 1, 2].map(|i| self.max[i].max(other.max[i])),
+        )
+    }
+
+    /// Overlapping region of both boxes, `None` if they don't touch.
+    pub fn intersection(&self, other: &Bounds) -> Option<Bounds> {
+        let bounds = Bounds::new(
+            [0, 1, 2].map(|i| self.min[i].max(other.min[i])),
+            [0, 1, 2].map(|i| self.max[i].min(other.max[i])),
+        );
+        (!bounds.is_empty()).then_some(bounds)
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Whether the point lies inside or on the surface of the box.
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    /// Grows the box by `margin` on every side.
+    pub fn expand(&self, margin: f32) -> Bounds {
+        Bounds::new(self.min.map(|v| v - margin), self.max.map(|v| v + margin))
+    }
+
+    /// Grows the box to include `point`.
+    pub fn extend(&self, point: [f32; 3]) -> Bounds {
+        Bounds::new(
+            [0, 1, 2].map(|i| self.min[i].min(point[i])),
+            [0, 1, 2].map(|i| self.max[i].max(point[i])),
+        )
+    }
+}
+
+impl Header {
+    /// Bounds of every mesh, collider, trigger box and entity position in the room.
+    pub fn bounding_box(&self) -> Bounds {
+        let meshes = self.meshes.iter().map(|m| m.bounding_box());
+        let colliders = self.colliders.iter().map(|m| m.bounding_box());
+        let trigger_boxes = self
+            .trigger_boxes
+            .iter()
+            .flat_map(|t| t.meshes.iter().map(|m| m.bounding_box()));
+        let entities = Bounds::from_points(
+            self.entities
+                .iter()
+                .filter_map(|e| e.entity_type.as_ref())
+                .map(|e| e.position()),
+        );
+
+        meshes
+            .chain(colliders)
+            .chain(trigger_boxes)
+            .fold(entities, |a, b| a.union(&b))
+    }
+}
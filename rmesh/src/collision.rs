@@ -0,0 +1,61 @@
+use crate::{ComplexMesh, Header, SimpleMesh, TextureBlendType};
+
+/// A piece of geometry the player collides with.
+#[derive(Debug, Clone, Copy)]
+pub enum CollisionSurface<'a> {
+    /// Visual mesh without any textures, used as invisible collision.
+    Invisible(&'a ComplexMesh),
+    /// Explicit collider from the `colliders` list.
+    Collider(&'a SimpleMesh),
+}
+
+impl CollisionSurface<'_> {
+    /// Vertex positions of the surface.
+    pub fn positions(&self) -> Vec<[f32; 3]> {
+        match self {
+            Self::Invisible(mesh) => mesh.positions(),
+            Self::Collider(mesh) => mesh.vertices.clone(),
+        }
+    }
+
+    /// Triangle indices into [`CollisionSurface::positions`].
+    pub fn triangles(&self) -> &[[u32; 3]] {
+        match self {
+            Self::Invisible(mesh) => &mesh.triangles,
+            Self::Collider(mesh) => &mesh.triangles,
+        }
+    }
+
+    /// Copies the surface into a standalone [`SimpleMesh`].
+    pub fn to_simple_mesh(&self) -> SimpleMesh {
+        let vertices = self.positions();
+        let triangles = self.triangles().to_vec();
+        SimpleMesh {
+            vertex_count: vertices.len() as u32,
+            vertices,
+            triangle_count: triangles.len() as u32,
+            triangles,
+        }
+    }
+}
+
+impl ComplexMesh {
+    /// Whether every texture slot is empty, which makes the mesh act as invisible collision.
+    pub fn is_invisible(&self) -> bool {
+        self.textures
+            .iter()
+            .all(|t| t.blend_type == TextureBlendType::None)
+    }
+}
+
+impl Header {
+    /// Gathers invisible meshes and explicit colliders into a single collision view.
+    pub fn collision_surfaces(&self) -> Vec<CollisionSurface<'_>> {
+        self.meshes
+            .iter()
+            .filter(|mesh| mesh.is_invisible())
+            .map(CollisionSurface::Invisible)
+            .chain(self.colliders.iter().map(CollisionSurface::Collider))
+            .collect()
+    }
+}
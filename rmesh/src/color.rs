@@ -0,0 +1,73 @@
+//! Vertex color conversions between the stored sRGB bytes and linear floats.
+
+use crate::{ComplexMesh, Vertex};
+
+/// Converts an sRGB encoded channel in `0.0..=1.0` to linear.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear channel in `0.0..=1.0` to sRGB encoding.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts stored sRGB bytes to linear RGBA with full alpha.
+pub fn color_to_linear(color: [u8; 3]) -> [f32; 4] {
+    let [r, g, b] = color.map(|c| srgb_to_linear(c as f32 / 255.0));
+    [r, g, b, 1.0]
+}
+
+/// Converts linear RGBA to sRGB bytes, dropping alpha.
+pub fn linear_to_color(color: [f32; 4]) -> [u8; 3] {
+    [color[0], color[1], color[2]]
+        .map(|c| (linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0).round() as u8)
+}
+
+/// Multiplies linear baked lighting by a stored vertex color.
+pub fn modulate(light: [f32; 3], color: [u8; 3]) -> [f32; 3] {
+    let linear = color_to_linear(color);
+    [0, 1, 2].map(|i| light[i] * linear[i])
+}
+
+impl Vertex {
+    /// Vertex color as linear RGBA.
+    pub fn linear_color(&self) -> [f32; 4] {
+        color_to_linear(self.color)
+    }
+
+    /// Stores a linear RGBA color, converting it to sRGB bytes.
+    pub fn set_linear_color(&mut self, color: [f32; 4]) {
+        self.color = linear_to_color(color);
+    }
+}
+
+impl ComplexMesh {
+    /// Sets every vertex to the same color.
+    pub fn fill_vertex_color(&mut self, color: [u8; 3]) {
+        for vertex in &mut self.vertices {
+            vertex.color = color;
+        }
+    }
+
+    /// Resets vertex colors to white, which leaves textures unchanged when multiplied.
+    pub fn clear_vertex_colors(&mut self) {
+        self.fill_vertex_color([255, 255, 255]);
+    }
+
+    /// Multiplies every vertex color by linear lighting computed per vertex.
+    pub fn multiply_vertex_colors(&mut self, mut light: impl FnMut(&Vertex) -> [f32; 3]) {
+        for vertex in &mut self.vertices {
+            let [r, g, b] = modulate(light(vertex), vertex.color);
+            vertex.set_linear_color([r, g, b, 1.0]);
+        }
+    }
+}
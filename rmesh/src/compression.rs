@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+use crate::{write_rmesh, Header, RMeshError};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Largest file [`decompress`] unpacks, so a small crafted stream can't exhaust memory.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+const MAX_DECOMPRESSED_SIZE: u64 = 1 << 30;
+
+/// Compression applied by [`write_rmesh_compressed`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain rmesh, readable by the original game.
+    #[default]
+    None,
+    /// Gzip with a level from `0` to `9`.
+    #[cfg(feature = "gzip")]
+    Gzip(u32),
+}
+
+/// Writes a .rmesh file wrapped in the given compression.
+///
+/// Only [`Compression::None`] produces files the original game can load.
+pub fn write_rmesh_compressed(
+    header: &Header,
+    compression: Compression,
+) -> Result<Vec<u8>, RMeshError> {
+    let bytes = write_rmesh(header)?;
+    match compression {
+        Compression::None => Ok(bytes),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip(level) => {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(vec![], flate2::Compression::new(level.min(9)));
+            encoder.write_all(&bytes)?;
+            Ok(encoder.finish()?)
+        }
+    }
+}
+
+/// Whether the file starts like a gzip or zstd stream.
+pub(crate) fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC) || bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// Unwraps gzip or zstd compressed files, passing plain files through untouched.
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, RMeshError> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        decompress_gzip(bytes).map(Cow::Owned)
+    } else if bytes.starts_with(&ZSTD_MAGIC) {
+        decompress_zstd(bytes).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(bytes))
+    }
+}
+
+/// Reads the whole stream, failing once it grows past `limit` bytes.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn read_limited(reader: impl std::io::Read, limit: u64) -> Result<Vec<u8>, RMeshError> {
+    use std::io::Read;
+
+    let mut decompressed = vec![];
+    reader.take(limit + 1).read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > limit {
+        return Err(RMeshError::DecompressedTooLarge(limit));
+    }
+    Ok(decompressed)
+}
+
+#[cfg(feature = "gzip")]
+fn decompress_gzip(bytes: &[u8]) -> Result<Vec<u8>, RMeshError> {
+    read_limited(flate2::read::GzDecoder::new(bytes), MAX_DECOMPRESSED_SIZE)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress_gzip(_bytes: &[u8]) -> Result<Vec<u8>, RMeshError> {
+    Err(RMeshError::UnsupportedCompression("gzip"))
+}
+
+// ruzstd only decodes, so zstd files can be read but not written
+#[cfg(feature = "zstd")]
+fn decompress_zstd(bytes: &[u8]) -> Result<Vec<u8>, RMeshError> {
+    use std::io;
+
+    let decoder = ruzstd::StreamingDecoder::new(bytes)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    read_limited(decoder, MAX_DECOMPRESSED_SIZE)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_bytes: &[u8]) -> Result<Vec<u8>, RMeshError> {
+    Err(RMeshError::UnsupportedCompression("zstd"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_files_pass_through() {
+        let bytes = b"\x08\0\0\0RoomMesh";
+
+        assert!(!is_compressed(bytes));
+        assert!(matches!(decompress(bytes).unwrap(), Cow::Borrowed(_)));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        let header = Header {
+            extra: b"trailing".to_vec(),
+            ..Default::default()
+        };
+
+        let bytes = write_rmesh_compressed(&header, Compression::Gzip(9)).unwrap();
+
+        assert!(is_compressed(&bytes));
+        let read = crate::read_rmesh(&bytes).unwrap();
+        assert_eq!(read.extra, b"trailing");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn output_past_the_limit_is_cut_off() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::best());
+        encoder.write_all(&[0; 4096]).unwrap();
+        let bytes = encoder.finish().unwrap();
+        let decoder = || flate2::read::GzDecoder::new(&bytes[..]);
+
+        assert_eq!(read_limited(decoder(), 4096).unwrap().len(), 4096);
+        assert!(matches!(
+            read_limited(decoder(), 4095),
+            Err(RMeshError::DecompressedTooLarge(4095))
+        ));
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn zstd_needs_its_feature() {
+        assert!(matches!(
+            decompress(&ZSTD_MAGIC),
+            Err(RMeshError::UnsupportedCompression("zstd"))
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+//! Conversion between the Blitz3D coordinate system and other conventions.
+
+use crate::{EntityType, Header, SimpleMesh, ROOM_SCALE};
+
+/// Uniform scale factor between room units and target units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Scale(pub f32);
+
+impl Scale {
+    /// Keeps the original Blitz3D room units.
+    pub const IDENTITY: Self = Self(1.0);
+    /// The scale the original game uses to place rooms in the world.
+    pub const ROOM: Self = Self(ROOM_SCALE);
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::ROOM
+    }
+}
+
+impl From<f32> for Scale {
+    fn from(value: f32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Scale> for f32 {
+    fn from(value: Scale) -> Self {
+        value.0
+    }
+}
+
+/// Describes how to move room data into another coordinate system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateTransform {
+    /// Uniform scale applied to positions and distances.
+    pub scale: f32,
+    /// Negate the Z axis, switching between left- and right-handed coordinates.
+    pub flip_z: bool,
+}
+
+impl CoordinateTransform {
+    /// Converts the left-handed Blitz3D room space into right-handed world units.
+    pub const BLITZ3D_TO_RIGHT_HANDED: Self = Self {
+        scale: ROOM_SCALE,
+        flip_z: true,
+    };
+
+    /// Transforms a position.
+    pub fn position(&self, position: [f32; 3]) -> [f32; 3] {
+        let [x, y, z] = position;
+        let z = if self.flip_z { -z } else { z };
+        [x * self.scale, y * self.scale, z * self.scale]
+    }
+
+    /// Transforms pitch, yaw and roll angles in degrees.
+    ///
+    /// Mirroring the Z axis reverses the direction of rotations around X and Y.
+    pub fn rotation(&self, rotation: [f32; 3]) -> [f32; 3] {
+        let [pitch, yaw, roll] = rotation;
+        if self.flip_z {
+            [-pitch, -yaw, roll]
+        } else {
+            [pitch, yaw, roll]
+        }
+    }
+
+    /// Reorders a triangle so it keeps facing outwards after the transform.
+    pub fn triangle(&self, triangle: [u32; 3]) -> [u32; 3] {
+        let [a, b, c] = triangle;
+        if self.flip_z {
+            [c, b, a]
+        } else {
+            [a, b, c]
+        }
+    }
+
+    /// Applies the transform to all geometry and entities of a room in place.
+    pub fn apply(&self, header: &mut Header) {
+        for mesh in &mut header.meshes {
+            for vertex in &mut mesh.vertices {
+                vertex.position = self.position(vertex.position);
+            }
+            for triangle in &mut mesh.triangles {
+                *triangle = self.triangle(*triangle);
+            }
+        }
+
+        for collider in &mut header.colliders {
+            self.apply_simple_mesh(collider);
+        }
+
+        for trigger_box in &mut header.trigger_boxes {
+            for mesh in &mut trigger_box.meshes {
+                self.apply_simple_mesh(mesh);
+            }
+        }
+
+        for entity in &mut header.entities {
+            if let Some(entity_type) = &mut entity.entity_type {
+                self.apply_entity(entity_type);
+            }
+        }
+    }
+
+    fn apply_simple_mesh(&self, mesh: &mut SimpleMesh) {
+        for vertex in &mut mesh.vertices {
+            *vertex = self.position(*vertex);
+        }
+        for triangle in &mut mesh.triangles {
+            *triangle = self.triangle(*triangle);
+        }
+    }
+
+    fn apply_entity(&self, entity_type: &mut EntityType) {
+        let position = entity_type.position_mut();
+        *position = self.position(*position);
+
+        match entity_type {
+            EntityType::Light(data) => data.range *= self.scale,
+            EntityType::SpotLight(data) => {
+                data.range *= self.scale;
+                data.angles.0 = self.rotation(data.angles.0);
+            }
+            EntityType::PlayerStart(data) => data.angles.0 = self.rotation(data.angles.0),
+            EntityType::SoundEmitter(data) => data.idk1 *= self.scale,
+            EntityType::Model(data) => {
+                data.rotation = self.rotation(data.rotation);
+                data.scale = data.scale.map(|s| s * self.scale);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Header {
+    /// Returns a copy of the room with all geometry and entities scaled by `factor`.
+    pub fn scaled(&self, factor: impl Into<Scale>) -> Header {
+        let mut header = self.clone();
+        CoordinateTransform {
+            scale: factor.into().0,
+            flip_z: false,
+        }
+        .apply(&mut header);
+        header
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityData, EntitySoundEmitter, EntitySpotlight};
+
+    fn entity(entity_type: EntityType) -> EntityData {
+        EntityData {
+            entity_type: Some(entity_type),
+        }
+    }
+
+    #[test]
+    fn distances_scale_with_the_room() {
+        let mut header = Header::default();
+        header
+            .entities
+            .push(entity(EntityType::SoundEmitter(EntitySoundEmitter {
+                position: [10.0, 0.0, 20.0],
+                idk0: 3,
+                idk1: 400.0,
+            })));
+        header
+            .entities
+            .push(entity(EntityType::SpotLight(EntitySpotlight {
+                position: [0.0; 3],
+                range: 100.0,
+                color: [255; 3].into(),
+                intensity: 1.0,
+                angles: [10.0, 20.0, 30.0].into(),
+                inner_cone_angle: 10.0,
+                outer_cone_angle: 20.0,
+            })));
+
+        CoordinateTransform {
+            scale: 0.5,
+            flip_z: true,
+        }
+        .apply(&mut header);
+
+        let Some(EntityType::SoundEmitter(sound)) = &header.entities[0].entity_type else {
+            panic!("expected the sound emitter");
+        };
+        assert_eq!(sound.position, [5.0, 0.0, -10.0]);
+        assert_eq!(sound.idk0, 3);
+        assert_eq!(sound.idk1, 200.0);
+        let Some(EntityType::SpotLight(light)) = &header.entities[1].entity_type else {
+            panic!("expected the spot light");
+        };
+        assert_eq!(light.range, 50.0);
+        assert_eq!(light.angles.0, [-10.0, -20.0, 30.0]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Approximate convex decomposition of collision geometry.
+//!
+//! Geometry is split recursively along the longest axis until every piece is close
+//! enough to its convex hull, similar in spirit to V-HACD.
+
+use crate::hull::convex_hull;
+use crate::math;
+use crate::{Bounds, Header, SimpleMesh};
+
+/// Tunes the decomposition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecompositionOptions {
+    /// Largest allowed distance, in room units, between the geometry and its hull.
+    pub max_concavity: f32,
+    /// How often pieces may be split, limiting the result to `2^max_depth` hulls.
+    pub max_depth: u32,
+    /// Pieces with fewer triangles are never split further.
+    pub min_triangles: usize,
+}
+
+impl Default for DecompositionOptions {
+    fn default() -> Self {
+        Self {
+            max_concavity: 16.0,
+            max_depth: 8,
+            min_triangles: 2,
+        }
+    }
+}
+
+/// Splits triangle soup into convex hulls according to `options`.
+pub fn decompose(triangles: &[[[f32; 3]; 3]], options: &DecompositionOptions) -> Vec<SimpleMesh> {
+    let mut hulls = vec![];
+    split(triangles.to_vec(), options, 0, &mut hulls);
+    hulls
+}
+
+fn split(
+    triangles: Vec<[[f32; 3]; 3]>,
+    options: &DecompositionOptions,
+    depth: u32,
+    hulls: &mut Vec<SimpleMesh>,
+) {
+    if triangles.is_empty() {
+        return;
+    }
+
+    let points: Vec<_> = triangles.iter().flatten().copied().collect();
+    // Flat pieces have no hull, use a thin box around them instead
+    let mut hull = convex_hull(&points);
+    if hull.triangles.is_empty() {
+        hull = box_hull(&Bounds::from_points(points.iter().copied()));
+    }
+
+    let done = depth >= options.max_depth
+        || triangles.len() < options.min_triangles.max(2)
+        || concavity(&hull, &points, &triangles) <= options.max_concavity;
+    if done {
+        hulls.push(hull);
+        return;
+    }
+
+    // Split at the median triangle centroid along the longest axis
+    let centroid =
+        |t: &[[f32; 3]; 3]| math::scale(math::add(math::add(t[0], t[1]), t[2]), 1.0 / 3.0);
+    let size = Bounds::from_points(triangles.iter().map(centroid)).size();
+    let axis = (0..3)
+        .max_by(|a, b| size[*a].total_cmp(&size[*b]))
+        .unwrap_or(0);
+
+    let mut triangles = triangles;
+    let mid = triangles.len() / 2;
+    triangles.select_nth_unstable_by(mid, |a, b| centroid(a)[axis].total_cmp(&centroid(b)[axis]));
+    let right = triangles.split_off(mid);
+
+    split(triangles, options, depth + 1, hulls);
+    split(right, options, depth + 1, hulls);
+}
+
+/// How far the geometry deviates from its hull.
+///
+/// Measures both points sinking below the hull surface and hull faces spanning
+/// empty space away from the geometry.
+fn concavity(hull: &SimpleMesh, points: &[[f32; 3]], triangles: &[[[f32; 3]; 3]]) -> f32 {
+    let faces: Vec<_> = hull
+        .triangles
+        .iter()
+        .map(|triangle| triangle.map(|i| hull.vertices[i as usize]))
+        .collect();
+    let planes: Vec<_> = faces
+        .iter()
+        .map(|[a, b, c]| {
+            (
+                math::normalize(math::cross(math::sub(*b, *a), math::sub(*c, *a))),
+                *a,
+            )
+        })
+        .collect();
+
+    let depth = points
+        .iter()
+        .map(|p| {
+            planes
+                .iter()
+                .map(|(normal, a)| -math::dot(*normal, math::sub(*p, *a)))
+                .fold(f32::INFINITY, f32::min)
+        })
+        .fold(0.0, f32::max);
+
+    let gap = faces
+        .iter()
+        .map(|[a, b, c]| {
+            let center = math::scale(math::add(math::add(*a, *b), *c), 1.0 / 3.0);
+            triangles
+                .iter()
+                .map(|[ta, tb, tc]| {
+                    let closest = math::closest_point_on_triangle(center, *ta, *tb, *tc);
+                    math::length(math::sub(closest, center))
+                })
+                .fold(f32::INFINITY, f32::min)
+        })
+        .fold(0.0, f32::max);
+
+    depth.max(gap)
+}
+
+/// Box shaped hull around degenerate geometry, padded so it keeps a volume.
+fn box_hull(bounds: &Bounds) -> SimpleMesh {
+    let bounds = bounds.expand(0.5);
+    let corners: Vec<_> = (0..8)
+        .map(|i| {
+            [0, 1, 2].map(|axis| {
+                if i >> axis & 1 == 0 {
+                    bounds.min[axis]
+                } else {
+                    bounds.max[axis]
+                }
+            })
+        })
+        .collect();
+    convex_hull(&corners)
+}
+
+impl Header {
+    /// Decomposes the room's collision surfaces into convex hulls.
+    pub fn convex_decomposition(&self, options: &DecompositionOptions) -> Vec<SimpleMesh> {
+        let triangles: Vec<_> = self
+            .collision_surfaces()
+            .iter()
+            .flat_map(|surface| {
+                let positions = surface.positions();
+                surface
+                    .triangles()
+                    .iter()
+                    .filter_map(|t| {
+                        let [a, b, c] = t.map(|i| positions.get(i as usize).copied());
+                        Some([a?, b?, c?])
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        decompose(&triangles, options)
+    }
+}
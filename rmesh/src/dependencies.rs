@@ -0,0 +1,54 @@
+use crate::{EntityType, Header, TextureBlendType};
+
+/// An external file referenced by a room.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Dependency {
+    /// Diffuse texture path, relative to the rmesh file.
+    Texture(String),
+    /// Lightmap texture path, relative to the rmesh file.
+    Lightmap(String),
+    /// Prop model name (`.x` or `.b3d`), relative to the `props` directory.
+    Model(String),
+    /// Index into the game's room ambience sound table.
+    Sound(u32),
+    /// Screen image name, relative to [`SCREENS_DIR`](crate::resolve::SCREENS_DIR).
+    Screen(String),
+}
+
+impl Header {
+    /// Lists every external file the room references, without duplicates.
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        let mut dependencies = vec![];
+        let mut push = |dependency: Dependency| {
+            if !dependencies.contains(&dependency) {
+                dependencies.push(dependency);
+            }
+        };
+
+        for mesh in &self.meshes {
+            for texture in &mesh.textures {
+                if let Some(path) = &texture.path {
+                    let path = String::from(path);
+                    if texture.blend_type == TextureBlendType::Lightmap {
+                        push(Dependency::Lightmap(path));
+                    } else {
+                        push(Dependency::Texture(path));
+                    }
+                }
+            }
+        }
+
+        for entity in &self.entities {
+            match &entity.entity_type {
+                Some(EntityType::Model(data)) => push(Dependency::Model(String::from(&data.name))),
+                Some(EntityType::SoundEmitter(data)) => push(Dependency::Sound(data.idk0)),
+                Some(EntityType::Screen(data)) => {
+                    push(Dependency::Screen(String::from(&data.name)))
+                }
+                _ => (),
+            }
+        }
+
+        dependencies
+    }
+}
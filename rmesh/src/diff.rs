@@ -0,0 +1,248 @@
+use std::io::Cursor;
+
+use binrw::BinWriterExt;
+
+use crate::{ComplexMesh, EntityData, Header};
+
+/// Differences between two versions of a room, see [`diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RMeshDiff {
+    /// Indices of meshes only in the new room.
+    pub added_meshes: Vec<usize>,
+    /// Indices of meshes only in the old room.
+    pub removed_meshes: Vec<usize>,
+    /// Meshes present in both rooms with different geometry or textures.
+    pub changed_meshes: Vec<MeshChange>,
+    /// Indices of entities only in the new room.
+    pub added_entities: Vec<usize>,
+    /// Indices of entities only in the old room.
+    pub removed_entities: Vec<usize>,
+    /// Entities present in both rooms at different positions.
+    pub moved_entities: Vec<EntityMove>,
+    /// Entities present in both rooms with other properties changed.
+    pub changed_entities: Vec<[usize; 2]>,
+    /// Change in visual vertex count over the whole room.
+    pub vertex_delta: i64,
+    /// Change in visual triangle count over the whole room.
+    pub triangle_delta: i64,
+    pub colliders_changed: bool,
+    pub trigger_boxes_changed: bool,
+}
+
+impl RMeshDiff {
+    /// Whether the rooms have the same content.
+    pub fn is_empty(&self) -> bool {
+        self.added_meshes.is_empty()
+            && self.removed_meshes.is_empty()
+            && self.changed_meshes.is_empty()
+            && self.added_entities.is_empty()
+            && self.removed_entities.is_empty()
+            && self.moved_entities.is_empty()
+            && self.changed_entities.is_empty()
+            && !self.colliders_changed
+            && !self.trigger_boxes_changed
+    }
+}
+
+/// A mesh matched between both rooms.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshChange {
+    pub old_index: usize,
+    pub new_index: usize,
+    pub geometry_changed: bool,
+    pub vertex_delta: i64,
+    pub triangle_delta: i64,
+    /// Texture slots whose path changed, `0` is the lightmap and `1` the diffuse texture.
+    pub texture_changes: Vec<TextureChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureChange {
+    pub slot: usize,
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityMove {
+    pub old_index: usize,
+    pub new_index: usize,
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+}
+
+/// Compares two versions of a room.
+///
+/// Identical meshes and entities are matched first wherever they are in the file. The
+/// remaining meshes pair up by geometry, then by textures, then in order. Remaining
+/// entities only pair up with one of the same type.
+pub fn diff(old: &Header, new: &Header) -> RMeshDiff {
+    let mut diff = RMeshDiff {
+        vertex_delta: vertex_count(new) - vertex_count(old),
+        triangle_delta: triangle_count(new) - triangle_count(old),
+        colliders_changed: old.colliders.len() != new.colliders.len()
+            || old
+                .colliders
+                .iter()
+                .zip(&new.colliders)
+                .any(|(a, b)| a.vertices != b.vertices || a.triangles != b.triangles),
+        trigger_boxes_changed: old.trigger_boxes.len() != new.trigger_boxes.len()
+            || old
+                .trigger_boxes
+                .iter()
+                .zip(&new.trigger_boxes)
+                .any(|(a, b)| {
+                    a.name != b.name
+                        || a.meshes.len() != b.meshes.len()
+                        || a.meshes
+                            .iter()
+                            .zip(&b.meshes)
+                            .any(|(a, b)| a.vertices != b.vertices || a.triangles != b.triangles)
+                }),
+        ..Default::default()
+    };
+
+    let old_hashes: Vec<_> = old.meshes.iter().map(ComplexMesh::geometry_hash).collect();
+    let new_hashes: Vec<_> = new.meshes.iter().map(ComplexMesh::geometry_hash).collect();
+    let (mut pairs, mut removed, mut added) =
+        match_items(old.meshes.len(), new.meshes.len(), |a, b| {
+            old_hashes[a] == new_hashes[b] && old.meshes[a].textures == new.meshes[b].textures
+        });
+    // Retextured meshes keep their geometry, edited ones usually keep their textures
+    pairs.extend(pair_leftovers(&mut removed, &mut added, |a, b| {
+        old_hashes[a] == new_hashes[b]
+    }));
+    pairs.extend(pair_leftovers(&mut removed, &mut added, |a, b| {
+        old.meshes[a].textures == new.meshes[b].textures
+    }));
+    pairs.extend(pair_leftovers(&mut removed, &mut added, |_, _| true));
+    diff.removed_meshes = removed;
+    diff.added_meshes = added;
+
+    for (old_index, new_index) in pairs {
+        let (a, b) = (&old.meshes[old_index], &new.meshes[new_index]);
+        let texture_changes: Vec<_> = (0..2)
+            .filter(|slot| a.textures[*slot].path != b.textures[*slot].path)
+            .map(|slot| TextureChange {
+                slot,
+                old_path: a.textures[slot].path.as_ref().map(String::from),
+                new_path: b.textures[slot].path.as_ref().map(String::from),
+            })
+            .collect();
+        let change = MeshChange {
+            old_index,
+            new_index,
+            geometry_changed: old_hashes[old_index] != new_hashes[new_index],
+            vertex_delta: b.vertices.len() as i64 - a.vertices.len() as i64,
+            triangle_delta: b.triangles.len() as i64 - a.triangles.len() as i64,
+            texture_changes,
+        };
+        if change.geometry_changed || !change.texture_changes.is_empty() {
+            diff.changed_meshes.push(change);
+        }
+    }
+
+    let old_entities: Vec<_> = old.entities.iter().map(EntityKey::new).collect();
+    let new_entities: Vec<_> = new.entities.iter().map(EntityKey::new).collect();
+    let (_, mut removed, mut added) =
+        match_items(old_entities.len(), new_entities.len(), |a, b| {
+            old_entities[a] == new_entities[b]
+        });
+    let pairs = pair_leftovers(&mut removed, &mut added, |a, b| {
+        old_entities[a].name == new_entities[b].name
+    });
+    diff.removed_entities = removed;
+    diff.added_entities = added;
+
+    for (old_index, new_index) in pairs {
+        let (a, b) = (&old_entities[old_index], &new_entities[new_index]);
+        if a.position != b.position {
+            diff.moved_entities.push(EntityMove {
+                old_index,
+                new_index,
+                from: a.position,
+                to: b.position,
+            });
+        }
+        if a.bytes != b.bytes {
+            diff.changed_entities.push([old_index, new_index]);
+        }
+    }
+
+    diff
+}
+
+fn vertex_count(header: &Header) -> i64 {
+    header.meshes.iter().map(|m| m.vertices.len() as i64).sum()
+}
+
+fn triangle_count(header: &Header) -> i64 {
+    header.meshes.iter().map(|m| m.triangles.len() as i64).sum()
+}
+
+/// Pairs up equal items, returning the pairs and the unmatched indices on either side.
+#[allow(clippy::type_complexity)]
+fn match_items(
+    old_len: usize,
+    new_len: usize,
+    equal: impl Fn(usize, usize) -> bool,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut pairs = vec![];
+    let mut unmatched_old = vec![];
+    let mut unmatched_new: Vec<_> = (0..new_len).collect();
+    for a in 0..old_len {
+        match unmatched_new.iter().position(|&b| equal(a, b)) {
+            Some(position) => pairs.push((a, unmatched_new.remove(position))),
+            None => unmatched_old.push(a),
+        }
+    }
+    (pairs, unmatched_old, unmatched_new)
+}
+
+/// Pairs unmatched items in order wherever `compatible` allows, removing them from the lists.
+fn pair_leftovers(
+    old: &mut Vec<usize>,
+    new: &mut Vec<usize>,
+    compatible: impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+    old.retain(|&a| match new.iter().position(|&b| compatible(a, b)) {
+        Some(position) => {
+            pairs.push((a, new.remove(position)));
+            false
+        }
+        None => true,
+    });
+    pairs
+}
+
+/// An entity split into its position and everything else.
+#[derive(PartialEq)]
+struct EntityKey {
+    name: &'static str,
+    position: [f32; 3],
+    /// The entity serialized with its position zeroed.
+    bytes: Vec<u8>,
+}
+
+impl EntityKey {
+    fn new(entity: &EntityData) -> Self {
+        let mut entity = entity.clone();
+        let (name, position) = match &mut entity.entity_type {
+            Some(entity_type) => {
+                let position = std::mem::take(entity_type.position_mut());
+                (entity_type.name(), position)
+            }
+            None => ("", [0.0; 3]),
+        };
+        let mut bytes = vec![];
+        Cursor::new(&mut bytes)
+            .write_le(&entity)
+            .expect("writing to a Vec can't fail");
+        Self {
+            name,
+            position,
+            bytes,
+        }
+    }
+}
@@ -1,19 +1,27 @@
 use binrw::{BinRead, BinWrite};
 
-use crate::strings::{FixedLengthString, ThreeTypeString};
+use crate::strings::{AnglesString, FixedLengthString, ThreeTypeString};
 
-#[derive(BinRead, BinWrite, Debug)]
+/// Placement of an entity, in room space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityTransform {
+    pub translation: [f32; 3],
+    /// Quaternion as `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+}
+
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntityScreen {
     pub position: [f32; 3],
     pub name: FixedLengthString,
 }
 
-#[derive(BinRead, BinWrite, Debug)]
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntityWaypoint {
     pub position: [f32; 3],
 }
 
-#[derive(BinRead, BinWrite, Debug)]
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntityLight {
     pub position: [f32; 3],
     pub range: f32,
@@ -21,31 +29,78 @@ pub struct EntityLight {
     pub intensity: f32,
 }
 
-#[derive(BinRead, BinWrite, Debug)]
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntitySpotlight {
     pub position: [f32; 3],
     pub range: f32,
     pub color: ThreeTypeString,
     pub intensity: f32,
-    pub angles: ThreeTypeString,
+    pub angles: AnglesString,
     pub inner_cone_angle: f32,
     pub outer_cone_angle: f32,
 }
 
-#[derive(BinRead, BinWrite, Debug)]
+impl EntitySpotlight {
+    /// Unit vector the light shines along, in room space.
+    pub fn direction(&self) -> [f32; 3] {
+        self.angles.forward()
+    }
+
+    /// Rotation turning Z to [`EntitySpotlight::direction`], see [`AnglesString::quaternion`].
+    pub fn rotation(&self) -> [f32; 4] {
+        self.angles.quaternion()
+    }
+
+    /// Inner and outer half angles of the cone in radians, the way most renderers take them.
+    ///
+    /// The cone angles are full angles in degrees. The outer angle is clamped to a hemisphere and
+    /// the inner angle to the outer one, invalid angles become 0.
+    pub fn cone_angles(&self) -> [f32; 2] {
+        let outer = match self.outer_cone_angle {
+            angle if angle.is_nan() => 0.0,
+            angle => angle.clamp(0.0, 180.0),
+        };
+        let inner = self.inner_cone_angle.max(0.0).min(outer);
+        [inner, outer].map(|angle| (angle * 0.5).to_radians())
+    }
+
+    /// Whether the cone angles are finite with `0 <= inner <= outer <= 180` degrees.
+    pub fn has_valid_cone(&self) -> bool {
+        (0.0..=self.outer_cone_angle).contains(&self.inner_cone_angle)
+            && self.outer_cone_angle <= 180.0
+    }
+}
+
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntitySoundEmitter {
     pub position: [f32; 3],
+    /// Index of the room ambience sound.
     pub idk0: u32,
+    /// Distance the sound carries, in room units like the light ranges.
     pub idk1: f32,
 }
 
-#[derive(BinRead, BinWrite, Debug)]
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntityPlayerStart {
     pub position: [f32; 3],
-    pub angles: ThreeTypeString,
+    pub angles: AnglesString,
+}
+
+impl EntityPlayerStart {
+    /// Where the player spawns and which way they face, see [`AnglesString::forward`].
+    ///
+    /// The position is multiplied by `scale`, like [`ROOM_SCALE`](crate::ROOM_SCALE). Both stay
+    /// in room space, engines with a mirrored Z negate the Z of the translation and the X and Y
+    /// of the rotation.
+    pub fn transform(&self, scale: f32) -> EntityTransform {
+        EntityTransform {
+            translation: self.position.map(|x| x * scale),
+            rotation: self.angles.quaternion(),
+        }
+    }
 }
 
-#[derive(BinRead, BinWrite, Debug)]
+#[derive(BinRead, BinWrite, Debug, Clone)]
 pub struct EntityModel {
     pub name: FixedLengthString,
     pub position: [f32; 3],
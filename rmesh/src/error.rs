@@ -8,4 +8,28 @@ pub enum RMeshError {
     NonUTF8(#[from] FromUtf8Error),
     #[error("Error while trying to write data: {0}")]
     BinRwError(#[from] binrw::Error),
+    #[error("Triangle {triangle} references vertex {index} but the mesh only has {vertex_count}")]
+    InvalidIndex {
+        triangle: usize,
+        index: u32,
+        vertex_count: usize,
+    },
+    #[error("Texel density must be positive and finite, got {0}")]
+    InvalidTexelDensity(f32),
+    #[error("Invalid .x file: {0}")]
+    InvalidX(String),
+    #[error("Invalid rooms.ini: {0}")]
+    InvalidIni(String),
+    #[error("Room {0} isn't in rooms.ini or its rmesh file is missing")]
+    MissingRoom(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("File is {0} compressed, enable the `{0}` feature to read it")]
+    UnsupportedCompression(&'static str),
+    #[error("File is compressed and can't be viewed in place, decompress it first")]
+    Compressed,
+    #[error("Decompressed file exceeds the {0} byte limit")]
+    DecompressedTooLarge(u64),
+    #[error("Unsupported .rmpack version {0}")]
+    UnsupportedRmPackVersion(u32),
 }
@@ -0,0 +1,90 @@
+//! Assembles a whole facility from a map seed's room grid, for baking full maps offline.
+//!
+//! The map generator places rooms on a grid in 90 degree turns. Rooms are looked up by name in
+//! `rooms.ini`, then either merged into one [`Header`] or listed with their placement.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::math;
+use crate::rooms_ini::RoomsIni;
+use crate::{read_rmesh, Header, RMeshError};
+
+/// Width of a grid cell in room units, 8 world units in game.
+pub const CELL_SIZE: f32 = 2048.0;
+
+/// A room the map generator placed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomPlacement {
+    /// Section name in `rooms.ini`.
+    pub name: String,
+    /// Grid column and row, along X and Z.
+    pub cell: [i32; 2],
+    /// Yaw in degrees, a multiple of 90.
+    pub angle: i32,
+}
+
+/// A placed room with its rmesh file found, one entry of a placement manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedRoom {
+    pub placement: RoomPlacement,
+    pub mesh_path: PathBuf,
+    /// Column-major matrix from the room to the facility, in room units.
+    pub transform: [[f32; 4]; 4],
+}
+
+impl RoomPlacement {
+    /// Column-major matrix turning the room by its angle and moving it to its cell.
+    pub fn transform(&self) -> [[f32; 4]; 4] {
+        let rotation = math::euler_to_matrix([0.0, self.angle as f32, 0.0]);
+        let [x, z] = self.cell.map(|cell| cell as f32 * CELL_SIZE);
+        let column = |c: usize| [rotation[0][c], rotation[1][c], rotation[2][c], 0.0];
+        [column(0), column(1), column(2), [x, 0.0, z, 1.0]]
+    }
+}
+
+/// Looks up the rmesh file of every placed room under the game directory.
+///
+/// Fails on the first room missing from `rooms.ini` or whose file can't be found.
+pub fn manifest(
+    ini: &RoomsIni,
+    game_dir: &Path,
+    placements: &[RoomPlacement],
+) -> Result<Vec<PlacedRoom>, RMeshError> {
+    placements
+        .iter()
+        .map(|placement| {
+            let mesh_path = ini
+                .room(&placement.name)
+                .and_then(|room| room.find_mesh(game_dir))
+                .ok_or_else(|| RMeshError::MissingRoom(placement.name.clone()))?;
+            Ok(PlacedRoom {
+                placement: placement.clone(),
+                mesh_path,
+                transform: placement.transform(),
+            })
+        })
+        .collect()
+}
+
+/// Reads every room of a manifest and merges them into one room, see [`Header::merge`].
+///
+/// Rooms sharing a file are read once.
+pub fn assemble(manifest: &[PlacedRoom]) -> Result<Header, RMeshError> {
+    let mut facility = Header::default();
+    let mut cache: Vec<(&Path, Header)> = vec![];
+    for placed in manifest {
+        let room = match cache.iter().find(|(path, _)| *path == placed.mesh_path) {
+            Some((_, room)) => room,
+            None => {
+                let room = read_rmesh(&fs::read(&placed.mesh_path)?)?;
+                cache.push((&placed.mesh_path, room));
+                &cache.last().unwrap().1
+            }
+        };
+        let mut room = room.clone();
+        room.transform(&placed.transform);
+        facility.merge(room, [0.0; 3]);
+    }
+    Ok(facility)
+}
@@ -0,0 +1,175 @@
+//! Random rooms for fuzzing, enabled by the `arbitrary` feature.
+//!
+//! Generated rooms are structurally valid: triangles only index existing vertices, counts
+//! match their lists and strings parse back, so every room can be written and read again.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    AnglesString, ComplexMesh, EntityData, EntityLight, EntityModel, EntityPlayerStart,
+    EntityScreen, EntitySoundEmitter, EntitySpotlight, EntityType, EntityWaypoint,
+    FixedLengthString, Header, SimpleMesh, Texture, TextureBlendType, ThreeTypeString, TriggerBox,
+    Vertex,
+};
+
+/// Triangles indexing `vertex_count` vertices, none without vertices.
+fn triangles(u: &mut Unstructured, vertex_count: usize) -> Result<Vec<[u32; 3]>> {
+    let Some(max) = vertex_count.checked_sub(1) else {
+        return Ok(vec![]);
+    };
+    let max = max as u32;
+    let len = u.arbitrary_len::<[u32; 3]>()?;
+    (0..len)
+        .map(|_| {
+            Ok([
+                u.int_in_range(0..=max)?,
+                u.int_in_range(0..=max)?,
+                u.int_in_range(0..=max)?,
+            ])
+        })
+        .collect()
+}
+
+impl<'a> Arbitrary<'a> for FixedLengthString {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(String::arbitrary(u)?.into())
+    }
+}
+
+impl<'a> Arbitrary<'a> for ThreeTypeString {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(<[u8; 3]>::arbitrary(u)?.into())
+    }
+}
+
+impl<'a> Arbitrary<'a> for AnglesString {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(<[f32; 3]>::arbitrary(u)?.into())
+    }
+}
+
+impl<'a> Arbitrary<'a> for TextureBlendType {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3u8)? {
+            0 => Self::None,
+            1 => Self::Visible,
+            2 => Self::Lightmap,
+            _ => Self::Transparent,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Texture {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let blend_type = TextureBlendType::arbitrary(u)?;
+        // Empty slots have no path in the file
+        let path = match blend_type {
+            TextureBlendType::None => None,
+            _ => Some(FixedLengthString::arbitrary(u)?),
+        };
+        Ok(Self { blend_type, path })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Vertex {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            position: u.arbitrary()?,
+            tex_coords: u.arbitrary()?,
+            color: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ComplexMesh {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let textures = u.arbitrary()?;
+        let vertices: Vec<Vertex> = u.arbitrary()?;
+        let triangles = triangles(u, vertices.len())?;
+        Ok(Self {
+            textures,
+            vertices,
+            triangles,
+            extension: None,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for SimpleMesh {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let vertices: Vec<[f32; 3]> = u.arbitrary()?;
+        let triangles = triangles(u, vertices.len())?;
+        Ok(Self::new(vertices, triangles))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TriggerBox {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            meshes: u.arbitrary()?,
+            name: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for EntityType {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=6u8)? {
+            0 => Self::Screen(EntityScreen {
+                position: u.arbitrary()?,
+                name: u.arbitrary()?,
+            }),
+            1 => Self::WayPoint(EntityWaypoint {
+                position: u.arbitrary()?,
+            }),
+            2 => Self::Light(EntityLight {
+                position: u.arbitrary()?,
+                range: u.arbitrary()?,
+                color: u.arbitrary()?,
+                intensity: u.arbitrary()?,
+            }),
+            3 => Self::SpotLight(EntitySpotlight {
+                position: u.arbitrary()?,
+                range: u.arbitrary()?,
+                color: u.arbitrary()?,
+                intensity: u.arbitrary()?,
+                angles: u.arbitrary()?,
+                inner_cone_angle: u.arbitrary()?,
+                outer_cone_angle: u.arbitrary()?,
+            }),
+            4 => Self::SoundEmitter(EntitySoundEmitter {
+                position: u.arbitrary()?,
+                idk0: u.arbitrary()?,
+                idk1: u.arbitrary()?,
+            }),
+            5 => Self::PlayerStart(EntityPlayerStart {
+                position: u.arbitrary()?,
+                angles: u.arbitrary()?,
+            }),
+            _ => Self::Model(EntityModel {
+                name: u.arbitrary()?,
+                position: u.arbitrary()?,
+                rotation: u.arbitrary()?,
+                scale: u.arbitrary()?,
+            }),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for EntityData {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Header {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            meshes: u.arbitrary()?,
+            colliders: u.arbitrary()?,
+            trigger_boxes: u.arbitrary()?,
+            entities: u.arbitrary()?,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use std::io::Cursor;
+
+use binrw::BinWriterExt;
+
+use crate::{ComplexMesh, Header, SimpleMesh, Texture};
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` it gives the same result on every platform and
+/// compiler version so hashes can be stored.
+struct ContentHasher(u64);
+
+impl ContentHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.bytes(&(len as u64).to_le_bytes());
+    }
+
+    /// Hashes by value, so `-0.0` matches `0.0` and every NaN matches.
+    fn f32(&mut self, value: f32) {
+        let value = if value == 0.0 {
+            0.0
+        } else if value.is_nan() {
+            f32::NAN
+        } else {
+            value
+        };
+        self.u32(value.to_bits());
+    }
+
+    fn texture(&mut self, texture: &Texture) {
+        self.bytes(&[texture.blend_type as u8]);
+        match &texture.path {
+            Some(path) => {
+                self.len(path.values.len());
+                self.bytes(&path.values);
+            }
+            None => self.len(usize::MAX),
+        }
+    }
+
+    fn geometry(&mut self, mesh: &ComplexMesh) {
+        self.len(mesh.vertices.len());
+        for vertex in &mesh.vertices {
+            vertex.position.iter().for_each(|c| self.f32(*c));
+            vertex
+                .tex_coords
+                .iter()
+                .flatten()
+                .for_each(|c| self.f32(*c));
+            self.bytes(&vertex.color);
+        }
+        self.triangles(&mesh.triangles);
+    }
+
+    fn simple(&mut self, mesh: &SimpleMesh) {
+        self.len(mesh.vertices.len());
+        mesh.vertices.iter().flatten().for_each(|c| self.f32(*c));
+        self.triangles(&mesh.triangles);
+    }
+
+    fn triangles(&mut self, triangles: &[[u32; 3]]) {
+        self.len(triangles.len());
+        triangles.iter().flatten().for_each(|i| self.u32(*i));
+    }
+}
+
+impl ComplexMesh {
+    /// Stable hash over the vertices and triangles, ignoring textures.
+    ///
+    /// Meshes with equal hashes are almost certainly the same geometry.
+    pub fn geometry_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+        hasher.geometry(self);
+        hasher.0
+    }
+}
+
+impl Header {
+    /// Stable hash over the geometry, textures and entities of the room.
+    ///
+    /// Only the content counts: re-saving a room, compressing it or switching format
+    /// variant gives the same hash.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+
+        hasher.len(self.meshes.len());
+        for mesh in &self.meshes {
+            mesh.textures.iter().for_each(|t| hasher.texture(t));
+            hasher.geometry(mesh);
+        }
+
+        hasher.len(self.colliders.len());
+        self.colliders.iter().for_each(|c| hasher.simple(c));
+
+        hasher.len(self.trigger_boxes.len());
+        for trigger_box in &self.trigger_boxes {
+            hasher.len(trigger_box.meshes.len());
+            trigger_box.meshes.iter().for_each(|m| hasher.simple(m));
+            hasher.len(trigger_box.name.values.len());
+            hasher.bytes(&trigger_box.name.values);
+        }
+
+        hasher.len(self.entities.len());
+        let mut bytes = vec![];
+        for entity in &self.entities {
+            bytes.clear();
+            Cursor::new(&mut bytes)
+                .write_le(entity)
+                .expect("writing to a Vec can't fail");
+            hasher.len(bytes.len());
+            hasher.bytes(&bytes);
+        }
+
+        hasher.0
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use crate::math;
+use crate::{Bounds, ComplexMesh, SimpleMesh};
+
+impl SimpleMesh {
+    /// Creates a mesh from vertices and triangles, filling in the counts.
+    pub fn new(vertices: Vec<[f32; 3]>, triangles: Vec<[u32; 3]>) -> Self {
+        Self {
+            vertex_count: vertices.len() as u32,
+            vertices,
+            triangle_count: triangles.len() as u32,
+            triangles,
+        }
+    }
+
+    /// Watertight convex hull of the vertices, with outward facing triangles.
+    pub fn convex_hull(&self) -> SimpleMesh {
+        convex_hull(&self.vertices)
+    }
+}
+
+impl ComplexMesh {
+    /// Watertight convex hull of the vertex positions, with outward facing triangles.
+    pub fn convex_hull(&self) -> SimpleMesh {
+        let positions = self.positions();
+        convex_hull(&positions)
+    }
+}
+
+/// Incremental convex hull, empty when the points don't span a volume.
+pub(crate) fn convex_hull(points: &[[f32; 3]]) -> SimpleMesh {
+    let bounds = Bounds::from_points(points.iter().copied());
+    let epsilon = math::length(bounds.size()) * 1e-6;
+
+    let Some(mut faces) = initial_tetrahedron(points, epsilon) else {
+        return SimpleMesh::new(vec![], vec![]);
+    };
+
+    let plane_distance = |face: &[usize; 3], p: [f32; 3]| {
+        let [a, b, c] = face.map(|i| points[i]);
+        let normal = math::normalize(math::cross(math::sub(b, a), math::sub(c, a)));
+        math::dot(normal, math::sub(p, a))
+    };
+
+    for (index, point) in points.iter().enumerate() {
+        let (visible, hidden): (Vec<[usize; 3]>, Vec<_>) = faces
+            .iter()
+            .copied()
+            .partition(|face| plane_distance(face, *point) > epsilon);
+        if visible.is_empty() {
+            continue;
+        }
+
+        // Edges of the visible region whose twin isn't visible form the horizon
+        let visible_edges: HashSet<(usize, usize)> = visible
+            .iter()
+            .flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+            .collect();
+        let horizon = visible_edges
+            .iter()
+            .filter(|(a, b)| !visible_edges.contains(&(*b, *a)));
+
+        faces = hidden;
+        faces.extend(horizon.map(|(a, b)| [*a, *b, index]));
+    }
+
+    // Keep only the vertices used by the hull
+    let mut remap = vec![None; points.len()];
+    let mut vertices = vec![];
+    let triangles = faces
+        .iter()
+        .map(|face| {
+            face.map(|i| {
+                *remap[i].get_or_insert_with(|| {
+                    vertices.push(points[i]);
+                    vertices.len() as u32 - 1
+                })
+            })
+        })
+        .collect();
+
+    SimpleMesh::new(vertices, triangles)
+}
+
+/// Four points spanning a volume, as outward facing triangles.
+fn initial_tetrahedron(points: &[[f32; 3]], epsilon: f32) -> Option<Vec<[usize; 3]>> {
+    let a = 0;
+    let b = (0..points.len()).find(|i| math::length(math::sub(points[*i], points[a])) > epsilon)?;
+    let ab = math::sub(points[b], points[a]);
+    let c = (0..points.len()).find(|i| {
+        math::length(math::cross(ab, math::sub(points[*i], points[a]))) > epsilon * math::length(ab)
+    })?;
+    let normal = math::normalize(math::cross(ab, math::sub(points[c], points[a])));
+    let d = (0..points.len())
+        .find(|i| math::dot(normal, math::sub(points[*i], points[a])).abs() > epsilon)?;
+
+    // Orient the base so the fourth point lies behind it
+    let base = if math::dot(normal, math::sub(points[d], points[a])) > 0.0 {
+        [a, c, b]
+    } else {
+        [a, b, c]
+    };
+    let [a, b, c] = base;
+    Some(vec![[a, b, c], [a, d, b], [b, d, c], [c, d, a]])
+}
@@ -0,0 +1,54 @@
+use crate::{Header, Texture, Vertex};
+
+/// All visual geometry of a room packed into one vertex and one index buffer.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedMesh {
+    /// Interleaved vertices of every mesh.
+    pub vertices: Vec<Vertex>,
+    /// Triangle list indices into `vertices`.
+    pub indices: Vec<u32>,
+    /// One range per original mesh, in the same order as `Header::meshes`.
+    pub draw_ranges: Vec<DrawRange>,
+}
+
+/// A slice of [`IndexedMesh::indices`] drawn with a single texture pair.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrawRange {
+    /// First index of the range.
+    pub start: u32,
+    /// Number of indices in the range.
+    pub count: u32,
+    pub textures: [Texture; 2],
+}
+
+impl Header {
+    /// Packs every mesh into shared buffers with per-mesh draw ranges.
+    pub fn to_indexed_mesh(&self) -> IndexedMesh {
+        let vertex_count = self.meshes.iter().map(|m| m.vertices.len()).sum();
+        let index_count = self.meshes.iter().map(|m| m.triangles.len() * 3).sum();
+
+        let mut indexed = IndexedMesh {
+            vertices: Vec::with_capacity(vertex_count),
+            indices: Vec::with_capacity(index_count),
+            draw_ranges: Vec::with_capacity(self.meshes.len()),
+        };
+
+        for mesh in &self.meshes {
+            let base = indexed.vertices.len() as u32;
+            let start = indexed.indices.len() as u32;
+
+            indexed.vertices.extend(mesh.vertices.iter().cloned());
+            indexed
+                .indices
+                .extend(mesh.triangles.iter().flatten().map(|i| i + base));
+
+            indexed.draw_ranges.push(DrawRange {
+                start,
+                count: indexed.indices.len() as u32 - start,
+                textures: mesh.textures.clone(),
+            });
+        }
+
+        indexed
+    }
+}
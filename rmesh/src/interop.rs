@@ -0,0 +1,175 @@
+//! Typed access to positions and rotations for math libraries.
+//!
+//! The `*_as` accessors return any type built from `[f32; 3]`, like `glam::Vec3`,
+//! `nalgebra::Point3` or `mint::Vector3`. The `glam`, `nalgebra` and `mint` features convert
+//! [`AnglesString`] rotations.
+//!
+//! Quaternions are `[x, y, z, w]` everywhere in the crate, the order `glam` and `mint` use.
+
+use crate::{AnglesString, Bounds, ComplexMesh, EntityType, SimpleMesh, Vertex};
+
+impl AnglesString {
+    /// The rotation in room space as a `[x, y, z, w]` quaternion: roll around Z, then pitch
+    /// around X, then yaw around Y.
+    pub fn quaternion(&self) -> [f32; 4] {
+        let [pitch, yaw, roll] = self.0.map(|angle| angle.to_radians() * 0.5);
+        let (sx, cx) = pitch.sin_cos();
+        let (sy, cy) = yaw.sin_cos();
+        let (sz, cz) = roll.sin_cos();
+        [
+            cy * sx * cz + sy * cx * sz,
+            sy * cx * cz - cy * sx * sz,
+            cy * cx * sz - sy * sx * cz,
+            cy * cx * cz + sy * sx * sz,
+        ]
+    }
+
+    /// Where Z points once rotated, the way lights and the player face. Positive pitch looks
+    /// down.
+    pub fn forward(&self) -> [f32; 3] {
+        let [pitch, yaw, _] = self.0.map(f32::to_radians);
+        [
+            pitch.cos() * yaw.sin(),
+            -pitch.sin(),
+            pitch.cos() * yaw.cos(),
+        ]
+    }
+}
+
+impl Vertex {
+    /// Position in room space as any vector type.
+    pub fn position_as<V: From<[f32; 3]>>(&self) -> V {
+        self.position.into()
+    }
+}
+
+impl ComplexMesh {
+    /// Vertex positions in room space as any vector type.
+    pub fn positions_as<V: From<[f32; 3]>>(&self) -> Vec<V> {
+        self.vertices.iter().map(Vertex::position_as).collect()
+    }
+}
+
+impl SimpleMesh {
+    /// Vertex positions in room space as any vector type.
+    pub fn vertices_as<V: From<[f32; 3]>>(&self) -> Vec<V> {
+        self.vertices.iter().map(|&vertex| vertex.into()).collect()
+    }
+}
+
+impl EntityType {
+    /// Position of the entity in room space.
+    pub fn position_as<V: From<[f32; 3]>>(&self) -> V {
+        self.position().into()
+    }
+}
+
+impl Bounds {
+    /// Smallest corner as any vector type.
+    pub fn min_as<V: From<[f32; 3]>>(&self) -> V {
+        self.min.into()
+    }
+
+    /// Largest corner as any vector type.
+    pub fn max_as<V: From<[f32; 3]>>(&self) -> V {
+        self.max.into()
+    }
+
+    /// Center as any vector type.
+    pub fn center_as<V: From<[f32; 3]>>(&self) -> V {
+        self.center().into()
+    }
+}
+
+/// The rotation in room space: roll around Z, then pitch around X, then yaw around Y.
+#[cfg(feature = "glam")]
+impl From<AnglesString> for glam::Quat {
+    fn from(angles: AnglesString) -> Self {
+        let [pitch, yaw, roll] = angles.0.map(f32::to_radians);
+        glam::Quat::from_euler(glam::EulerRot::YXZ, yaw, pitch, roll)
+    }
+}
+
+/// The rotation in room space: roll around Z, then pitch around X, then yaw around Y.
+#[cfg(feature = "nalgebra")]
+impl From<AnglesString> for nalgebra::UnitQuaternion<f32> {
+    fn from(angles: AnglesString) -> Self {
+        use nalgebra::{UnitQuaternion, Vector3};
+
+        let [pitch, yaw, roll] = angles.0.map(f32::to_radians);
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), pitch)
+            * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), roll)
+    }
+}
+
+/// The rotation in room space: roll around Z, then pitch around X, then yaw around Y.
+#[cfg(feature = "mint")]
+impl From<AnglesString> for mint::Quaternion<f32> {
+    fn from(angles: AnglesString) -> Self {
+        let [x, y, z, w] = angles.quaternion();
+        mint::Quaternion {
+            v: mint::Vector3 { x, y, z },
+            s: w,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math;
+
+    const ANGLES: [[f32; 3]; 4] = [
+        [0.0, 90.0, 0.0],
+        [30.0, -45.0, 10.0],
+        [-80.0, 200.0, 95.0],
+        [45.0, 0.0, 180.0],
+    ];
+
+    #[test]
+    fn quaternions_match_the_blitz3d_rotation() {
+        for angles in ANGLES {
+            let rotation = AnglesString::from(angles).quaternion();
+
+            let matrix = math::linear_part(&math::trs_matrix([0.0; 3], rotation, [1.0; 3]));
+            let expected = math::euler_to_matrix(angles);
+            for (row, expected) in matrix.iter().zip(expected) {
+                for (value, expected) in row.iter().zip(expected) {
+                    assert!((value - expected).abs() < 1e-5, "{angles:?}");
+                }
+            }
+
+            let forward = math::transform_point(
+                &math::trs_matrix([0.0; 3], rotation, [1.0; 3]),
+                [0.0, 0.0, 1.0],
+            );
+            let expected = AnglesString::from(angles).forward();
+            assert!(
+                math::length(math::sub(forward, expected)) < 1e-5,
+                "{angles:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn glam_uses_the_same_order() {
+        for angles in ANGLES {
+            let [x, y, z, w] = AnglesString::from(angles).quaternion();
+            let quat = glam::Quat::from(AnglesString::from(angles));
+            assert!(quat.abs_diff_eq(glam::Quat::from_xyzw(x, y, z, w), 1e-5));
+        }
+    }
+
+    #[test]
+    fn accessors_convert_positions() {
+        let bounds = Bounds::new([0.0, 1.0, 2.0], [4.0, 5.0, 6.0]);
+        let center: [f32; 3] = bounds.center_as();
+        assert_eq!(center, [2.0, 3.0, 4.0]);
+
+        let mesh = SimpleMesh::new(vec![[1.0, 2.0, 3.0]], vec![]);
+        let vertices: Vec<[f32; 3]> = mesh.vertices_as();
+        assert_eq!(vertices, [[1.0, 2.0, 3.0]]);
+    }
+}
@@ -1,16 +1,114 @@
+use std::borrow::Cow;
 use std::io::Cursor;
 
 use binrw::binrw;
 use binrw::prelude::*;
 
+use crate::variant::{has_mesh_data, has_trigger_boxes, section_tag, tag_sections};
+
 // Re-exports
+pub use crate::arrays::VertexArrays;
+#[cfg(feature = "async")]
+pub use crate::asynchronous::read_rmesh_async;
+pub use crate::bounds::Bounds;
+pub use crate::collision::*;
+pub use crate::compression::{write_rmesh_compressed, Compression};
+pub use crate::dependencies::*;
+pub use crate::diff::*;
 pub use crate::entities::*;
 pub use crate::error::RMeshError;
+pub use crate::indexed::*;
+pub use crate::manifold::ManifoldReport;
+pub use crate::map::{MapExport, MapOptions};
+#[cfg(feature = "mmap")]
+pub use crate::mmap::read_rmesh_mmap;
+pub use crate::normals::{NormalOptions, SplitNormals};
+pub use crate::obj::ObjExport;
+#[cfg(feature = "parallel")]
+pub use crate::parallel::read_rmesh_parallel;
+pub use crate::ply::PlyFormat;
+pub use crate::query::Entity;
+#[cfg(feature = "bytemuck")]
+pub use crate::raw::{raw_vertex_views, RawVertex};
+pub use crate::sections::{index, SectionIndex};
+pub use crate::stats::RoomStats;
 pub use crate::strings::*;
-
+pub use crate::usage::{TextureUsage, TextureUsageReport};
+pub use crate::variant::{detect_variant, FormatVariant};
+pub use crate::weld::WeldOptions;
+
+#[cfg(feature = "atlas")]
+pub mod atlas;
+pub mod b3d;
+#[cfg(feature = "bake")]
+pub mod bake;
+pub mod color;
+pub mod convert;
+#[cfg(feature = "decompose")]
+pub mod decompose;
+#[cfg(feature = "facility")]
+pub mod facility;
+pub mod lighting;
+pub mod lightmaps;
+#[cfg(feature = "nav")]
+pub mod nav;
+pub mod patch;
+pub mod resolve;
+pub mod rmpack;
+#[cfg(feature = "rooms-ini")]
+pub mod rooms_ini;
+#[cfg(feature = "spatial")]
+pub mod spatial;
+#[cfg(feature = "unwrap")]
+pub mod unwrap;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+pub mod x;
+
+mod arrays;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod bounds;
+mod collision;
+mod compression;
+mod dependencies;
+mod diff;
 mod entities;
 mod error;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod hash;
+mod hull;
+mod indexed;
+mod interop;
+mod manifold;
+mod map;
+mod math;
+mod merge;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod normals;
+mod obj;
+mod optimize;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "parry")]
+mod parry;
+mod ply;
+mod query;
+#[cfg(feature = "bytemuck")]
+mod raw;
+mod sections;
+mod stats;
 mod strings;
+mod tangents;
+mod transform;
+mod triangles;
+mod trigger;
+mod usage;
+mod uv;
+mod variant;
+mod weld;
 
 pub const ROOM_SCALE: f32 = 8. / 2048.;
 
@@ -23,15 +121,23 @@ pub fn header_tag(trigger_box_count: usize) -> Result<FixedLengthString, RMeshEr
 }
 
 #[binrw]
-#[derive(Debug, Default)]
+#[br(import(lenient: bool))]
+#[derive(Debug, Clone, Default)]
 pub struct Header {
-    #[bw(try_calc(header_tag(trigger_boxes.len())))]
+    /// The header tag as read, rewritten on write to announce the sections the room has.
+    #[br(assert(
+        lenient || tag_sections(&kind.values).is_some(),
+        "unknown header tag {:?}",
+        String::from_utf8_lossy(&kind.values)
+    ))]
+    #[bw(map = |_: &FixedLengthString| self.section_tag())]
     pub kind: FixedLengthString,
 
     #[bw(try_calc(u32::try_from(meshes.len())))]
     mesh_count: u32,
 
-    #[br(count = mesh_count)]
+    #[br(count = mesh_count, args { inner: (has_mesh_data(&kind),) })]
+    #[bw(args(self.writes_mesh_data()))]
     pub meshes: Vec<ComplexMesh>,
 
     #[bw(try_calc(u32::try_from(colliders.len())))]
@@ -41,11 +147,12 @@ pub struct Header {
     #[br(count = collider_count)]
     pub colliders: Vec<SimpleMesh>,
 
-    #[bw(try_calc(u32::try_from(trigger_boxes.len())))]
-    #[br(temp, if(kind.values == b"RoomMesh.HasTriggerBox"))]
+    #[bw(try_calc(u32::try_from(trigger_boxes.len())), if(!trigger_boxes.is_empty()))]
+    #[br(temp, if(has_trigger_boxes(&kind)))]
     trigger_boxes_count: u32,
 
-    #[br(count = trigger_boxes_count, if(kind.values == b"RoomMesh.HasTriggerBox"))]
+    #[br(count = trigger_boxes_count, if(has_trigger_boxes(&kind)))]
+    #[bw(if(!trigger_boxes.is_empty()))]
     pub trigger_boxes: Vec<TriggerBox>,
 
     #[bw(try_calc(u32::try_from(entities.len())))]
@@ -54,10 +161,31 @@ pub struct Header {
 
     #[br(count = entity_count)]
     pub entities: Vec<EntityData>,
+
+    /// Bytes after the entities, kept so editor extensions survive a round trip.
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub extra: Vec<u8>,
+
+    #[br(calc(FormatVariant::detect(&kind.values, &meshes, &extra)))]
+    #[bw(ignore)]
+    pub variant: FormatVariant,
+}
+
+impl Header {
+    /// Whether the meshes are written with their [`MeshExtension`].
+    fn writes_mesh_data(&self) -> bool {
+        self.meshes.iter().any(|mesh| mesh.extension.is_some())
+    }
+
+    /// Tag announcing the sections written for the room.
+    fn section_tag(&self) -> FixedLengthString {
+        section_tag(!self.trigger_boxes.is_empty(), self.writes_mesh_data())
+    }
 }
 
 #[binrw]
-#[derive(Debug, Default)]
+#[brw(import(mesh_data: bool))]
+#[derive(Debug, Clone, Default)]
 pub struct ComplexMesh {
     pub textures: [Texture; 2],
 
@@ -74,10 +202,28 @@ pub struct ComplexMesh {
 
     #[br(count = triangle_count)]
     pub triangles: Vec<[u32; 3]>,
+
+    /// Data CBRE-EX stores after the mesh, `None` for files without it. As soon as one mesh
+    /// has it, the others are written with an empty one.
+    #[br(if(mesh_data))]
+    #[bw(if(mesh_data), map = |extension: &Option<MeshExtension>| extension.clone().unwrap_or_default())]
+    pub extension: Option<MeshExtension>,
 }
 
+/// Per-mesh block of a CBRE-EX file, kept as is so it survives a round trip.
 #[binrw]
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MeshExtension {
+    #[bw(try_calc(u32::try_from(data.len())))]
+    #[br(temp)]
+    len: u32,
+
+    #[br(count = len)]
+    pub data: Vec<u8>,
+}
+
+#[binrw]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Texture {
     pub blend_type: TextureBlendType,
 
@@ -87,7 +233,7 @@ pub struct Texture {
 
 #[binrw]
 #[brw(repr(u8))]
-#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TextureBlendType {
     #[default]
     None,
@@ -97,7 +243,7 @@ pub enum TextureBlendType {
 }
 
 #[binrw]
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub tex_coords: [[f32; 2]; 2],
@@ -105,7 +251,7 @@ pub struct Vertex {
 }
 
 #[binrw]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SimpleMesh {
     pub vertex_count: u32,
 
@@ -119,7 +265,7 @@ pub struct SimpleMesh {
 }
 
 #[binrw]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TriggerBox {
     #[bw(try_calc(u32::try_from(meshes.len())))]
     #[br(temp)]
@@ -132,212 +278,177 @@ pub struct TriggerBox {
 }
 
 impl ExtMesh for SimpleMesh {
-    fn bounding_box(&self) -> Bounds {
-        let mut min_x = f32::INFINITY;
-        let mut min_y = f32::INFINITY;
-        let mut min_z = f32::INFINITY;
-        let mut max_x = f32::NEG_INFINITY;
-        let mut max_y = f32::NEG_INFINITY;
-        let mut max_z = f32::NEG_INFINITY;
-
-        for vertex in &self.vertices {
-            let [x, y, z] = *vertex;
-
-            // Update min values
-            min_x = min_x.min(x);
-            min_y = min_y.min(y);
-            min_z = min_z.min(z);
-
-            // Update max values
-            max_x = max_x.max(x);
-            max_y = max_y.max(y);
-            max_z = max_z.max(z);
-        }
-
-        let min_point = [min_x, min_y, min_z];
-        let max_point = [max_x, max_y, max_z];
-        Bounds::new(min_point, max_point)
+    fn vertex_positions(&self) -> Cow<'_, [[f32; 3]]> {
+        Cow::Borrowed(&self.vertices)
     }
-    
-    fn calculate_normals(&self) -> Vec<[f32; 3]> {
-        // Initialize vertex normals with zero vectors
-        let mut vertex_normals = vec![[0.0, 0.0, 0.0]; self.vertices.len()];
-
-        // Calculate face normals and accumulate them to vertex normals
-        for triangle in &self.triangles {
-            let vertex0 = self.vertices[triangle[0] as usize];
-            let vertex1 = self.vertices[triangle[1] as usize];
-            let vertex2 = self.vertices[triangle[2] as usize];
-
-            let edge1 = [
-                vertex1[0] - vertex0[0],
-                vertex1[1] - vertex0[1],
-                vertex1[2] - vertex0[2],
-            ];
-            let edge2 = [
-                vertex2[0] - vertex0[0],
-                vertex2[1] - vertex0[1],
-                vertex2[2] - vertex0[2],
-            ];
-
-            let normal = [
-                edge1[1] * edge2[2] - edge1[2] * edge2[1],
-                edge1[2] * edge2[0] - edge1[0] * edge2[2],
-                edge1[0] * edge2[1] - edge1[1] * edge2[0],
-            ];
-
-            // Accumulate face normal to the vertices of the triangle
-            for i in 0..3 {
-                let vertex_index = triangle[i] as usize;
-                vertex_normals[vertex_index][0] += normal[0];
-                vertex_normals[vertex_index][1] += normal[1];
-                vertex_normals[vertex_index][2] += normal[2];
-            }
-        }
 
-        // Normalize vertex normals
-        for normal in &mut vertex_normals {
-            let length = (normal[0].powi(2) + normal[1].powi(2) + normal[2].powi(2)).sqrt();
-            if length != 0.0 {
-                normal[0] /= length;
-                normal[1] /= length;
-                normal[2] /= length;
-            }
-        }
-
-        vertex_normals
+    fn triangle_indices(&self) -> &[[u32; 3]] {
+        &self.triangles
     }
 }
 
 impl ExtMesh for ComplexMesh {
-    fn bounding_box(&self) -> Bounds {
-        let mut min_x = f32::INFINITY;
-        let mut min_y = f32::INFINITY;
-        let mut min_z = f32::INFINITY;
-        let mut max_x = f32::NEG_INFINITY;
-        let mut max_y = f32::NEG_INFINITY;
-        let mut max_z = f32::NEG_INFINITY;
-
-        for vertex in &self.vertices {
-            let [x, y, z] = vertex.position;
-
-            // Update min values
-            min_x = min_x.min(x);
-            min_y = min_y.min(y);
-            min_z = min_z.min(z);
-
-            // Update max values
-            max_x = max_x.max(x);
-            max_y = max_y.max(y);
-            max_z = max_z.max(z);
-        }
-
-        let min_point = [min_x, min_y, min_z];
-        let max_point = [max_x, max_y, max_z];
-        Bounds::new(min_point, max_point)
+    fn vertex_positions(&self) -> Cow<'_, [[f32; 3]]> {
+        Cow::Owned(self.positions())
     }
-    
-    fn calculate_normals(&self) -> Vec<[f32; 3]> {
-        // Initialize vertex normals with zero vectors
-        let mut vertex_normals = vec![[0.0, 0.0, 0.0]; self.vertices.len()];
-
-        // Calculate face normals and accumulate them to vertex normals
-        for triangle in &self.triangles {
-            let vertex0 = self.vertices[triangle[0] as usize].position;
-            let vertex1 = self.vertices[triangle[1] as usize].position;
-            let vertex2 = self.vertices[triangle[2] as usize].position;
-
-            let edge1 = [
-                vertex1[0] - vertex0[0],
-                vertex1[1] - vertex0[1],
-                vertex1[2] - vertex0[2],
-            ];
-            let edge2 = [
-                vertex2[0] - vertex0[0],
-                vertex2[1] - vertex0[1],
-                vertex2[2] - vertex0[2],
-            ];
-
-            let normal = [
-                edge1[1] * edge2[2] - edge1[2] * edge2[1],
-                edge1[2] * edge2[0] - edge1[0] * edge2[2],
-                edge1[0] * edge2[1] - edge1[1] * edge2[0],
-            ];
-
-            // Accumulate face normal to the vertices of the triangle
-            for i in 0..3 {
-                let vertex_index = triangle[i] as usize;
-                vertex_normals[vertex_index][0] += normal[0];
-                vertex_normals[vertex_index][1] += normal[1];
-                vertex_normals[vertex_index][2] += normal[2];
-            }
-        }
 
-        // Normalize vertex normals
-        for normal in &mut vertex_normals {
-            let length = (normal[0].powi(2) + normal[1].powi(2) + normal[2].powi(2)).sqrt();
-            if length != 0.0 {
-                normal[0] /= length;
-                normal[1] /= length;
-                normal[2] /= length;
-            }
-        }
+    fn triangle_indices(&self) -> &[[u32; 3]] {
+        &self.triangles
+    }
 
-        vertex_normals
+    fn calculate_tangents(&self) -> Vec<[f32; 4]> {
+        tangents::uv_tangents(self)
     }
 }
 
+/// Geometry helpers shared by room meshes and colliders.
+///
+/// Only the two accessors are required, everything else is computed from them and may be
+/// overridden where a mesh knows better.
 pub trait ExtMesh {
+    /// Positions of the vertices.
+    fn vertex_positions(&self) -> Cow<'_, [[f32; 3]]>;
+    /// Triangles indexing the vertices.
+    fn triangle_indices(&self) -> &[[u32; 3]];
+
     /// Used for aabb calc
-    fn bounding_box(&self) -> Bounds;
+    fn bounding_box(&self) -> Bounds {
+        Bounds::from_points(self.vertex_positions().iter().copied())
+    }
     /// Calculate normals for the vertices based on the triangle faces.
-    fn calculate_normals(&self) -> Vec<[f32; 3]>;
-}
-
-pub struct Bounds {
-    pub min: [f32; 3],
-    pub max: [f32; 3],
-}
-
-impl Bounds {
-    pub fn new(min: [f32; 3], max: [f32; 3]) -> Self {
-        Self { min, max }
+    fn calculate_normals(&self) -> Vec<[f32; 3]> {
+        normals::smooth_normals(&self.vertex_positions(), self.triangle_indices())
+    }
+    /// Calculate normals with crease and weighting options, splitting vertices where
+    /// neighbouring faces must not be smoothed together.
+    ///
+    /// Triangles referencing a vertex out of range are left out of the result.
+    fn calculate_normals_with(&self, options: &NormalOptions) -> SplitNormals {
+        normals::split_normals(&self.vertex_positions(), self.triangle_indices(), options)
+    }
+    /// Total area of all triangles, skipping ones with invalid indices.
+    fn surface_area(&self) -> f32 {
+        stats::surface_area(&self.vertex_positions(), self.triangle_indices())
+    }
+    /// Finds open edges, edges shared by more than two faces and inconsistently wound faces.
+    fn manifold_report(&self) -> ManifoldReport {
+        manifold::manifold_report(&self.vertex_positions(), self.triangle_indices())
+    }
+    /// Calculate tangents for the vertices, with the bitangent sign stored in `w`.
+    ///
+    /// Without texture coordinates any tangent in the surface plane will do, [`ComplexMesh`]
+    /// follows the U direction of its first UV set instead.
+    fn calculate_tangents(&self) -> Vec<[f32; 4]> {
+        tangents::perpendicular_tangents(&self.calculate_normals())
     }
 }
 
 #[binrw]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EntityData {
+    #[br(temp)]
+    #[bw(try_calc(u32::try_from(entity_type.as_ref().map_or(0, |e| e.name().len()))))]
     entity_name_size: u32,
     pub entity_type: Option<EntityType>,
 }
 
+impl EntityData {
+    /// Wraps an entity, ready to be written.
+    pub fn new(entity_type: EntityType) -> Self {
+        Self {
+            entity_type: Some(entity_type),
+        }
+    }
+}
+
+impl From<EntityType> for EntityData {
+    fn from(entity_type: EntityType) -> Self {
+        Self::new(entity_type)
+    }
+}
+
 #[binrw]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum EntityType {
-    #[br(magic = b"screen")]
+    #[brw(magic = b"screen")]
     Screen(EntityScreen),
-    #[br(magic = b"waypoint")]
+    #[brw(magic = b"waypoint")]
     WayPoint(EntityWaypoint),
-    #[br(magic = b"light")]
+    #[brw(magic = b"light")]
     Light(EntityLight),
-    #[br(magic = b"spotlight")]
+    #[brw(magic = b"spotlight")]
     SpotLight(EntitySpotlight),
-    #[br(magic = b"soundemitter")]
+    #[brw(magic = b"soundemitter")]
     SoundEmitter(EntitySoundEmitter),
-    #[br(magic = b"playerstart")]
+    #[brw(magic = b"playerstart")]
     PlayerStart(EntityPlayerStart),
-    #[br(magic = b"model")]
+    #[brw(magic = b"model")]
     Model(EntityModel),
 }
 
+impl EntityType {
+    /// Name of the entity type as stored in the file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Screen(_) => "screen",
+            Self::WayPoint(_) => "waypoint",
+            Self::Light(_) => "light",
+            Self::SpotLight(_) => "spotlight",
+            Self::SoundEmitter(_) => "soundemitter",
+            Self::PlayerStart(_) => "playerstart",
+            Self::Model(_) => "model",
+        }
+    }
+
+    /// Position of the entity in room space.
+    pub fn position(&self) -> [f32; 3] {
+        match self {
+            Self::Screen(data) => data.position,
+            Self::WayPoint(data) => data.position,
+            Self::Light(data) => data.position,
+            Self::SpotLight(data) => data.position,
+            Self::SoundEmitter(data) => data.position,
+            Self::PlayerStart(data) => data.position,
+            Self::Model(data) => data.position,
+        }
+    }
+
+    /// Mutable position of the entity in room space.
+    pub fn position_mut(&mut self) -> &mut [f32; 3] {
+        match self {
+            Self::Screen(data) => &mut data.position,
+            Self::WayPoint(data) => &mut data.position,
+            Self::Light(data) => &mut data.position,
+            Self::SpotLight(data) => &mut data.position,
+            Self::SoundEmitter(data) => &mut data.position,
+            Self::PlayerStart(data) => &mut data.position,
+            Self::Model(data) => &mut data.position,
+        }
+    }
+}
+
 /// Reads a .rmesh file.
+///
+/// Gzip and zstd compressed files are unwrapped when the matching feature is enabled.
 pub fn read_rmesh(bytes: &[u8]) -> Result<Header, RMeshError> {
-    let mut cursor = Cursor::new(bytes);
+    let bytes = compression::decompress(bytes)?;
+    let mut cursor = Cursor::new(&bytes[..]);
     let header: Header = cursor.read_le()?;
     Ok(header)
 }
 
+/// Reads a .rmesh file like [`read_rmesh`], parsing unknown header tags as a plain `RoomMesh`.
+///
+/// Such rooms report [`FormatVariant::Unknown`] with their tag, and are written back as
+/// `RoomMesh`. Tags from future editors may announce sections this crate doesn't know, so the
+/// parse can still fail or misread the data after the colliders.
+pub fn read_rmesh_lenient(bytes: &[u8]) -> Result<Header, RMeshError> {
+    let bytes = compression::decompress(bytes)?;
+    let mut cursor = Cursor::new(&bytes[..]);
+    let header: Header = cursor.read_le_args((true,))?;
+    Ok(header)
+}
+
 /// Writes a .rmesh file.
 pub fn write_rmesh(header: &Header) -> Result<Vec<u8>, RMeshError> {
     let mut bytes = Vec::new();
@@ -347,3 +458,168 @@ pub fn write_rmesh(header: &Header) -> Result<Vec<u8>, RMeshError> {
 
     Ok(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad(texture: &str) -> ComplexMesh {
+        let corners = [
+            [0.0, 0.0, 0.0],
+            [64.0, 0.0, 0.0],
+            [64.0, 0.0, 64.0],
+            [0.0, 0.0, 64.0],
+        ];
+        ComplexMesh {
+            textures: [
+                Texture {
+                    blend_type: TextureBlendType::Visible,
+                    path: Some(texture.into()),
+                },
+                Texture::default(),
+            ],
+            vertices: corners
+                .into_iter()
+                .map(|position| Vertex {
+                    position,
+                    tex_coords: [[position[0] / 64.0, position[2] / 64.0], [0.0; 2]],
+                    color: [255, 255, 255],
+                })
+                .collect(),
+            triangles: vec![[0, 1, 2], [0, 2, 3]],
+            extension: None,
+        }
+    }
+
+    fn tag(header: &Header) -> String {
+        String::from(&header.kind)
+    }
+
+    #[test]
+    fn round_trip_keeps_every_section() {
+        let header = Header {
+            meshes: vec![quad("floor.jpg")],
+            colliders: vec![SimpleMesh {
+                vertex_count: 3,
+                vertices: vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                triangle_count: 1,
+                triangles: vec![[0, 1, 2]],
+            }],
+            trigger_boxes: vec![TriggerBox {
+                meshes: vec![SimpleMesh {
+                    vertex_count: 3,
+                    vertices: vec![[0.0, 0.0, 8.0], [8.0, 0.0, 8.0], [0.0, 8.0, 8.0]],
+                    triangle_count: 1,
+                    triangles: vec![[2, 1, 0]],
+                }],
+                name: "trigger".into(),
+            }],
+            entities: vec![
+                EntityType::Light(EntityLight {
+                    position: [32.0, 100.0, 32.0],
+                    range: 600.0,
+                    color: [255, 200, 150].into(),
+                    intensity: 0.8,
+                })
+                .into(),
+                EntityType::WayPoint(EntityWaypoint {
+                    position: [0.0, 10.0, 0.0],
+                })
+                .into(),
+            ],
+            extra: vec![0xde, 0xad],
+            ..Default::default()
+        };
+
+        let bytes = write_rmesh(&header).unwrap();
+        let read = read_rmesh(&bytes).unwrap();
+
+        assert_eq!(tag(&read), "RoomMesh.HasTriggerBox");
+        assert_eq!(read.meshes.len(), 1);
+        assert_eq!(read.meshes[0].triangles, [[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(read.meshes[0].vertices[2].position, [64.0, 0.0, 64.0]);
+        assert_eq!(read.colliders[0].vertices, header.colliders[0].vertices);
+        assert_eq!(String::from(&read.trigger_boxes[0].name), "trigger");
+        assert_eq!(read.trigger_boxes[0].meshes[0].triangles, [[2, 1, 0]]);
+        let names: Vec<_> = read
+            .entities
+            .iter()
+            .filter_map(|e| e.entity_type.as_ref())
+            .map(EntityType::name)
+            .collect();
+        assert_eq!(names, ["light", "waypoint"]);
+        assert_eq!(read.extra, [0xde, 0xad]);
+        assert_eq!(write_rmesh(&read).unwrap(), bytes);
+    }
+
+    #[test]
+    fn stale_trigger_box_tag_is_rewritten() {
+        let header = Header {
+            kind: "RoomMesh.HasTriggerBox".into(),
+            meshes: vec![quad("wall.png")],
+            ..Default::default()
+        };
+
+        let read = read_rmesh(&write_rmesh(&header).unwrap()).unwrap();
+
+        assert_eq!(tag(&read), "RoomMesh");
+        assert_eq!(read.variant, FormatVariant::RoomMesh);
+        assert!(read.trigger_boxes.is_empty());
+    }
+
+    #[test]
+    fn cbre_ex_mesh_data_round_trips() {
+        let mut first = quad("a.jpg");
+        first.extension = Some(MeshExtension {
+            data: vec![1, 2, 3],
+        });
+        let header = Header {
+            meshes: vec![first, quad("b.jpg")],
+            ..Default::default()
+        };
+
+        let bytes = write_rmesh(&header).unwrap();
+        let read = read_rmesh(&bytes).unwrap();
+
+        assert_eq!(tag(&read), "RoomMesh.HasMeshData");
+        assert_eq!(read.variant, FormatVariant::CbreEx);
+        assert_eq!(read.meshes[0].extension.as_ref().unwrap().data, [1, 2, 3]);
+        assert_eq!(read.meshes[1].extension, Some(MeshExtension::default()));
+        assert_eq!(read.meshes[1].triangles.len(), 2);
+        assert_eq!(write_rmesh(&read).unwrap(), bytes);
+    }
+
+    #[test]
+    fn unknown_tag_is_rejected() {
+        let mut header = write_rmesh(&Header::default()).unwrap();
+        // Same length as "RoomMesh", so only the tag changes
+        header[4..12].copy_from_slice(b"RoomMush");
+
+        assert!(matches!(
+            read_rmesh(&header),
+            Err(RMeshError::BinRwError(binrw::Error::AssertFail { .. }))
+        ));
+        assert_eq!(
+            detect_variant(&header),
+            FormatVariant::Unknown("RoomMush".into())
+        );
+    }
+
+    #[test]
+    fn unknown_tag_reads_leniently_as_the_base_layout() {
+        let room = Header {
+            colliders: vec![SimpleMesh::new(
+                vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                vec![[0, 1, 2]],
+            )],
+            ..Default::default()
+        };
+        let mut bytes = write_rmesh(&room).unwrap();
+        bytes[4..12].copy_from_slice(b"RoomMush");
+
+        let read = read_rmesh_lenient(&bytes).unwrap();
+        assert_eq!(read.variant, FormatVariant::Unknown("RoomMush".into()));
+        assert_eq!(read.colliders[0].triangles, [[0, 1, 2]]);
+        assert_eq!(write_rmesh(&read).unwrap(), write_rmesh(&room).unwrap());
+    }
+}
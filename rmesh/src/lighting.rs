@@ -0,0 +1,48 @@
+//! Conversions from Blitz3D light values to physically based units.
+//!
+//! Blitz3D lights have a range in room units, a unitless intensity and an sRGB color. Renderers
+//! want a radius in world units, lumens or candela and a linear color, converting through these
+//! keeps every consumer at the same brightness.
+
+use std::f32::consts::PI;
+
+use crate::color::srgb_to_linear;
+use crate::ThreeTypeString;
+
+/// Default luminous power in lumens of a light with an intensity of 1, about a 300 W
+/// incandescent bulb.
+pub const LUMENS_PER_INTENSITY: f32 = 4800.0;
+
+/// Radius of a light in world units, lights have no effect past it.
+pub fn radius(range: f32, scale: f32) -> f32 {
+    range * scale
+}
+
+/// Luminous power of a light, negative intensities are treated as off.
+///
+/// Blitz3D intensities have no unit, `lumens_per_intensity` is usually
+/// [`LUMENS_PER_INTENSITY`] and tuned per game.
+pub fn lumens(intensity: f32, lumens_per_intensity: f32) -> f32 {
+    intensity.max(0.0) * lumens_per_intensity
+}
+
+/// Luminous intensity of a point light spreading `lumens` in every direction.
+pub fn point_candela(lumens: f32) -> f32 {
+    lumens / (4.0 * PI)
+}
+
+/// Luminous intensity of a spot light focusing `lumens` into a cone, `outer_angle` being the
+/// half angle in radians from [`EntitySpotlight::cone_angles`](crate::EntitySpotlight::cone_angles).
+pub fn spot_candela(lumens: f32, outer_angle: f32) -> f32 {
+    let solid_angle = 2.0 * PI * (1.0 - outer_angle.cos());
+    if solid_angle > 0.0 {
+        lumens / solid_angle
+    } else {
+        0.0
+    }
+}
+
+/// Linear RGB of a light color, missing components are full.
+pub fn linear_color(color: &ThreeTypeString) -> [f32; 3] {
+    [0, 1, 2].map(|i| srgb_to_linear(color.0.get(i).copied().unwrap_or(255) as f32 / 255.0))
+}
@@ -0,0 +1,179 @@
+//! The `<room>_lm<i>.png` names vanilla exports give lightmaps, written next to the rmesh.
+//!
+//! Indices count from 1, one per distinct lightmap in order of first use by the meshes.
+
+use std::path::{Path, PathBuf};
+
+use crate::resolve::{find_path, normalize, same_path};
+use crate::{Header, Texture, TextureBlendType};
+
+/// Name of the lightmap at `index` of the room at `room_path`.
+pub fn lightmap_name(room_path: &Path, index: usize) -> String {
+    let room = room_path.file_stem().unwrap_or_default().to_string_lossy();
+    format!("{room}_lm{index}.png")
+}
+
+/// Path of the lightmap at `index`, next to the room at `room_path`.
+pub fn lightmap_path(room_path: &Path, index: usize) -> PathBuf {
+    room_dir(room_path).join(lightmap_name(room_path, index))
+}
+
+/// Index of a lightmap path following the convention for the room at `room_path`, ignoring
+/// case and directories.
+pub fn lightmap_index(room_path: &Path, path: &str) -> Option<usize> {
+    let path = normalize(path);
+    let stem = path.file_stem()?.to_str()?.to_ascii_lowercase();
+    let room = room_path.file_stem()?.to_str()?.to_ascii_lowercase();
+    let extension = path.extension()?.to_str()?;
+    if !extension.eq_ignore_ascii_case("png") {
+        return None;
+    }
+    let index = stem.strip_prefix(&room)?.strip_prefix("_lm")?;
+    if index.is_empty() || !index.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+/// Paths the lightmap stored as `path` may be at: relative to the room, by its file name next
+/// to the room, for paths left absolute by the exporter, then by its conventional name.
+pub fn lightmap_candidates(room_path: &Path, path: &str, index: usize) -> Vec<PathBuf> {
+    let dir = room_dir(room_path);
+    let path = normalize(path);
+    let mut candidates = vec![dir.join(&path)];
+    let alternates = [
+        path.file_name().map(|name| dir.join(name)),
+        Some(lightmap_path(room_path, index)),
+    ];
+    for alternate in alternates.into_iter().flatten() {
+        if !candidates.contains(&alternate) {
+            candidates.push(alternate);
+        }
+    }
+    candidates
+}
+
+fn room_dir(room_path: &Path) -> &Path {
+    room_path.parent().unwrap_or(Path::new(""))
+}
+
+/// A lightmap path [`Header::lightmap_issues`] found wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LightmapIssue {
+    /// The path doesn't follow the convention, `expected` would.
+    Misnamed { path: String, expected: String },
+    /// None of the [`lightmap_candidates`] exist.
+    Missing { path: String },
+}
+
+/// Part of a lightmap atlas an old lightmap was copied into, in UV space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightmapRegion {
+    /// Lightmap path as stored in the room before re-pointing.
+    pub path: String,
+    /// Top left corner.
+    pub offset: [f32; 2],
+    /// Size relative to the atlas.
+    pub scale: [f32; 2],
+}
+
+fn lightmap_slot(textures: &[Texture; 2]) -> Option<String> {
+    let texture = &textures[0];
+    match texture.blend_type {
+        TextureBlendType::Lightmap => texture.path.as_ref().map(String::from),
+        _ => None,
+    }
+}
+
+impl Header {
+    /// Distinct lightmap paths in order of first use, the first spelling of each.
+    ///
+    /// The lightmap at position `i` is conventionally named [`lightmap_name`]`(room, i + 1)`.
+    pub fn lightmap_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = vec![];
+        for mesh in &self.meshes {
+            let Some(path) = lightmap_slot(&mesh.textures) else {
+                continue;
+            };
+            if !paths.iter().any(|other| same_path(other, &path)) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// Index of the lightmap of each mesh, see [`Header::lightmap_paths`].
+    pub fn lightmap_indices(&self) -> Vec<Option<usize>> {
+        let mut paths: Vec<String> = vec![];
+        self.meshes
+            .iter()
+            .map(|mesh| {
+                let path = lightmap_slot(&mesh.textures)?;
+                let i = match paths.iter().position(|other| same_path(other, &path)) {
+                    Some(i) => i,
+                    None => {
+                        paths.push(path);
+                        paths.len() - 1
+                    }
+                };
+                Some(i + 1)
+            })
+            .collect()
+    }
+
+    /// Checks every lightmap is named after the room at `room_path` and exists next to it.
+    pub fn lightmap_issues(&self, room_path: &Path) -> Vec<LightmapIssue> {
+        let mut issues = vec![];
+        for (i, path) in self.lightmap_paths().into_iter().enumerate() {
+            let index = i + 1;
+            if lightmap_index(room_path, &path) != Some(index)
+                || normalize(&path).parent() != Some(Path::new(""))
+            {
+                issues.push(LightmapIssue::Misnamed {
+                    path: path.clone(),
+                    expected: lightmap_name(room_path, index),
+                });
+            }
+            let found = lightmap_candidates(room_path, &path, index)
+                .iter()
+                .any(|candidate| find_path(candidate).is_some_and(|path| path.is_file()));
+            if !found {
+                issues.push(LightmapIssue::Missing { path });
+            }
+        }
+        issues
+    }
+
+    /// Renames every lightmap slot after the room at `room_path`, returning the new names in
+    /// index order. Slots sharing a lightmap keep sharing it.
+    pub fn name_lightmaps(&mut self, room_path: &Path) -> Vec<String> {
+        let indices = self.lightmap_indices();
+        for (mesh, index) in self.meshes.iter_mut().zip(&indices) {
+            if let Some(index) = index {
+                mesh.textures[0].path = Some(lightmap_name(room_path, *index).as_str().into());
+            }
+        }
+        let count = indices.iter().flatten().max().copied().unwrap_or(0);
+        (1..=count)
+            .map(|index| lightmap_name(room_path, index))
+            .collect()
+    }
+
+    /// Points the lightmap slots of the meshes using a lightmap in `regions` at `atlas_path`,
+    /// remapping their UV1 into its region. Other lightmaps are left as they are.
+    pub fn retarget_lightmaps(&mut self, atlas_path: &str, regions: &[LightmapRegion]) {
+        for mesh in &mut self.meshes {
+            let Some(path) = lightmap_slot(&mesh.textures) else {
+                continue;
+            };
+            let Some(region) = regions.iter().find(|region| same_path(&region.path, &path)) else {
+                continue;
+            };
+            for vertex in &mut mesh.vertices {
+                let uv = &mut vertex.tex_coords[1];
+                *uv = [0, 1].map(|i| region.offset[i] + uv[i] * region.scale[i]);
+            }
+            mesh.textures[0].path = Some(atlas_path.into());
+        }
+    }
+}
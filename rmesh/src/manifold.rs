@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+/// Topology problems found by [`ExtMesh::manifold_report`](crate::ExtMesh::manifold_report).
+///
+/// Vertices sharing a position are treated as one, so texture seams don't count as open
+/// edges. Edges are reported using the first vertex index at each position.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifoldReport {
+    /// Edges used by a single triangle.
+    pub open_edges: Vec<[u32; 2]>,
+    /// Edges shared by more than two triangles.
+    pub non_manifold_edges: Vec<[u32; 2]>,
+    /// Triangles wound against the rest of their connected surface.
+    pub flipped_faces: Vec<usize>,
+}
+
+impl ManifoldReport {
+    /// Whether every edge is shared by exactly two triangles.
+    pub fn is_watertight(&self) -> bool {
+        self.open_edges.is_empty() && self.non_manifold_edges.is_empty()
+    }
+
+    /// Whether the mesh is watertight and consistently wound.
+    pub fn is_valid(&self) -> bool {
+        self.is_watertight() && self.flipped_faces.is_empty()
+    }
+}
+
+pub(crate) fn manifold_report(positions: &[[f32; 3]], triangles: &[[u32; 3]]) -> ManifoldReport {
+    // Collapse vertices with identical positions
+    let mut first_at: HashMap<[u32; 3], u32> = HashMap::new();
+    let canonical: Vec<u32> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| *first_at.entry(p.map(f32::to_bits)).or_insert(i as u32))
+        .collect();
+
+    // Undirected edge -> faces using it, with whether they walk it from low to high
+    let mut edges: HashMap<[u32; 2], Vec<(usize, bool)>> = HashMap::new();
+    let mut order = vec![];
+    for (face, triangle) in triangles.iter().enumerate() {
+        let Some(triangle) = triangle
+            .iter()
+            .map(|i| canonical.get(*i as usize).copied())
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        for corner in 0..3 {
+            let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+            if a == b {
+                continue;
+            }
+            let key = [a.min(b), a.max(b)];
+            let faces = edges.entry(key).or_default();
+            if faces.is_empty() {
+                order.push(key);
+            }
+            faces.push((face, a < b));
+        }
+    }
+
+    let mut report = ManifoldReport::default();
+    let mut neighbours = vec![vec![]; triangles.len()];
+    for key in order {
+        match edges[&key].as_slice() {
+            [_] => report.open_edges.push(key),
+            [(f0, d0), (f1, d1)] => {
+                // Consistent winding walks a shared edge in opposite directions
+                let flipped = d0 == d1;
+                neighbours[*f0].push((*f1, flipped));
+                neighbours[*f1].push((*f0, flipped));
+            }
+            _ => report.non_manifold_edges.push(key),
+        }
+    }
+
+    // Propagate orientation through each connected surface and flag the minority
+    let mut flip: Vec<Option<bool>> = vec![None; triangles.len()];
+    for seed in 0..triangles.len() {
+        if flip[seed].is_some() {
+            continue;
+        }
+        flip[seed] = Some(false);
+        let mut component = vec![seed];
+        let mut stack = vec![seed];
+        while let Some(face) = stack.pop() {
+            let state = flip[face].unwrap_or_default();
+            for &(other, flipped) in &neighbours[face] {
+                if flip[other].is_none() {
+                    flip[other] = Some(state ^ flipped);
+                    component.push(other);
+                    stack.push(other);
+                }
+            }
+        }
+
+        let flipped_count = component.iter().filter(|f| flip[**f] == Some(true)).count();
+        let minority = flipped_count * 2 <= component.len();
+        report
+            .flipped_faces
+            .extend(component.into_iter().filter(|f| flip[*f] == Some(minority)));
+    }
+    report.flipped_faces.sort_unstable();
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComplexMesh, ExtMesh, SimpleMesh, Vertex};
+
+    // An octahedron, closed and wound outwards
+    const POSITIONS: [[f32; 3]; 6] = [
+        [1.0, 0.0, 0.0],
+        [-1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, -1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, -1.0],
+    ];
+    const TRIANGLES: [[u32; 3]; 8] = [
+        [0, 2, 4],
+        [2, 1, 4],
+        [1, 3, 4],
+        [3, 0, 4],
+        [2, 0, 5],
+        [1, 2, 5],
+        [3, 1, 5],
+        [0, 3, 5],
+    ];
+
+    #[test]
+    fn closed_mesh_is_valid() {
+        let collider = SimpleMesh::new(POSITIONS.to_vec(), TRIANGLES.to_vec());
+        let mesh = ComplexMesh {
+            vertices: POSITIONS
+                .map(|position| Vertex {
+                    position,
+                    ..Default::default()
+                })
+                .to_vec(),
+            triangles: TRIANGLES.to_vec(),
+            ..Default::default()
+        };
+
+        assert!(collider.manifold_report().is_valid());
+        assert_eq!(mesh.manifold_report(), collider.manifold_report());
+    }
+
+    #[test]
+    fn missing_face_leaves_open_edges() {
+        let report = manifold_report(&POSITIONS, &TRIANGLES[1..]);
+
+        assert_eq!(report.open_edges.len(), 3);
+        assert!(report.non_manifold_edges.is_empty());
+        assert!(!report.is_watertight());
+    }
+
+    #[test]
+    fn flipped_face_and_seams() {
+        let mut triangles = TRIANGLES.to_vec();
+        triangles[6].swap(0, 1);
+        let report = manifold_report(&POSITIONS, &triangles);
+        assert!(report.is_watertight());
+        assert_eq!(report.flipped_faces.len(), 1);
+
+        // A duplicated vertex at the same position, like a texture seam, is no hole
+        let mut positions = POSITIONS.to_vec();
+        positions.push(POSITIONS[4]);
+        let mut triangles = TRIANGLES.to_vec();
+        triangles[0][2] = 6;
+        assert!(manifold_report(&positions, &triangles).is_valid());
+    }
+}
@@ -0,0 +1,461 @@
+use std::fmt::{self, Write};
+
+use crate::convert::CoordinateTransform;
+use crate::math;
+use crate::{ComplexMesh, Header, ObjExport, Texture};
+
+/// Controls how [`Header::to_map_with`] turns geometry into brushes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapOptions {
+    /// Depth each surface is extruded backwards by to form a brush.
+    pub thickness: f32,
+    /// Planar pieces smaller than this go into the reference mesh instead of becoming brushes.
+    pub min_area: f32,
+    /// Assumed texture size in pixels, Valve 220 texture axes work in pixels.
+    pub texture_size: f32,
+    /// Texture for the back and side faces of brushes.
+    pub nodraw_texture: String,
+    /// Texture for brushes built from colliders and invisible meshes.
+    pub clip_texture: String,
+    /// File name of the reference mesh model, relative to the map.
+    pub reference_name: String,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self {
+            thickness: 8.0,
+            min_area: 1.0,
+            texture_size: 256.0,
+            nodraw_texture: "NULL".into(),
+            clip_texture: "CLIP".into(),
+            reference_name: "reference.obj".into(),
+        }
+    }
+}
+
+/// A Valve 220 `.map` file with the geometry that couldn't be turned into brushes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapExport {
+    pub map: String,
+    pub brush_count: usize,
+    /// Leftover geometry in map coordinates, placed in the map by a `misc_model` entity.
+    pub reference: Option<ObjExport>,
+}
+
+/// Triangle positions in map space with their diffuse texture coordinates.
+type MapTriangle = ([[f32; 3]; 3], [[f32; 2]; 3]);
+
+/// Pixel axes and offsets of a face, `pixels = dot(point, axis) + offset`.
+type TextureAxes = [([f32; 3], f32); 2];
+
+/// Convex planar polygon in map space, counter-clockwise around `normal`.
+#[derive(Debug, Clone)]
+struct Polygon {
+    points: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    normal: [f32; 3],
+    texture: usize,
+}
+
+/// Blitz3D is left-handed with Y up, maps are right-handed with Z up.
+fn to_map_space([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [x, z, y]
+}
+
+impl Header {
+    /// Exports the room as a Valve 220 `.map` with default [`MapOptions`].
+    pub fn to_map(&self) -> MapExport {
+        self.to_map_with(&MapOptions::default())
+    }
+
+    /// Exports the room as a Valve 220 `.map` for TrenchBroom or CBRE.
+    ///
+    /// Coplanar neighbouring triangles sharing a texture mapping are merged into convex
+    /// polygons, each extruded into a brush. Colliders and invisible meshes become clip
+    /// brushes. Pieces smaller than [`MapOptions::min_area`] are collected into a
+    /// reference mesh instead.
+    pub fn to_map_with(&self, options: &MapOptions) -> MapExport {
+        let mut textures = vec![];
+        let mut polygons = vec![];
+        let mut leftovers = vec![];
+
+        for mesh in &self.meshes {
+            textures.push(if mesh.is_invisible() {
+                options.clip_texture.clone()
+            } else {
+                texture_name(&mesh.textures)
+            });
+            let texture = textures.len() - 1;
+
+            // Remember which mesh triangle each resolved one came from
+            let (sources, triangles): (Vec<_>, Vec<_>) = mesh
+                .triangles_resolved()
+                .enumerate()
+                .filter_map(|(index, vertices)| Some((index, vertices.ok()?)))
+                .map(|(index, [v0, v1, v2])| {
+                    // Mirroring into map space flips the winding back to front facing
+                    let vertices = [v2, v1, v0];
+                    (
+                        index,
+                        (
+                            vertices.map(|v| to_map_space(v.position)),
+                            vertices.map(|v| v.tex_coords[0]),
+                        ),
+                    )
+                })
+                .unzip();
+            let (merged, small) = merge_coplanar(&triangles, texture, options.min_area);
+            polygons.extend(merged);
+
+            if !small.is_empty() {
+                let small: Vec<_> = small.into_iter().map(|i| sources[i]).collect();
+                leftovers.push(small_mesh(mesh, &small));
+            }
+        }
+
+        textures.push(options.clip_texture.clone());
+        let clip = textures.len() - 1;
+        for collider in &self.colliders {
+            let triangles: Vec<_> = collider
+                .triangles_resolved()
+                .flatten()
+                .map(|[v0, v1, v2]| ([v2, v1, v0].map(to_map_space), [[0.0; 2]; 3]))
+                .collect();
+            // Collision keeps every piece, however small
+            polygons.extend(merge_coplanar(&triangles, clip, 0.0).0);
+        }
+
+        let mut export = MapExport {
+            brush_count: polygons.len(),
+            ..Default::default()
+        };
+
+        if !leftovers.is_empty() {
+            let mut reference = Header {
+                meshes: leftovers,
+                ..Default::default()
+            };
+            // The reference mesh is written in map space so it lines up with the brushes
+            reference.transform(&[
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]);
+            let mtl_name = format!(
+                "{}.mtl",
+                options
+                    .reference_name
+                    .rsplit_once('.')
+                    .map_or(options.reference_name.as_str(), |(stem, _)| stem)
+            );
+            export.reference = Some(reference.to_obj_with(
+                &mtl_name,
+                &CoordinateTransform {
+                    scale: 1.0,
+                    flip_z: false,
+                },
+            ));
+        }
+
+        let has_reference = export.reference.is_some();
+        write_map(
+            &mut export.map,
+            &polygons,
+            &textures,
+            options,
+            has_reference,
+        )
+        .expect("writing to a String can't fail");
+        export
+    }
+}
+
+fn texture_name(textures: &[Texture; 2]) -> String {
+    let [lightmap, diffuse] = textures;
+    diffuse
+        .path
+        .as_ref()
+        .or(lightmap.path.as_ref())
+        .map(String::from)
+        .map(|path| {
+            let name = path.rsplit(['/', '\\']).next().unwrap_or_default();
+            let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+            stem.replace(char::is_whitespace, "_")
+        })
+        .unwrap_or_else(|| "NULL".into())
+}
+
+/// Copies the given triangles of `mesh` into a new mesh with only the vertices they use.
+fn small_mesh(mesh: &ComplexMesh, triangles: &[usize]) -> ComplexMesh {
+    let mut small = ComplexMesh {
+        textures: mesh.textures.clone(),
+        ..Default::default()
+    };
+    let mut remap = vec![u32::MAX; mesh.vertices.len()];
+    for &triangle in triangles {
+        let triangle = mesh.triangles[triangle].map(|i| {
+            let i = i as usize;
+            if remap[i] == u32::MAX {
+                remap[i] = small.vertices.len() as u32;
+                small.vertices.push(mesh.vertices[i].clone());
+            }
+            remap[i]
+        });
+        small.triangles.push(triangle);
+    }
+    small
+}
+
+/// Affine texture mapping of a triangle, `uv = dot(point, gradient) + offset` per channel.
+fn uv_mapping(points: &[[f32; 3]; 3], uvs: &[[f32; 2]; 3]) -> Option<TextureAxes> {
+    let e1 = math::sub(points[1], points[0]);
+    let e2 = math::sub(points[2], points[0]);
+    let n = math::cross(e1, e2);
+    let n2 = math::dot(n, n);
+    if n2 <= f32::EPSILON {
+        return None;
+    }
+
+    Some([0, 1].map(|c| {
+        let d1 = uvs[1][c] - uvs[0][c];
+        let d2 = uvs[2][c] - uvs[0][c];
+        let gradient = math::scale(
+            math::add(
+                math::scale(math::cross(e2, n), d1),
+                math::scale(math::cross(n, e1), d2),
+            ),
+            1.0 / n2,
+        );
+        (gradient, uvs[0][c] - math::dot(gradient, points[0]))
+    }))
+}
+
+fn fits_mapping(mapping: &TextureAxes, point: [f32; 3], uv: [f32; 2]) -> bool {
+    (0..2).all(|c| (math::dot(mapping[c].0, point) + mapping[c].1 - uv[c]).abs() < 1e-3)
+}
+
+/// Greedily merges coplanar triangles into convex polygons.
+///
+/// Returns the polygons and the indices of the triangles too small to become brushes.
+fn merge_coplanar(
+    triangles: &[MapTriangle],
+    texture: usize,
+    min_area: f32,
+) -> (Vec<Polygon>, Vec<usize>) {
+    let mut polygons: Vec<Polygon> = vec![];
+    let mut small = vec![];
+
+    for (index, (points, uvs)) in triangles.iter().enumerate() {
+        let normal = math::cross(
+            math::sub(points[1], points[0]),
+            math::sub(points[2], points[0]),
+        );
+        if math::length(normal) * 0.5 < min_area.max(f32::EPSILON) {
+            small.push(index);
+            continue;
+        }
+        let mut polygon = Polygon {
+            points: points.to_vec(),
+            uvs: uvs.to_vec(),
+            normal: math::normalize(normal),
+            texture,
+        };
+
+        // Keep merging into existing polygons until nothing fits
+        while let Some(merged) = polygons
+            .iter()
+            .enumerate()
+            .find_map(|(i, other)| merge_polygons(other, &polygon).map(|merged| (i, merged)))
+        {
+            polygons.swap_remove(merged.0);
+            polygon = merged.1;
+        }
+        polygons.push(polygon);
+    }
+
+    (polygons, small)
+}
+
+fn merge_polygons(a: &Polygon, b: &Polygon) -> Option<Polygon> {
+    if a.texture != b.texture || math::dot(a.normal, b.normal) < 0.9999 {
+        return None;
+    }
+    let distance = math::dot(a.normal, a.points[0]);
+    if (math::dot(a.normal, b.points[0]) - distance).abs() > 1e-3 {
+        return None;
+    }
+
+    let mapping = uv_mapping(
+        &[a.points[0], a.points[1], a.points[2]],
+        &[a.uvs[0], a.uvs[1], a.uvs[2]],
+    );
+    if let Some(mapping) = &mapping {
+        if !b
+            .points
+            .iter()
+            .zip(&b.uvs)
+            .all(|(p, uv)| fits_mapping(mapping, *p, *uv))
+        {
+            return None;
+        }
+    }
+
+    // Find an edge of `a` that `b` walks in the opposite direction
+    let (na, nb) = (a.points.len(), b.points.len());
+    let (i, j) = (0..na).find_map(|i| {
+        (0..nb).find_map(|j| {
+            (a.points[i] == b.points[(j + 1) % nb] && a.points[(i + 1) % na] == b.points[j])
+                .then_some((i, j))
+        })
+    })?;
+
+    let mut points = vec![];
+    let mut uvs = vec![];
+    for k in 0..na {
+        let k = (i + 1 + k) % na;
+        points.push(a.points[k]);
+        uvs.push(a.uvs[k]);
+    }
+    for k in 0..nb - 2 {
+        let k = (j + 2 + k) % nb;
+        points.push(b.points[k]);
+        uvs.push(b.uvs[k]);
+    }
+
+    // Drop collinear points, then require every corner to turn the same way
+    let mut k = 0;
+    while k < points.len() && points.len() > 3 {
+        let n = points.len();
+        let prev = points[(k + n - 1) % n];
+        let next = points[(k + 1) % n];
+        let turn = math::cross(math::sub(points[k], prev), math::sub(next, points[k]));
+        if math::length(turn) < 1e-4 {
+            points.remove(k);
+            uvs.remove(k);
+        } else {
+            k += 1;
+        }
+    }
+    let n = points.len();
+    let convex = (0..n).all(|k| {
+        let prev = points[(k + n - 1) % n];
+        let next = points[(k + 1) % n];
+        let turn = math::cross(math::sub(points[k], prev), math::sub(next, points[k]));
+        math::dot(turn, a.normal) > 1e-4
+    });
+
+    convex.then_some(Polygon {
+        points,
+        uvs,
+        normal: a.normal,
+        texture: a.texture,
+    })
+}
+
+fn write_map(
+    map: &mut String,
+    polygons: &[Polygon],
+    textures: &[String],
+    options: &MapOptions,
+    has_reference: bool,
+) -> fmt::Result {
+    writeln!(map, "// Game: Generic")?;
+    writeln!(map, "// Format: Valve")?;
+    writeln!(map, "{{")?;
+    writeln!(map, "\"classname\" \"worldspawn\"")?;
+    writeln!(map, "\"mapversion\" \"220\"")?;
+
+    for (index, polygon) in polygons.iter().enumerate() {
+        writeln!(map, "// brush {index}")?;
+        writeln!(map, "{{")?;
+
+        let offset = math::scale(polygon.normal, -options.thickness);
+        let back: Vec<_> = polygon
+            .points
+            .iter()
+            .map(|p| math::add(*p, offset))
+            .collect();
+        let [p0, p1, p2] = [0, 1, 2].map(|i| polygon.points[i]);
+
+        let axes = uv_mapping(
+            &[p0, p1, p2],
+            &[polygon.uvs[0], polygon.uvs[1], polygon.uvs[2]],
+        )
+        .map(|mapping| {
+            mapping.map(|(gradient, offset)| {
+                (
+                    math::scale(gradient, options.texture_size),
+                    offset * options.texture_size,
+                )
+            })
+        });
+        let front = &textures[polygon.texture];
+        write_face(map, [p0, p1, p2], polygon.normal, front, axes)?;
+
+        let nodraw = &options.nodraw_texture;
+        let inward = math::scale(polygon.normal, -1.0);
+        write_face(map, [back[0], back[1], back[2]], inward, nodraw, None)?;
+
+        let n = polygon.points.len();
+        for (k, back) in back.iter().enumerate() {
+            let (a, b) = (polygon.points[k], polygon.points[(k + 1) % n]);
+            let outward = math::normalize(math::cross(math::sub(b, a), polygon.normal));
+            write_face(map, [a, b, *back], outward, nodraw, None)?;
+        }
+
+        writeln!(map, "}}")?;
+    }
+    writeln!(map, "}}")?;
+
+    if has_reference {
+        writeln!(map, "{{")?;
+        writeln!(map, "\"classname\" \"misc_model\"")?;
+        writeln!(map, "\"model\" \"{}\"", options.reference_name)?;
+        writeln!(map, "\"origin\" \"0 0 0\"")?;
+        writeln!(map, "}}")?;
+    }
+
+    Ok(())
+}
+
+/// Writes one brush plane, ordering the points so the plane faces `outward`.
+fn write_face(
+    map: &mut String,
+    points: [[f32; 3]; 3],
+    outward: [f32; 3],
+    texture: &str,
+    axes: Option<TextureAxes>,
+) -> fmt::Result {
+    let [mut p0, p1, mut p2] = points;
+    // Quake derives the plane normal from (p0 - p1) x (p2 - p1)
+    if math::dot(math::cross(math::sub(p0, p1), math::sub(p2, p1)), outward) < 0.0 {
+        std::mem::swap(&mut p0, &mut p2);
+    }
+    // Adding zero turns -0 into 0
+    for [x, y, z] in [p0, p1, p2].map(|p| p.map(|c| c + 0.0)) {
+        write!(map, "( {x} {y} {z} ) ")?;
+    }
+
+    // Faces without a usable mapping get axes lying in the plane
+    let axes = axes
+        .filter(|axes| {
+            axes.iter()
+                .all(|(axis, _)| math::length(*axis) > f32::EPSILON)
+        })
+        .unwrap_or_else(|| {
+            let u = math::perpendicular(outward);
+            let v = math::cross(outward, u);
+            [(u, 0.0), (v, 0.0)]
+        });
+
+    // Valve 220 stores unit axes with a separate scale
+    write!(map, "{texture}")?;
+    for (axis, offset) in &axes {
+        let [x, y, z] = math::normalize(*axis).map(|c| c + 0.0);
+        write!(map, " [ {x} {y} {z} {} ]", offset + 0.0)?;
+    }
+    let [u_scale, v_scale] = axes.map(|(axis, _)| 1.0 / math::length(axis));
+    writeln!(map, " 0 {u_scale} {v_scale}")
+}
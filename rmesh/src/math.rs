@@ -0,0 +1,263 @@
+//! Small vector helpers shared by the geometry code.
+
+pub(crate) fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// Normalizes `a`, leaving zero vectors untouched.
+pub(crate) fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = length(a);
+    if length != 0.0 {
+        scale(a, 1.0 / length)
+    } else {
+        a
+    }
+}
+
+/// Any unit vector perpendicular to `n`.
+pub(crate) fn perpendicular(n: [f32; 3]) -> [f32; 3] {
+    let axis = if n[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    normalize(sub(axis, scale(n, dot(n, axis))))
+}
+
+/// Column-major affine matrix, `m[column][row]`.
+pub(crate) type Mat4 = [[f32; 4]; 4];
+
+pub(crate) const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Row-major rotation matrix, `m[row][column]`.
+pub(crate) type Mat3 = [[f32; 3]; 3];
+
+pub(crate) fn transform_point(m: &Mat4, p: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = p;
+    [0, 1, 2].map(|r| m[0][r] * x + m[1][r] * y + m[2][r] * z + m[3][r])
+}
+
+/// Upper 3x3 block of an affine matrix, converted to row-major.
+pub(crate) fn linear_part(m: &Mat4) -> Mat3 {
+    [0, 1, 2].map(|r| [m[0][r], m[1][r], m[2][r]])
+}
+
+pub(crate) fn determinant(m: &Mat3) -> f32 {
+    dot(m[0], cross(m[1], m[2]))
+}
+
+pub(crate) fn mul_mat3(a: &Mat3, b: &Mat3) -> Mat3 {
+    [0, 1, 2].map(|r| [0, 1, 2].map(|c| (0..3).map(|k| a[r][k] * b[k][c]).sum()))
+}
+
+/// Builds the Blitz3D rotation (yaw, then pitch, then roll) from angles in degrees.
+pub(crate) fn euler_to_matrix(angles: [f32; 3]) -> Mat3 {
+    let [pitch, yaw, roll] = angles.map(f32::to_radians);
+    let (sp, cp) = pitch.sin_cos();
+    let (sy, cy) = yaw.sin_cos();
+    let (sr, cr) = roll.sin_cos();
+    let rx = [[1.0, 0.0, 0.0], [0.0, cp, -sp], [0.0, sp, cp]];
+    let ry = [[cy, 0.0, sy], [0.0, 1.0, 0.0], [-sy, 0.0, cy]];
+    let rz = [[cr, -sr, 0.0], [sr, cr, 0.0], [0.0, 0.0, 1.0]];
+    mul_mat3(&mul_mat3(&ry, &rx), &rz)
+}
+
+/// Inverse of [`euler_to_matrix`] for pure rotation matrices.
+pub(crate) fn matrix_to_euler(m: &Mat3) -> [f32; 3] {
+    let pitch = (-m[1][2]).clamp(-1.0, 1.0).asin();
+    let (yaw, roll) = if m[1][2].abs() < 0.9999 {
+        (m[0][2].atan2(m[2][2]), m[1][0].atan2(m[1][1]))
+    } else {
+        // Gimbal lock, fold the roll into the yaw
+        ((-m[2][0]).atan2(m[0][0]), 0.0)
+    };
+    [pitch, yaw, roll].map(f32::to_degrees)
+}
+
+pub(crate) fn triangle_area(v0: [f32; 3], v1: [f32; 3], v2: [f32; 3]) -> f32 {
+    length(cross(sub(v1, v0), sub(v2, v0))) * 0.5
+}
+
+/// Distance along the ray to triangle `abc`, hitting both sides.
+pub(crate) fn ray_triangle(
+    origin: [f32; 3],
+    direction: [f32; 3],
+    [a, b, c]: [[f32; 3]; 3],
+) -> Option<f32> {
+    // Möller-Trumbore
+    let edge1 = sub(b, a);
+    let edge2 = sub(c, a);
+    let p = cross(direction, edge2);
+    let det = dot(edge1, p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = sub(origin, a);
+    let u = dot(s, p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = cross(s, edge1);
+    let v = dot(direction, q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = dot(edge2, q) * inv_det;
+    (t >= 0.0).then_some(t)
+}
+
+/// Separating axis test between a triangle and a box given by center and half extents.
+pub(crate) fn triangle_intersects_box(
+    triangle: [[f32; 3]; 3],
+    center: [f32; 3],
+    half_extents: [f32; 3],
+) -> bool {
+    let v = triangle.map(|p| sub(p, center));
+    let separated = |axis: [f32; 3]| {
+        let projected = v.map(|p| dot(p, axis));
+        let radius: f32 = (0..3).map(|i| half_extents[i] * axis[i].abs()).sum();
+        let min = projected[0].min(projected[1]).min(projected[2]);
+        let max = projected[0].max(projected[1]).max(projected[2]);
+        min > radius || max < -radius
+    };
+
+    let box_axes = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let edges = [sub(v[1], v[0]), sub(v[2], v[1]), sub(v[0], v[2])];
+
+    !(box_axes.iter().any(|axis| separated(*axis))
+        || separated(cross(edges[0], edges[1]))
+        || box_axes
+            .iter()
+            .any(|axis| edges.iter().any(|edge| separated(cross(*axis, *edge)))))
+}
+
+/// Closest point on triangle `abc` to `p`.
+#[cfg(any(feature = "spatial", feature = "decompose"))]
+pub(crate) fn closest_point_on_triangle(
+    p: [f32; 3],
+    a: [f32; 3],
+    b: [f32; 3],
+    c: [f32; 3],
+) -> [f32; 3] {
+    let ab = sub(b, a);
+    let ac = sub(c, a);
+    let ap = sub(p, a);
+    let d1 = dot(ab, ap);
+    let d2 = dot(ac, ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = sub(p, b);
+    let d3 = dot(ab, bp);
+    let d4 = dot(ac, bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return add(a, scale(ab, d1 / (d1 - d3)));
+    }
+
+    let cp = sub(p, c);
+    let d5 = dot(ab, cp);
+    let d6 = dot(ac, cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return add(a, scale(ac, d2 / (d2 - d6)));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return add(b, scale(sub(c, b), (d4 - d3) / ((d4 - d3) + (d5 - d6))));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    add(a, add(scale(ab, vb * denom), scale(ac, vc * denom)))
+}
+
+pub(crate) fn mul_mat4(a: &Mat4, b: &Mat4) -> Mat4 {
+    b.map(|column| [0, 1, 2, 3].map(|r| (0..4).map(|k| a[k][r] * column[k]).sum()))
+}
+
+/// Affine matrix from a translation, a `[x, y, z, w]` quaternion and a scale.
+pub(crate) fn trs_matrix(translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> Mat4 {
+    let [x, y, z, w] = rotation;
+    let r = [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+        ],
+        [
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+        ],
+        [
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ];
+    let [tx, ty, tz] = translation;
+    [
+        [
+            r[0][0] * scale[0],
+            r[1][0] * scale[0],
+            r[2][0] * scale[0],
+            0.0,
+        ],
+        [
+            r[0][1] * scale[1],
+            r[1][1] * scale[1],
+            r[2][1] * scale[1],
+            0.0,
+        ],
+        [
+            r[0][2] * scale[2],
+            r[1][2] * scale[2],
+            r[2][2] * scale[2],
+            0.0,
+        ],
+        [tx, ty, tz, 1.0],
+    ]
+}
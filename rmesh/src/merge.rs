@@ -0,0 +1,185 @@
+use crate::{ComplexMesh, Header};
+
+impl ComplexMesh {
+    /// Appends the geometry of `other`, keeping the textures of `self`.
+    ///
+    /// A [`MeshExtension`](crate::MeshExtension) of `other` is kept too, when both meshes have a
+    /// different one the data of `other` is appended to that of `self`.
+    pub fn append(&mut self, other: ComplexMesh) {
+        match (&mut self.extension, other.extension) {
+            (Some(extension), Some(other)) if *extension != other => {
+                extension.data.extend(other.data)
+            }
+            (extension @ None, other) => *extension = other,
+            _ => (),
+        }
+
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.triangles.extend(
+            other
+                .triangles
+                .into_iter()
+                .map(|triangle| triangle.map(|i| i + offset)),
+        );
+    }
+
+    /// Same textures and extension, so appending loses nothing.
+    fn can_merge(&self, other: &ComplexMesh) -> bool {
+        self.textures == other.textures && self.extension == other.extension
+    }
+}
+
+impl Header {
+    /// Appends all geometry and entities of another room, translated by `offset`.
+    ///
+    /// Meshes using the same textures and extension as an existing mesh are merged into it.
+    /// Entities of `other` only move, see [`Header::translate`].
+    pub fn merge(&mut self, mut other: Header, offset: [f32; 3]) {
+        if offset != [0.0; 3] {
+            other.translate(offset);
+        }
+
+        for mesh in other.meshes {
+            match self.meshes.iter_mut().find(|m| m.can_merge(&mesh)) {
+                Some(existing) => existing.append(mesh),
+                None => self.meshes.push(mesh),
+            }
+        }
+        self.colliders.extend(other.colliders);
+        self.trigger_boxes.extend(other.trigger_boxes);
+        self.entities.extend(other.entities);
+    }
+
+    /// Reorganizes the geometry so every texture pair is used by exactly one mesh.
+    ///
+    /// Meshes sharing the same diffuse and lightmap textures are merged, keeping the
+    /// order in which each pair first appears. Meshes with different extensions stay apart.
+    pub fn split_by_texture(&mut self) {
+        let mut meshes: Vec<ComplexMesh> = Vec::with_capacity(self.meshes.len());
+        for mesh in self.meshes.drain(..) {
+            match meshes.iter_mut().find(|m| m.can_merge(&mesh)) {
+                Some(existing) => existing.append(mesh),
+                None => meshes.push(mesh),
+            }
+        }
+        self.meshes = meshes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        read_rmesh, write_rmesh, AnglesString, ComplexMesh, EntitySpotlight, EntityType, Header,
+        MeshExtension, Texture, TextureBlendType, Vertex,
+    };
+
+    fn room(texture: &str, angles: [f32; 3]) -> Header {
+        let texture = Texture {
+            blend_type: TextureBlendType::Visible,
+            path: Some(texture.into()),
+        };
+        Header {
+            meshes: vec![ComplexMesh {
+                textures: [texture, Texture::default()],
+                vertices: (0..3)
+                    .map(|i| Vertex {
+                        position: [i as f32, 0.0, 0.0],
+                        ..Default::default()
+                    })
+                    .collect(),
+                triangles: vec![[0, 1, 2]],
+                ..Default::default()
+            }],
+            entities: vec![EntityType::SpotLight(EntitySpotlight {
+                position: [1.0, 2.0, 3.0],
+                range: 400.0,
+                color: [255, 255, 255].into(),
+                intensity: 1.0,
+                angles: AnglesString::from(angles),
+                inner_cone_angle: 20.0,
+                outer_cone_angle: 45.0,
+            })
+            .into()],
+            ..Default::default()
+        }
+    }
+
+    fn spotlight(header: &Header, index: usize) -> &EntitySpotlight {
+        match &header.entities[index].entity_type {
+            Some(EntityType::SpotLight(data)) => data,
+            other => panic!("expected a spotlight, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn zero_offset_keeps_entities_exact() {
+        // Angles that don't survive a trip through a rotation matrix bit for bit
+        let angles = [33.3, 271.7, 0.1];
+        let other = room("b.jpg", angles);
+        let original = write_rmesh(&other).unwrap();
+
+        let mut merged = room("a.jpg", [0.0; 3]);
+        merged.merge(other, [0.0; 3]);
+
+        let light = spotlight(&merged, 1);
+        assert_eq!(light.angles.0, angles);
+        assert_eq!(light.position, [1.0, 2.0, 3.0]);
+        assert_eq!(light.range, 400.0);
+        // The merged room writes the angle string exactly as the original did
+        let angle_string = b"33.3 271.7 0.1";
+        let find = |bytes: &[u8]| bytes.windows(angle_string.len()).any(|w| w == angle_string);
+        assert!(find(&original));
+        assert!(find(&write_rmesh(&merged).unwrap()));
+    }
+
+    #[test]
+    fn offset_moves_geometry_and_entities_only() {
+        let mut merged = room("a.jpg", [0.0; 3]);
+        merged.merge(room("a.jpg", [10.0, 20.0, 30.0]), [100.0, 0.0, -50.0]);
+
+        // Same textures, so the meshes are merged into one
+        assert_eq!(merged.meshes.len(), 1);
+        let mesh = &merged.meshes[0];
+        assert_eq!(mesh.triangles, [[0, 1, 2], [3, 4, 5]]);
+        assert_eq!(mesh.vertices[4].position, [101.0, 0.0, -50.0]);
+
+        let light = spotlight(&merged, 1);
+        assert_eq!(light.position, [101.0, 2.0, -47.0]);
+        assert_eq!(light.angles.0, [10.0, 20.0, 30.0]);
+
+        let read = read_rmesh(&write_rmesh(&merged).unwrap()).unwrap();
+        assert_eq!(spotlight(&read, 1).angles.0, [10.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn extensions_survive_merging() {
+        let extension = |data: &[u8]| {
+            Some(MeshExtension {
+                data: data.to_vec(),
+            })
+        };
+        let mut merged = room("a.jpg", [0.0; 3]);
+        merged.meshes[0].extension = extension(&[1, 2]);
+        let mut other = room("a.jpg", [0.0; 3]);
+        other.meshes[0].extension = extension(&[3]);
+        other.meshes.push(room("a.jpg", [0.0; 3]).meshes.remove(0));
+        other.meshes[1].extension = extension(&[1, 2]);
+        merged.merge(other, [0.0; 3]);
+
+        // Different extensions keep the meshes apart, equal ones merge
+        assert_eq!(merged.meshes.len(), 2);
+        assert_eq!(merged.meshes[0].extension, extension(&[1, 2]));
+        assert_eq!(merged.meshes[0].triangles.len(), 2);
+        assert_eq!(merged.meshes[1].extension, extension(&[3]));
+
+        merged.split_by_texture();
+        assert_eq!(merged.meshes.len(), 2);
+
+        let mut mesh = room("a.jpg", [0.0; 3]).meshes.remove(0);
+        mesh.append(merged.meshes.remove(1));
+        assert_eq!(mesh.extension, extension(&[3]));
+        mesh.append(merged.meshes.remove(0));
+        assert_eq!(mesh.extension, extension(&[3, 1, 2]));
+    }
+}
@@ -0,0 +1,19 @@
+//! Reading rooms straight from memory-mapped files, enabled by the `mmap` feature.
+
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{read_rmesh, Header, RMeshError};
+
+/// Reads a .rmesh file by memory-mapping it instead of reading it into a buffer first.
+///
+/// Meant for tools scanning many rooms, the file is only paged in as it's parsed. The file
+/// must not be modified while it's read.
+pub fn read_rmesh_mmap(path: impl AsRef<Path>) -> Result<Header, RMeshError> {
+    let file = File::open(path)?;
+    // SAFETY: the mapping is dropped before returning, and the caller keeps the file unchanged
+    let mmap = unsafe { Mmap::map(&file)? };
+    read_rmesh(&mmap)
+}
@@ -0,0 +1,214 @@
+//! Waypoint navigation graph with A* pathfinding.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::Write;
+
+use crate::math;
+use crate::{EntityType, Header, ROOM_SCALE};
+
+/// Controls how waypoints are connected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavOptions {
+    /// Maximum distance between connected waypoints in room units.
+    pub max_distance: f32,
+    /// Only connect waypoints that can see each other.
+    pub line_of_sight: bool,
+}
+
+impl Default for NavOptions {
+    fn default() -> Self {
+        Self {
+            // The game links waypoints closer than 7 world units
+            max_distance: 7.0 / ROOM_SCALE,
+            line_of_sight: true,
+        }
+    }
+}
+
+/// A waypoint in the graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavNode {
+    /// Index into `Header::entities`.
+    pub entity_index: usize,
+    pub position: [f32; 3],
+}
+
+/// A connection to another node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavEdge {
+    pub to: usize,
+    pub cost: f32,
+}
+
+/// Navigation graph built from the waypoint entities of a room.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NavGraph {
+    pub nodes: Vec<NavNode>,
+    /// Outgoing edges per node.
+    pub edges: Vec<Vec<NavEdge>>,
+}
+
+impl NavGraph {
+    /// Collects the waypoints of a room and connects them according to `options`.
+    pub fn from_header(header: &Header, options: &NavOptions) -> Self {
+        let nodes: Vec<_> = header
+            .entities
+            .iter()
+            .enumerate()
+            .filter_map(|(entity_index, entity)| match &entity.entity_type {
+                Some(EntityType::WayPoint(data)) => Some(NavNode {
+                    entity_index,
+                    position: data.position,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let bvh = options.line_of_sight.then(|| header.bvh());
+        let mut edges = vec![vec![]; nodes.len()];
+
+        for a in 0..nodes.len() {
+            for b in a + 1..nodes.len() {
+                let delta = math::sub(nodes[b].position, nodes[a].position);
+                let distance = math::length(delta);
+                if distance > options.max_distance {
+                    continue;
+                }
+                if let Some(bvh) = &bvh {
+                    // Anything hit before reaching the other waypoint blocks the way
+                    if bvh
+                        .raycast(nodes[a].position, delta, distance * 0.999)
+                        .is_some()
+                    {
+                        continue;
+                    }
+                }
+                edges[a].push(NavEdge {
+                    to: b,
+                    cost: distance,
+                });
+                edges[b].push(NavEdge {
+                    to: a,
+                    cost: distance,
+                });
+            }
+        }
+
+        Self { nodes, edges }
+    }
+
+    /// Node closest to `point`.
+    pub fn nearest_node(&self, point: [f32; 3]) -> Option<usize> {
+        (0..self.nodes.len()).min_by(|a, b| {
+            let da = math::length(math::sub(self.nodes[*a].position, point));
+            let db = math::length(math::sub(self.nodes[*b].position, point));
+            da.total_cmp(&db)
+        })
+    }
+
+    /// Shortest path between two nodes as a list of node indices, including both ends.
+    pub fn find_path(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        if start >= self.nodes.len() || goal >= self.nodes.len() {
+            return None;
+        }
+
+        let heuristic = |node: usize| {
+            math::length(math::sub(
+                self.nodes[node].position,
+                self.nodes[goal].position,
+            ))
+        };
+
+        let mut costs = vec![f32::INFINITY; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0.0;
+        open.push(OpenNode {
+            node: start,
+            estimate: heuristic(start),
+        });
+
+        while let Some(OpenNode { node, estimate }) = open.pop() {
+            if node == goal {
+                let mut path = vec![goal];
+                while let Some(prev) = previous[*path.last().unwrap()] {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if estimate - heuristic(node) > costs[node] {
+                continue;
+            }
+
+            for edge in &self.edges[node] {
+                let cost = costs[node] + edge.cost;
+                if cost < costs[edge.to] {
+                    costs[edge.to] = cost;
+                    previous[edge.to] = Some(node);
+                    open.push(OpenNode {
+                        node: edge.to,
+                        estimate: cost + heuristic(edge.to),
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Every undirected edge once, as `(from, to, cost)`.
+    pub fn edge_list(&self) -> Vec<(usize, usize, f32)> {
+        self.edges
+            .iter()
+            .enumerate()
+            .flat_map(|(from, edges)| {
+                edges
+                    .iter()
+                    .filter(move |edge| from < edge.to)
+                    .map(move |edge| (from, edge.to, edge.cost))
+            })
+            .collect()
+    }
+
+    /// Exports the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph waypoints {\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let [x, _, z] = node.position;
+            let _ = writeln!(dot, "    {i} [label=\"{i}\" pos=\"{x},{z}\"];");
+        }
+        for (from, to, cost) in self.edge_list() {
+            let _ = writeln!(dot, "    {from} -- {to} [label=\"{cost:.0}\"];");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Entry of the A* open set, ordered so the heap pops the lowest estimate first.
+struct OpenNode {
+    node: usize,
+    estimate: f32,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
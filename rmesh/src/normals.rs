@@ -0,0 +1,249 @@
+use std::f32::consts::PI;
+
+use crate::math;
+
+/// Controls how vertex normals are generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalOptions {
+    /// Maximum angle in radians between two faces that are smoothed together.
+    ///
+    /// `0.0` gives flat shading, `PI` smooths across every shared vertex.
+    pub angle_threshold: f32,
+    /// Weight face normals by triangle area instead of averaging them equally.
+    pub area_weighted: bool,
+}
+
+impl NormalOptions {
+    /// One normal per face.
+    pub const FLAT: Self = Self {
+        angle_threshold: 0.0,
+        area_weighted: true,
+    };
+
+    /// Smooths across every shared vertex.
+    pub const SMOOTH: Self = Self {
+        angle_threshold: PI,
+        area_weighted: true,
+    };
+}
+
+impl Default for NormalOptions {
+    fn default() -> Self {
+        Self::SMOOTH
+    }
+}
+
+/// Normals generated with [`NormalOptions`], with vertices split along creases.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SplitNormals {
+    /// Index of the original vertex each output vertex was copied from.
+    pub vertex_indices: Vec<u32>,
+    /// One normal per output vertex.
+    pub normals: Vec<[f32; 3]>,
+    /// Triangles indexing the output vertices, without the input triangles referencing a
+    /// vertex out of range.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl SplitNormals {
+    /// Expands per-vertex data of the original mesh to match the output vertices.
+    pub fn remap<T: Clone>(&self, data: &[T]) -> Vec<T> {
+        self.vertex_indices
+            .iter()
+            .map(|i| data[*i as usize].clone())
+            .collect()
+    }
+}
+
+/// One area weighted normal per vertex, smoothed across every face using it.
+///
+/// Triangles with out of range indices or a non-finite normal are skipped, so the result always
+/// lines up with `positions`.
+pub(crate) fn smooth_normals(positions: &[[f32; 3]], triangles: &[[u32; 3]]) -> Vec<[f32; 3]> {
+    let mut normals = vec![[0.0, 0.0, 0.0]; positions.len()];
+    for triangle in triangles {
+        let [Some(v0), Some(v1), Some(v2)] = triangle.map(|i| positions.get(i as usize).copied())
+        else {
+            continue;
+        };
+        let normal = math::cross(math::sub(v1, v0), math::sub(v2, v0));
+        if !normal.iter().all(|c| c.is_finite()) {
+            continue;
+        }
+        for index in triangle {
+            let sum = &mut normals[*index as usize];
+            *sum = math::add(*sum, normal);
+        }
+    }
+    normals.into_iter().map(math::normalize).collect()
+}
+
+pub(crate) fn split_normals(
+    positions: &[[f32; 3]],
+    triangles: &[[u32; 3]],
+    options: &NormalOptions,
+) -> SplitNormals {
+    // Like the smooth normals, skip triangles that don't line up with the positions
+    let triangles: Vec<_> = triangles
+        .iter()
+        .filter(|triangle| triangle.iter().all(|i| (*i as usize) < positions.len()))
+        .copied()
+        .collect();
+
+    let face_normals: Vec<_> = triangles
+        .iter()
+        .map(|triangle| {
+            let [v0, v1, v2] = triangle.map(|i| positions[i as usize]);
+            math::cross(math::sub(v1, v0), math::sub(v2, v0))
+        })
+        .collect();
+    let unit_normals: Vec<_> = face_normals.iter().map(|n| math::normalize(*n)).collect();
+    let weights = if options.area_weighted {
+        &face_normals
+    } else {
+        &unit_normals
+    };
+
+    // Faces touching each vertex, with the corner they use it at
+    let mut corners = vec![vec![]; positions.len()];
+    for (face, triangle) in triangles.iter().enumerate() {
+        for (corner, index) in triangle.iter().enumerate() {
+            corners[*index as usize].push((face, corner));
+        }
+    }
+
+    let cos_threshold = if options.angle_threshold >= PI {
+        f32::NEG_INFINITY
+    } else {
+        options.angle_threshold.cos() - 1e-6
+    };
+
+    let mut split = SplitNormals {
+        triangles,
+        ..Default::default()
+    };
+
+    for (vertex, corners) in corners.iter().enumerate() {
+        // Keep unreferenced vertices so nothing gets lost
+        if corners.is_empty() {
+            split.vertex_indices.push(vertex as u32);
+            split.normals.push([0.0, 0.0, 0.0]);
+            continue;
+        }
+
+        let first = split.normals.len();
+        for &(face, corner) in corners {
+            let mut normal = [0.0, 0.0, 0.0];
+            for &(other, _) in corners {
+                if other == face
+                    || math::dot(unit_normals[face], unit_normals[other]) >= cos_threshold
+                {
+                    normal = math::add(normal, weights[other]);
+                }
+            }
+            let normal = math::normalize(normal);
+
+            // Corners ending up with the same normal share a vertex
+            let index = match split.normals[first..].iter().position(|n| *n == normal) {
+                Some(offset) => first + offset,
+                None => {
+                    split.vertex_indices.push(vertex as u32);
+                    split.normals.push(normal);
+                    split.normals.len() - 1
+                }
+            };
+            split.triangles[face][corner] = index as u32;
+        }
+    }
+
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtMesh, SimpleMesh};
+
+    // Two faces folded 90 degrees along the edge from vertex 0 to vertex 1
+    const FOLD: [[f32; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+    const FOLD_TRIANGLES: [[u32; 3]; 2] = [[0, 1, 2], [0, 3, 1]];
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn smooth_normals_average_shared_vertices() {
+        let normals = smooth_normals(&FOLD, &FOLD_TRIANGLES);
+        let diagonal = 0.5f32.sqrt();
+
+        assert_eq!(normals.len(), FOLD.len());
+        assert!(close(normals[0], [diagonal, diagonal, 0.0]));
+        assert!(close(normals[2], [0.0, 1.0, 0.0]));
+        assert!(close(normals[3], [1.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn smooth_normals_line_up_with_bad_input() {
+        let mut positions = FOLD.to_vec();
+        positions.push([f32::NAN, 0.0, f32::INFINITY]);
+        let triangles = [[0, 1, 2], [0, 4, 1], [1, 2, 9]];
+
+        let normals = smooth_normals(&positions, &triangles);
+
+        assert_eq!(normals.len(), positions.len());
+        assert!(close(normals[0], [0.0, 1.0, 0.0]));
+        assert_eq!(normals[4], [0.0; 3]);
+    }
+
+    #[test]
+    fn flat_normals_split_the_crease() {
+        let split = split_normals(&FOLD, &FOLD_TRIANGLES, &NormalOptions::FLAT);
+
+        // Both crease vertices get one copy per face
+        assert_eq!(split.vertex_indices, [0, 0, 1, 1, 2, 3]);
+        assert_eq!(split.remap(&FOLD).len(), split.normals.len());
+        for (triangle, original) in split.triangles.iter().zip(FOLD_TRIANGLES) {
+            let sources = triangle.map(|i| split.vertex_indices[i as usize]);
+            assert_eq!(sources, original);
+        }
+
+        let smooth = split_normals(&FOLD, &FOLD_TRIANGLES, &NormalOptions::SMOOTH);
+        assert_eq!(smooth.vertex_indices, [0, 1, 2, 3]);
+        assert!(close(
+            smooth.normals[0],
+            smooth_normals(&FOLD, &FOLD_TRIANGLES)[0]
+        ));
+    }
+
+    #[test]
+    fn split_normals_skip_out_of_range_triangles() {
+        let triangles = [FOLD_TRIANGLES[0], [0, 1, 9], FOLD_TRIANGLES[1]];
+
+        let split = split_normals(&FOLD, &triangles, &NormalOptions::FLAT);
+
+        assert_eq!(
+            split,
+            split_normals(&FOLD, &FOLD_TRIANGLES, &NormalOptions::FLAT)
+        );
+    }
+
+    #[test]
+    fn generic_meshes_reach_the_options() {
+        fn flat(mesh: &impl ExtMesh) -> SplitNormals {
+            mesh.calculate_normals_with(&NormalOptions::FLAT)
+        }
+
+        let mesh = SimpleMesh::new(FOLD.to_vec(), FOLD_TRIANGLES.to_vec());
+
+        assert_eq!(
+            flat(&mesh),
+            split_normals(&FOLD, &FOLD_TRIANGLES, &NormalOptions::FLAT)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::{self, Write};
+
+use crate::convert::CoordinateTransform;
+use crate::{ExtMesh, Header, Texture, TextureBlendType};
+
+/// A Wavefront `.obj` file and the `.mtl` library it references.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjExport {
+    pub obj: String,
+    pub mtl: String,
+}
+
+impl Header {
+    /// Exports the visible meshes as Wavefront OBJ in right-handed world units.
+    ///
+    /// `mtl_name` is the file name the `.obj` uses to reference the material library.
+    pub fn to_obj(&self, mtl_name: &str) -> ObjExport {
+        self.to_obj_with(mtl_name, &CoordinateTransform::BLITZ3D_TO_RIGHT_HANDED)
+    }
+
+    /// Exports the visible meshes as Wavefront OBJ, moving them with `transform`.
+    ///
+    /// OBJ only has one texture coordinate set, so meshes with a diffuse texture export
+    /// their diffuse coordinates and lightmap-only meshes export their lightmap
+    /// coordinates. Invisible collision meshes are skipped.
+    pub fn to_obj_with(&self, mtl_name: &str, transform: &CoordinateTransform) -> ObjExport {
+        let mut export = ObjExport::default();
+        self.write_obj(&mut export, mtl_name, transform)
+            .expect("writing to a String can't fail");
+        export
+    }
+
+    fn write_obj(
+        &self,
+        export: &mut ObjExport,
+        mtl_name: &str,
+        transform: &CoordinateTransform,
+    ) -> fmt::Result {
+        let ObjExport { obj, mtl } = export;
+        writeln!(obj, "mtllib {mtl_name}")?;
+
+        let mut materials: Vec<[Texture; 2]> = vec![];
+        // OBJ indices are 1-based and global across the file
+        let mut base = 1;
+
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            if mesh.is_invisible() {
+                continue;
+            }
+
+            let [lightmap, diffuse] = &mesh.textures;
+            let channel = if diffuse.path.is_none() && lightmap.path.is_some() {
+                1
+            } else {
+                0
+            };
+
+            let material = match materials.iter().position(|m| *m == mesh.textures) {
+                Some(index) => index,
+                None => {
+                    materials.push(mesh.textures.clone());
+                    write_material(mtl, materials.len() - 1, &mesh.textures)?;
+                    materials.len() - 1
+                }
+            };
+
+            writeln!(obj, "o mesh{i}")?;
+            for vertex in &mesh.vertices {
+                let [x, y, z] = transform.position(vertex.position);
+                writeln!(obj, "v {x} {y} {z}")?;
+            }
+            for vertex in &mesh.vertices {
+                let [u, v] = vertex.tex_coords[channel];
+                // Blitz3D puts the texture origin at the top left, OBJ at the bottom left
+                writeln!(obj, "vt {u} {}", 1.0 - v)?;
+            }
+            for normal in mesh.calculate_normals() {
+                let [x, y, z] = normal;
+                let z = if transform.flip_z { -z } else { z };
+                writeln!(obj, "vn {x} {y} {z}")?;
+            }
+
+            writeln!(obj, "usemtl {}", material_name(material, &mesh.textures))?;
+            for triangle in &mesh.triangles {
+                let [a, b, c] = transform.triangle(*triangle).map(|i| i as usize + base);
+                writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+            }
+
+            base += mesh.vertices.len();
+        }
+
+        Ok(())
+    }
+}
+
+/// Unique material name built from the texture file stem.
+fn material_name(index: usize, textures: &[Texture; 2]) -> String {
+    let [lightmap, diffuse] = textures;
+    let stem = diffuse
+        .path
+        .as_ref()
+        .or(lightmap.path.as_ref())
+        .map(String::from)
+        .and_then(|path| {
+            path.rsplit(['/', '\\'])
+                .next()
+                .and_then(|name| name.split('.').next())
+                .map(|stem| stem.replace(char::is_whitespace, "_"))
+        })
+        .unwrap_or_default();
+    format!("mat{index}_{stem}")
+}
+
+fn write_material(mtl: &mut String, index: usize, textures: &[Texture; 2]) -> fmt::Result {
+    let [lightmap, diffuse] = textures;
+    writeln!(mtl, "newmtl {}", material_name(index, textures))?;
+    writeln!(mtl, "Kd 1 1 1")?;
+
+    let texture = if diffuse.path.is_some() {
+        diffuse
+    } else {
+        lightmap
+    };
+    if let Some(path) = &texture.path {
+        let path = String::from(path);
+        writeln!(mtl, "map_Kd {path}")?;
+        if texture.blend_type == TextureBlendType::Transparent {
+            writeln!(mtl, "map_d {path}")?;
+        }
+    }
+    writeln!(mtl)
+}
@@ -0,0 +1,145 @@
+use crate::ComplexMesh;
+
+/// Size of the simulated post-transform vertex cache.
+const CACHE_SIZE: usize = 32;
+
+/// Score of a vertex in Tom Forsyth's linear-speed vertex cache optimization.
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+
+    let cache_score = match cache_position {
+        // Vertices of the last triangle get a fixed score so they aren't reused right away
+        Some(position) if position < 3 => 0.75,
+        Some(position) => {
+            let scaler = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scaler).powf(1.5)
+        }
+        None => 0.0,
+    };
+
+    // Favour vertices with few triangles left so they can leave the cache
+    cache_score + 2.0 * (remaining as f32).powf(-0.5)
+}
+
+impl ComplexMesh {
+    /// Reorders triangles to improve post-transform vertex cache hits.
+    pub fn optimize_vertex_cache(&mut self) {
+        let vertex_count = self.vertices.len();
+        let triangles = &self.triangles;
+        if triangles
+            .iter()
+            .flatten()
+            .any(|i| *i as usize >= vertex_count)
+        {
+            return;
+        }
+
+        let mut vertex_triangles = vec![vec![]; vertex_count];
+        for (t, triangle) in triangles.iter().enumerate() {
+            for index in triangle {
+                vertex_triangles[*index as usize].push(t);
+            }
+        }
+
+        let mut remaining: Vec<usize> = vertex_triangles.iter().map(Vec::len).collect();
+        let mut cache_position = vec![None; vertex_count];
+        let mut scores: Vec<f32> = (0..vertex_count)
+            .map(|v| vertex_score(None, remaining[v]))
+            .collect();
+        let triangle_score = |scores: &[f32], t: usize| {
+            triangles[t]
+                .iter()
+                .map(|i| scores[*i as usize])
+                .sum::<f32>()
+        };
+
+        let mut added = vec![false; triangles.len()];
+        let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+        let mut order = Vec::with_capacity(triangles.len());
+        let mut scan_start = 0;
+
+        while order.len() < triangles.len() {
+            // Prefer triangles touching cached vertices, fall back to a scan
+            let best = cache
+                .iter()
+                .flat_map(|v| &vertex_triangles[*v as usize])
+                .filter(|t| !added[**t])
+                .copied()
+                .max_by(|a, b| triangle_score(&scores, *a).total_cmp(&triangle_score(&scores, *b)))
+                .unwrap_or_else(|| {
+                    while added[scan_start] {
+                        scan_start += 1;
+                    }
+                    scan_start
+                });
+
+            added[best] = true;
+            order.push(best);
+
+            for index in triangles[best] {
+                remaining[index as usize] -= 1;
+                cache.retain(|v| *v != index);
+                cache.insert(0, index);
+            }
+            let evicted = cache.split_off(cache.len().min(CACHE_SIZE));
+            for vertex in evicted {
+                cache_position[vertex as usize] = None;
+                scores[vertex as usize] = vertex_score(None, remaining[vertex as usize]);
+            }
+            for (position, vertex) in cache.iter().enumerate() {
+                let vertex = *vertex as usize;
+                cache_position[vertex] = Some(position);
+                scores[vertex] = vertex_score(Some(position), remaining[vertex]);
+            }
+        }
+
+        self.triangles = order.into_iter().map(|t| self.triangles[t]).collect();
+    }
+
+    /// Reorders vertices by first use so they're fetched sequentially.
+    ///
+    /// Vertices not referenced by any triangle are dropped.
+    pub fn optimize_vertex_fetch(&mut self) {
+        let mut remap = vec![None; self.vertices.len()];
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+
+        for triangle in &mut self.triangles {
+            for index in triangle.iter_mut() {
+                let Some(vertex) = self.vertices.get(*index as usize) else {
+                    continue;
+                };
+                *index = *remap[*index as usize].get_or_insert_with(|| {
+                    vertices.push(vertex.clone());
+                    vertices.len() as u32 - 1
+                });
+            }
+        }
+
+        self.vertices = vertices;
+    }
+
+    /// Average number of vertex cache misses per triangle for a FIFO cache.
+    ///
+    /// Lower is better, `0.5` is close to optimal and `3.0` means no reuse at all.
+    pub fn average_cache_miss_ratio(&self, cache_size: usize) -> f32 {
+        if self.triangles.is_empty() {
+            return 0.0;
+        }
+
+        let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+        let mut misses = 0;
+        for index in self.triangles.iter().flatten() {
+            if !cache.contains(index) {
+                misses += 1;
+                if cache.len() == cache_size {
+                    cache.pop_front();
+                }
+                cache.push_back(*index);
+            }
+        }
+
+        misses as f32 / self.triangles.len() as f32
+    }
+}
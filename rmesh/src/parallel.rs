@@ -0,0 +1,65 @@
+//! Reading rooms with their meshes decoded on several threads, enabled by the `parallel` feature.
+
+use std::io::Cursor;
+
+use binrw::{BinReaderExt, BinResult};
+use rayon::prelude::*;
+
+use crate::compression;
+use crate::sections::mesh_ranges;
+use crate::variant::{has_mesh_data, has_trigger_boxes, tag_sections};
+use crate::{
+    read_rmesh, ComplexMesh, EntityData, FixedLengthString, FormatVariant, Header, RMeshError,
+    SimpleMesh, TriggerBox,
+};
+
+/// Reads a .rmesh file like [`read_rmesh`], decoding the meshes in parallel.
+///
+/// The mesh boundaries are found first by skipping over the vertex and triangle data, then
+/// every mesh is decoded on the rayon thread pool. Worth it for big rooms with many meshes,
+/// small rooms read just as fast with [`read_rmesh`]. Files whose meshes can't be skipped
+/// over are read sequentially, so errors are the same as [`read_rmesh`]'s.
+pub fn read_rmesh_parallel(bytes: &[u8]) -> Result<Header, RMeshError> {
+    let bytes = compression::decompress(bytes)?;
+    let mut cursor = Cursor::new(&bytes[..]);
+    let Ok(tag) = cursor.read_le::<FixedLengthString>() else {
+        return read_rmesh(&bytes);
+    };
+    let (Some(_), Ok(ranges)) = (tag_sections(&tag.values), mesh_ranges(&mut cursor, &tag)) else {
+        return read_rmesh(&bytes);
+    };
+
+    let mesh_data = has_mesh_data(&tag);
+    let meshes = ranges
+        .into_par_iter()
+        .map(|range| Cursor::new(&bytes[range.mesh]).read_le_args::<ComplexMesh>((mesh_data,)))
+        .collect::<BinResult<Vec<_>>>()?;
+
+    let colliders = read_vec::<SimpleMesh>(&mut cursor)?;
+    let trigger_boxes = if has_trigger_boxes(&tag) {
+        read_vec::<TriggerBox>(&mut cursor)?
+    } else {
+        vec![]
+    };
+    let entities = read_vec::<EntityData>(&mut cursor)?;
+    let extra = bytes[cursor.position() as usize..].to_vec();
+    let variant = FormatVariant::detect(&tag.values, &meshes, &extra);
+
+    Ok(Header {
+        kind: tag,
+        meshes,
+        colliders,
+        trigger_boxes,
+        entities,
+        extra,
+        variant,
+    })
+}
+
+fn read_vec<T>(cursor: &mut Cursor<&[u8]>) -> BinResult<Vec<T>>
+where
+    T: for<'a> binrw::BinRead<Args<'a> = ()>,
+{
+    let count: u32 = cursor.read_le()?;
+    (0..count).map(|_| cursor.read_le()).collect()
+}
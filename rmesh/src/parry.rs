@@ -0,0 +1,67 @@
+//! [`parry3d`] shapes for room collision, for Rapier and standalone physics tooling.
+//!
+//! Shapes are in room units, like the meshes they are built from.
+
+use parry3d::math::Point;
+use parry3d::shape::{SharedShape, TriMesh};
+
+use crate::{ComplexMesh, Header, SimpleMesh};
+
+impl SimpleMesh {
+    /// Builds a trimesh from the valid triangles, `None` when there are none.
+    pub fn to_trimesh(&self) -> Option<TriMesh> {
+        trimesh([(self.vertices.as_slice(), self.triangles.as_slice())])
+    }
+}
+
+impl ComplexMesh {
+    /// Builds a trimesh from the vertex positions and valid triangles, `None` when there are none.
+    pub fn to_trimesh(&self) -> Option<TriMesh> {
+        let positions = self.positions();
+        trimesh([(positions.as_slice(), self.triangles.as_slice())])
+    }
+}
+
+impl Header {
+    /// Collider for everything the player collides with: every room mesh, visible or not, and
+    /// the explicit colliders. `None` when the room has no valid triangles.
+    ///
+    /// parry doesn't allow trimeshes inside a [`Compound`](parry3d::shape::Compound), so the
+    /// surfaces are joined into a single trimesh shape instead.
+    pub fn to_compound_collider(&self) -> Option<SharedShape> {
+        let positions: Vec<_> = self.meshes.iter().map(ComplexMesh::positions).collect();
+        let meshes = positions
+            .iter()
+            .zip(&self.meshes)
+            .map(|(positions, mesh)| (positions.as_slice(), mesh.triangles.as_slice()));
+        let colliders = self
+            .colliders
+            .iter()
+            .map(|collider| (collider.vertices.as_slice(), collider.triangles.as_slice()));
+        let trimesh = trimesh(meshes.chain(colliders))?;
+        Some(SharedShape::new(trimesh))
+    }
+}
+
+/// Joins surfaces into one trimesh, dropping triangles with out of range indices.
+fn trimesh<'a>(
+    surfaces: impl IntoIterator<Item = (&'a [[f32; 3]], &'a [[u32; 3]])>,
+) -> Option<TriMesh> {
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    for (positions, triangles) in surfaces {
+        let offset = vertices.len() as u32;
+        vertices.extend(positions.iter().map(|&position| Point::from(position)));
+        indices.extend(
+            triangles
+                .iter()
+                .filter(|triangle| triangle.iter().all(|&i| (i as usize) < positions.len()))
+                .map(|triangle| triangle.map(|i| i + offset)),
+        );
+    }
+    // parry panics on trimeshes without triangles
+    if indices.is_empty() {
+        return None;
+    }
+    Some(TriMesh::new(vertices, indices))
+}
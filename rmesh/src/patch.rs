@@ -0,0 +1,132 @@
+//! Edits .rmesh files in place, without decoding and re-encoding them.
+
+use std::io::Cursor;
+
+use binrw::BinReaderExt;
+
+use crate::resolve::same_path;
+use crate::sections::mesh_ranges;
+use crate::{compression, FixedLengthString, RMeshError};
+
+/// Replaces every mesh texture path equal to `old` with `new`, fixing up the length prefixes.
+///
+/// Paths are compared like the game finds files on Windows, ignoring case and slash direction.
+/// Only texture paths and counts are decoded, everything else is copied as is. Compressed files
+/// can't be patched in place, decompress them first.
+pub fn replace_texture_path(bytes: &[u8], old: &str, new: &str) -> Result<Vec<u8>, RMeshError> {
+    if compression::is_compressed(bytes) {
+        return Err(RMeshError::Compressed);
+    }
+    let mut cursor = Cursor::new(bytes);
+    let tag: FixedLengthString = cursor.read_le()?;
+    let ranges = mesh_ranges(&mut cursor, &tag)?;
+
+    let mut patched = Vec::with_capacity(bytes.len());
+    let mut copied = 0;
+    for range in ranges
+        .iter()
+        .flat_map(|range| range.textures.iter().flatten())
+    {
+        // Skips the length prefix
+        let path = String::from_utf8_lossy(&bytes[range.start + 4..range.end]);
+        if !same_path(&path, old) {
+            continue;
+        }
+        patched.extend_from_slice(&bytes[copied..range.start]);
+        patched.extend_from_slice(&(new.len() as u32).to_le_bytes());
+        patched.extend_from_slice(new.as_bytes());
+        copied = range.end;
+    }
+    patched.extend_from_slice(&bytes[copied..]);
+    Ok(patched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        read_rmesh, write_rmesh, ComplexMesh, EntityScreen, EntityType, Header, Texture,
+        TextureBlendType,
+    };
+
+    fn textured(diffuse: &str, lightmap: &str) -> ComplexMesh {
+        let texture = |blend_type, path: &str| Texture {
+            blend_type,
+            path: Some(path.into()),
+        };
+        ComplexMesh {
+            textures: [
+                texture(TextureBlendType::Lightmap, lightmap),
+                texture(TextureBlendType::Visible, diffuse),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn paths(header: &Header) -> Vec<String> {
+        header
+            .meshes
+            .iter()
+            .flat_map(|mesh| &mesh.textures)
+            .filter_map(|texture| texture.path.as_ref())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn longer_and_shorter_paths_fix_up_the_lengths() {
+        let header = Header {
+            meshes: vec![
+                textured("GFX\\Map\\Wall.jpg", "room_lm1.png"),
+                textured("floor.jpg", "room_lm2.png"),
+                textured("gfx/map/wall.JPG", "room_lm3.png"),
+            ],
+            entities: vec![EntityType::Screen(EntityScreen {
+                position: [1.0, 2.0, 3.0],
+                name: "screen.jpg".into(),
+            })
+            .into()],
+            ..Default::default()
+        };
+        let bytes = write_rmesh(&header).unwrap();
+
+        let longer =
+            replace_texture_path(&bytes, "gfx/map/wall.jpg", "GFX/map/new/wall.png").unwrap();
+        assert_eq!(longer.len(), bytes.len() + 2 * 4);
+        let read = read_rmesh(&longer).unwrap();
+        assert_eq!(
+            paths(&read),
+            [
+                "room_lm1.png",
+                "GFX/map/new/wall.png",
+                "room_lm2.png",
+                "floor.jpg",
+                "room_lm3.png",
+                "GFX/map/new/wall.png",
+            ]
+        );
+        let Some(EntityType::Screen(screen)) = &read.entities[0].entity_type else {
+            panic!("screen entity lost");
+        };
+        assert_eq!(String::from(&screen.name), "screen.jpg");
+
+        let shorter = replace_texture_path(&bytes, "floor.jpg", "f.jpg").unwrap();
+        assert_eq!(shorter.len(), bytes.len() - 4);
+        assert_eq!(paths(&read_rmesh(&shorter).unwrap())[3], "f.jpg");
+    }
+
+    #[test]
+    fn unmatched_path_copies_the_file() {
+        let header = Header {
+            meshes: vec![textured("door.jpg", "door_lm1.png")],
+            extra: vec![7; 5],
+            ..Default::default()
+        };
+        let bytes = write_rmesh(&header).unwrap();
+
+        assert_eq!(
+            replace_texture_path(&bytes, "doors.jpg", "x.jpg").unwrap(),
+            bytes
+        );
+    }
+}
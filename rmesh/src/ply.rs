@@ -0,0 +1,92 @@
+use std::io::{self, Write};
+
+use crate::convert::CoordinateTransform;
+use crate::{ExtMesh, Header};
+
+/// Encoding of a PLY file body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlyFormat {
+    #[default]
+    Ascii,
+    BinaryLittleEndian,
+}
+
+impl Header {
+    /// Exports all visual geometry merged into a single PLY mesh in right-handed world units.
+    pub fn to_ply(&self, format: PlyFormat) -> Vec<u8> {
+        self.to_ply_with(format, &CoordinateTransform::BLITZ3D_TO_RIGHT_HANDED)
+    }
+
+    /// Exports all visual geometry merged into a single PLY mesh, moving it with `transform`.
+    ///
+    /// Every vertex carries its position, smooth normal and color.
+    pub fn to_ply_with(&self, format: PlyFormat, transform: &CoordinateTransform) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write_ply(&mut bytes, format, transform)
+            .expect("writing to a Vec can't fail");
+        bytes
+    }
+
+    fn write_ply(
+        &self,
+        writer: &mut impl Write,
+        format: PlyFormat,
+        transform: &CoordinateTransform,
+    ) -> io::Result<()> {
+        let vertex_count: usize = self.meshes.iter().map(|m| m.vertices.len()).sum();
+        let face_count: usize = self.meshes.iter().map(|m| m.triangles.len()).sum();
+
+        writeln!(writer, "ply")?;
+        match format {
+            PlyFormat::Ascii => writeln!(writer, "format ascii 1.0")?,
+            PlyFormat::BinaryLittleEndian => writeln!(writer, "format binary_little_endian 1.0")?,
+        }
+        writeln!(writer, "element vertex {vertex_count}")?;
+        for property in ["x", "y", "z", "nx", "ny", "nz"] {
+            writeln!(writer, "property float {property}")?;
+        }
+        for property in ["red", "green", "blue"] {
+            writeln!(writer, "property uchar {property}")?;
+        }
+        writeln!(writer, "element face {face_count}")?;
+        writeln!(writer, "property list uchar uint vertex_indices")?;
+        writeln!(writer, "end_header")?;
+
+        for mesh in &self.meshes {
+            for (vertex, normal) in mesh.vertices.iter().zip(mesh.calculate_normals()) {
+                let [x, y, z] = transform.position(vertex.position);
+                let [nx, ny, nz] = normal;
+                let nz = if transform.flip_z { -nz } else { nz };
+                let [r, g, b] = vertex.color;
+                match format {
+                    PlyFormat::Ascii => writeln!(writer, "{x} {y} {z} {nx} {ny} {nz} {r} {g} {b}")?,
+                    PlyFormat::BinaryLittleEndian => {
+                        for value in [x, y, z, nx, ny, nz] {
+                            writer.write_all(&value.to_le_bytes())?;
+                        }
+                        writer.write_all(&vertex.color)?;
+                    }
+                }
+            }
+        }
+
+        let mut base = 0;
+        for mesh in &self.meshes {
+            for triangle in &mesh.triangles {
+                let [a, b, c] = transform.triangle(*triangle).map(|i| i + base);
+                match format {
+                    PlyFormat::Ascii => writeln!(writer, "3 {a} {b} {c}")?,
+                    PlyFormat::BinaryLittleEndian => {
+                        writer.write_all(&[3])?;
+                        for index in [a, b, c] {
+                            writer.write_all(&index.to_le_bytes())?;
+                        }
+                    }
+                }
+            }
+            base += mesh.vertices.len() as u32;
+        }
+
+        Ok(())
+    }
+}
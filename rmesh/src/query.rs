@@ -0,0 +1,120 @@
+use crate::{
+    EntityData, EntityLight, EntityModel, EntityPlayerStart, EntityScreen, EntitySoundEmitter,
+    EntitySpotlight, EntityType, EntityWaypoint, Header,
+};
+
+/// An entity payload that can be pulled out of an [`EntityType`].
+pub trait Entity: Sized + 'static {
+    /// Returns the payload if `entity` is of this type.
+    fn from_entity(entity: &EntityType) -> Option<&Self>;
+    /// Returns the mutable payload if `entity` is of this type.
+    fn from_entity_mut(entity: &mut EntityType) -> Option<&mut Self>;
+}
+
+macro_rules! impl_entity {
+    ($ty:ty, $variant:ident) => {
+        impl Entity for $ty {
+            fn from_entity(entity: &EntityType) -> Option<&Self> {
+                match entity {
+                    EntityType::$variant(data) => Some(data),
+                    _ => None,
+                }
+            }
+
+            fn from_entity_mut(entity: &mut EntityType) -> Option<&mut Self> {
+                match entity {
+                    EntityType::$variant(data) => Some(data),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_entity!(EntityScreen, Screen);
+impl_entity!(EntityWaypoint, WayPoint);
+impl_entity!(EntityLight, Light);
+impl_entity!(EntitySpotlight, SpotLight);
+impl_entity!(EntitySoundEmitter, SoundEmitter);
+impl_entity!(EntityPlayerStart, PlayerStart);
+impl_entity!(EntityModel, Model);
+
+impl Header {
+    /// Iterates over the entities with a known type.
+    pub fn entity_types(&self) -> impl Iterator<Item = &EntityType> {
+        self.entities.iter().filter_map(|e| e.entity_type.as_ref())
+    }
+
+    /// Iterates over every entity of type `T`.
+    pub fn entities_of<T: Entity>(&self) -> impl Iterator<Item = &T> {
+        self.entity_types().filter_map(T::from_entity)
+    }
+
+    /// Mutably iterates over every entity of type `T`.
+    pub fn entities_of_mut<T: Entity>(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entities
+            .iter_mut()
+            .filter_map(|e| e.entity_type.as_mut())
+            .filter_map(T::from_entity_mut)
+    }
+
+    /// Iterates over the screens.
+    pub fn screens(&self) -> impl Iterator<Item = &EntityScreen> {
+        self.entities_of()
+    }
+
+    /// Iterates over the waypoints.
+    pub fn waypoints(&self) -> impl Iterator<Item = &EntityWaypoint> {
+        self.entities_of()
+    }
+
+    /// Iterates over the point lights.
+    pub fn lights(&self) -> impl Iterator<Item = &EntityLight> {
+        self.entities_of()
+    }
+
+    /// Iterates over the spotlights.
+    pub fn spotlights(&self) -> impl Iterator<Item = &EntitySpotlight> {
+        self.entities_of()
+    }
+
+    /// Iterates over the sound emitters.
+    pub fn sound_emitters(&self) -> impl Iterator<Item = &EntitySoundEmitter> {
+        self.entities_of()
+    }
+
+    /// Iterates over the player starts.
+    pub fn player_starts(&self) -> impl Iterator<Item = &EntityPlayerStart> {
+        self.entities_of()
+    }
+
+    /// Iterates over the prop models.
+    pub fn models(&self) -> impl Iterator<Item = &EntityModel> {
+        self.entities_of()
+    }
+
+    /// Finds the first screen or model whose file name equals `name`.
+    pub fn find_entity_by_name(&self, name: &str) -> Option<&EntityType> {
+        self.entity_types().find(|entity| match entity {
+            EntityType::Screen(data) => data.name.values == name.as_bytes(),
+            EntityType::Model(data) => data.name.values == name.as_bytes(),
+            _ => false,
+        })
+    }
+
+    /// Appends an entity and returns its index.
+    pub fn add_entity(&mut self, entity: impl Into<EntityData>) -> usize {
+        self.entities.push(entity.into());
+        self.entities.len() - 1
+    }
+
+    /// Removes the entity at `index`, returning `None` if it is out of range.
+    pub fn remove_entity(&mut self, index: usize) -> Option<EntityData> {
+        (index < self.entities.len()).then(|| self.entities.remove(index))
+    }
+
+    /// Keeps only the entities for which `predicate` returns `true`.
+    pub fn retain_entities(&mut self, predicate: impl FnMut(&EntityData) -> bool) {
+        self.entities.retain(predicate);
+    }
+}
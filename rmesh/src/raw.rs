@@ -0,0 +1,144 @@
+//! Vertices in the file layout, viewed in place with bytemuck, enabled by the `bytemuck` feature.
+
+use std::io::Cursor;
+
+use binrw::BinReaderExt;
+use bytemuck::{Pod, Zeroable};
+
+use crate::sections::mesh_ranges;
+use crate::{compression, ComplexMesh, FixedLengthString, RMeshError, Vertex};
+
+/// A [`Vertex`] laid out like in the file: position, both texture coordinates and the color,
+/// 31 bytes without padding.
+///
+/// Packed, so fields are read by copy. Vertex buffers using it have a 31 byte stride, which
+/// OpenGL takes as is but WebGPU doesn't.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct RawVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [[f32; 2]; 2],
+    pub color: [u8; 3],
+}
+
+impl From<&Vertex> for RawVertex {
+    fn from(vertex: &Vertex) -> Self {
+        Self {
+            position: vertex.position,
+            tex_coords: vertex.tex_coords,
+            color: vertex.color,
+        }
+    }
+}
+
+impl From<RawVertex> for Vertex {
+    fn from(vertex: RawVertex) -> Self {
+        Self {
+            position: vertex.position,
+            tex_coords: vertex.tex_coords,
+            color: vertex.color,
+        }
+    }
+}
+
+impl ComplexMesh {
+    /// The vertices in the file layout.
+    pub fn raw_vertices(&self) -> Vec<RawVertex> {
+        self.vertices.iter().map(RawVertex::from).collect()
+    }
+
+    /// The vertices in the file layout as bytes, ready to upload.
+    ///
+    /// The vertices are written to `buffer`, which the bytes borrow. Reuse it across meshes to
+    /// upload them all without allocating again.
+    pub fn raw_vertex_bytes<'a>(&self, buffer: &'a mut Vec<RawVertex>) -> &'a [u8] {
+        buffer.clear();
+        buffer.extend(self.vertices.iter().map(RawVertex::from));
+        bytemuck::cast_slice(buffer)
+    }
+
+    /// The positions as one flat run of floats, three per vertex, ready to upload.
+    ///
+    /// Filled into `buffer` like [`ComplexMesh::raw_vertex_bytes`].
+    pub fn positions_slice<'a>(&self, buffer: &'a mut Vec<[f32; 3]>) -> &'a [f32] {
+        buffer.clear();
+        buffer.extend(self.vertices.iter().map(|vertex| vertex.position));
+        bytemuck::cast_slice(buffer)
+    }
+}
+
+/// The vertices of each mesh of a .rmesh file, viewed in place without copying.
+///
+/// Only texture paths and counts are decoded to find the vertex data. Compressed files have
+/// no vertex data to borrow, decompress them first.
+pub fn raw_vertex_views(bytes: &[u8]) -> Result<Vec<&[RawVertex]>, RMeshError> {
+    if compression::is_compressed(bytes) {
+        return Err(RMeshError::Compressed);
+    }
+    let mut cursor = Cursor::new(bytes);
+    let tag: FixedLengthString = cursor.read_le()?;
+    let ranges = mesh_ranges(&mut cursor, &tag)?;
+    Ok(ranges
+        .into_iter()
+        .map(|range| bytemuck::cast_slice(&bytes[range.vertices]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_rmesh, Header, MeshExtension, Texture, TextureBlendType};
+
+    fn mesh(first: f32, count: usize) -> ComplexMesh {
+        ComplexMesh {
+            textures: [
+                Texture::default(),
+                Texture {
+                    blend_type: TextureBlendType::Transparent,
+                    path: Some("glass.png".into()),
+                },
+            ],
+            vertices: (0..count)
+                .map(|i| Vertex {
+                    position: [first + i as f32, 1.0, 2.0],
+                    tex_coords: [[0.25, 0.5], [0.75, 1.0]],
+                    color: [1, 2, 3],
+                })
+                .collect(),
+            triangles: vec![[0, 0, 0]; count / 3],
+            extension: Some(MeshExtension { data: vec![5; 3] }),
+        }
+    }
+
+    #[test]
+    fn views_match_the_parsed_vertices() {
+        let header = Header {
+            meshes: vec![mesh(0.0, 3), mesh(100.0, 7)],
+            ..Default::default()
+        };
+        let bytes = write_rmesh(&header).unwrap();
+
+        let views = raw_vertex_views(&bytes).unwrap();
+
+        assert_eq!(views.len(), 2);
+        for (view, mesh) in views.iter().zip(&header.meshes) {
+            assert_eq!(*view, &mesh.raw_vertices()[..]);
+        }
+        let position = views[1][6].position;
+        assert_eq!(position, [106.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn byte_and_position_slices_reuse_the_buffer() {
+        let mut vertices = vec![];
+        let mut positions = vec![];
+
+        assert_eq!(mesh(0.0, 2).raw_vertex_bytes(&mut vertices).len(), 2 * 31);
+        let bytes = mesh(8.0, 1).raw_vertex_bytes(&mut vertices);
+        assert_eq!(bytes.len(), 31);
+        assert_eq!(&bytes[..4], 8.0f32.to_le_bytes());
+
+        let floats = mesh(4.0, 2).positions_slice(&mut positions);
+        assert_eq!(floats, [4.0, 1.0, 2.0, 5.0, 1.0, 2.0]);
+    }
+}
@@ -0,0 +1,162 @@
+//! Finds the files rooms reference, the way the game looks them up.
+//!
+//! Game files are authored on Windows, so names use backslashes and any case. The
+//! `*_candidates` functions only build paths, for asset systems with their own file access, the
+//! others search the file system ignoring case.
+
+use std::path::{Path, PathBuf};
+
+use crate::EntityModel;
+
+/// Directory screen images are in, relative to the game directory.
+pub const SCREENS_DIR: &str = "GFX/screens";
+
+/// Extensions of prop models, tried after the name as stored.
+pub const MODEL_EXTENSIONS: [&str; 2] = ["x", "b3d"];
+
+/// Turns the backslashes of a name stored in a room into slashes.
+pub fn normalize(name: &str) -> PathBuf {
+    PathBuf::from(name.replace('\\', "/"))
+}
+
+/// Whether two names stored in rooms point at the same file, ignoring ASCII case and slash
+/// direction like the game's lookups on Windows.
+pub fn same_path(a: &str, b: &str) -> bool {
+    let fold = |c: u8| match c {
+        b'\\' => b'/',
+        c => c.to_ascii_lowercase(),
+    };
+    a.len() == b.len() && a.bytes().zip(b.bytes()).all(|(a, b)| fold(a) == fold(b))
+}
+
+/// Paths the image of an [`EntityScreen`](crate::EntityScreen) may be at, in the order of
+/// `roots`, which are usually [`SCREENS_DIR`] in the game and mod directories.
+pub fn screen_candidates<P: AsRef<Path>>(name: &str, roots: &[P]) -> Vec<PathBuf> {
+    let name = normalize(name);
+    roots.iter().map(|root| root.as_ref().join(&name)).collect()
+}
+
+/// Finds the image of a screen in the first root that has it, ignoring case.
+pub fn screen_path<P: AsRef<Path>>(name: &str, roots: &[P]) -> Option<PathBuf> {
+    screen_candidates(name, roots)
+        .iter()
+        .find_map(|candidate| find_path(candidate).filter(|path| path.is_file()))
+}
+
+/// Directory prop models are in by default, `props` next to the room.
+pub fn props_dir(room_path: &Path) -> PathBuf {
+    room_path.parent().unwrap_or(Path::new("")).join("props")
+}
+
+/// Paths the model of an [`EntityModel`] may be at, in the order of `roots`. Each root is tried
+/// with the name as stored, then with the other [`MODEL_EXTENSIONS`].
+pub fn model_candidates<P: AsRef<Path>>(name: &str, roots: &[P]) -> Vec<PathBuf> {
+    let name = normalize(name);
+    let mut candidates = vec![];
+    for root in roots {
+        let path = root.as_ref().join(&name);
+        candidates.push(path.clone());
+        for extension in MODEL_EXTENSIONS {
+            let alternate = path.with_extension(extension);
+            if !candidates.contains(&alternate) {
+                candidates.push(alternate);
+            }
+        }
+    }
+    candidates
+}
+
+/// Finds the model of a prop in the first root that has it, ignoring case.
+///
+/// Without `roots` the model is looked up in the [`props_dir`] of the room at `room_path`.
+pub fn model_path<P: AsRef<Path>>(
+    room_path: &Path,
+    model: &EntityModel,
+    roots: &[P],
+) -> Option<PathBuf> {
+    let name = String::from(&model.name);
+    let candidates = if roots.is_empty() {
+        model_candidates(&name, &[props_dir(room_path)])
+    } else {
+        model_candidates(&name, roots)
+    };
+    candidates
+        .iter()
+        .find_map(|candidate| find_path(candidate).filter(|path| path.is_file()))
+}
+
+/// Finds an existing file or directory ignoring case.
+pub fn find_path(path: &Path) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        let candidate = resolved.join(component);
+        if candidate.exists() {
+            resolved = candidate;
+            continue;
+        }
+        let name = component.as_os_str().to_str()?;
+        let dir = if resolved.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            &resolved
+        };
+        let entry = std::fs::read_dir(dir).ok()?.flatten().find(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|other| other.eq_ignore_ascii_case(name))
+        })?;
+        resolved.push(entry.file_name());
+    }
+    Some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_path_folds_ascii_case_and_slashes() {
+        assert!(same_path("GFX\\Map\\Wall.JPG", "gfx/map/wall.jpg"));
+        assert!(!same_path("gfx/map/wall.jpg", "gfx/map/wall.jpeg"));
+        assert!(!same_path("gfx/map/wall.jpg", "gfx/map/wall.jp"));
+        // Only ASCII is folded, like the game's lookups
+        assert!(!same_path("Ä.jpg", "ä.jpg"));
+    }
+
+    #[test]
+    fn model_candidates_try_every_extension_per_root() {
+        let candidates = model_candidates("Props\\chair.x", &["a", "b"]);
+
+        assert_eq!(
+            candidates,
+            [
+                "a/Props/chair.x",
+                "a/Props/chair.b3d",
+                "b/Props/chair.x",
+                "b/Props/chair.b3d"
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(
+            props_dir(Path::new("GFX/map/room.rmesh")),
+            Path::new("GFX/map/props")
+        );
+    }
+
+    #[test]
+    fn find_path_ignores_case() {
+        let dir = std::env::temp_dir().join(format!("rmesh-resolve-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("GFX/Screens")).unwrap();
+        std::fs::write(dir.join("GFX/Screens/Info.JPG"), []).unwrap();
+
+        let found = screen_path("info.jpg", &[dir.join("gfx/screens")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, Some(dir.join("GFX/Screens/Info.JPG")));
+    }
+}
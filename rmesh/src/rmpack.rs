@@ -0,0 +1,282 @@
+//! `.rmpack` archives bundling a room with the files it references.
+//!
+//! The archive starts with a table of contents listing every file's path, offset and size,
+//! followed by the file contents back to back. The first entry is always the room itself.
+
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::{Component, Path, PathBuf};
+
+use binrw::{binrw, BinReaderExt, BinWriterExt};
+
+use crate::{read_rmesh, Dependency, FixedLengthString, Header, RMeshError};
+
+const VERSION: u32 = 1;
+
+/// Size of the magic, version and entry count before the entries.
+const TOC_PREFIX_SIZE: u64 = 12;
+
+/// Smallest table of contents entry, one with an empty path.
+const MIN_ENTRY_SIZE: u64 = 20;
+
+#[binrw]
+#[brw(little, magic = b"RMPK")]
+struct Toc {
+    version: u32,
+
+    #[bw(try_calc(u32::try_from(entries.len())))]
+    #[br(temp)]
+    entry_count: u32,
+
+    #[br(count = entry_count)]
+    entries: Vec<TocEntry>,
+}
+
+#[binrw]
+struct TocEntry {
+    path: FixedLengthString,
+    offset: u64,
+    size: u64,
+}
+
+/// A file stored in an [`RmPack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackFile {
+    /// Path relative to the archive root, using `/` separators.
+    pub path: String,
+    pub data: Vec<u8>,
+}
+
+/// A room bundled with its textures, lightmaps and prop models.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RmPack {
+    /// The room comes first, followed by its dependencies.
+    pub files: Vec<PackFile>,
+}
+
+impl RmPack {
+    /// Collects a room and every file it references from disk.
+    ///
+    /// Textures are looked up relative to the room and models in its `props` directory,
+    /// matching the paths the loader uses. Sounds and screens live in the game files and
+    /// are not packed.
+    pub fn from_room(rmesh_path: impl AsRef<Path>) -> Result<Self, RMeshError> {
+        let rmesh_path = rmesh_path.as_ref();
+        let root = rmesh_path.parent().unwrap_or(Path::new(""));
+        let bytes = fs::read(rmesh_path)?;
+        let header = read_rmesh(&bytes)?;
+
+        let name = rmesh_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "room.rmesh".into());
+        let mut pack = Self {
+            files: vec![PackFile {
+                path: name,
+                data: bytes,
+            }],
+        };
+
+        for dependency in header.dependencies() {
+            let path = match dependency {
+                Dependency::Texture(path) | Dependency::Lightmap(path) => path,
+                Dependency::Model(name) => format!("props/{name}"),
+                Dependency::Sound(_) | Dependency::Screen(_) => continue,
+            };
+            let path = path.replace('\\', "/");
+            let data = fs::read(root.join(&path))?;
+            pack.files.push(PackFile { path, data });
+        }
+
+        Ok(pack)
+    }
+
+    /// Parses the room stored in the archive.
+    pub fn room(&self) -> Result<Header, RMeshError> {
+        let room = self.files.first().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "archive contains no room")
+        })?;
+        read_rmesh(&room.data)
+    }
+
+    /// Looks up a file by its archive path.
+    pub fn file(&self, path: &str) -> Option<&PackFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Writes every file below `dir`, recreating the original folder layout.
+    pub fn unpack(&self, dir: impl AsRef<Path>) -> Result<(), RMeshError> {
+        let dir = dir.as_ref();
+        for file in &self.files {
+            let path = dir.join(safe_path(&file.path)?);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, &file.data)?;
+        }
+        Ok(())
+    }
+}
+
+/// Refuses paths that would escape the unpack directory.
+fn safe_path(path: &str) -> io::Result<PathBuf> {
+    let path = Path::new(path);
+    if path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(path.to_path_buf())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsafe path in archive: {}", path.display()),
+        ))
+    }
+}
+
+/// Reads a .rmpack file.
+pub fn read_rmpack(bytes: &[u8]) -> Result<RmPack, RMeshError> {
+    let mut cursor = Cursor::new(bytes);
+    let (magic, version, entry_count): ([u8; 4], u32, u32) = cursor.read_le()?;
+    if magic == *b"RMPK" && version != VERSION {
+        return Err(RMeshError::UnsupportedRmPackVersion(version));
+    }
+    // Entries can't take more room than the archive has, a corrupt count shouldn't read on
+    if u64::from(entry_count) * MIN_ENTRY_SIZE
+        > (bytes.len() as u64).saturating_sub(TOC_PREFIX_SIZE)
+    {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "too many archive entries").into());
+    }
+
+    cursor.set_position(0);
+    let toc: Toc = cursor.read_le()?;
+
+    let files = toc
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let data = usize::try_from(entry.offset)
+                .ok()
+                .zip(usize::try_from(entry.size).ok())
+                .and_then(|(offset, size)| bytes.get(offset..offset.checked_add(size)?))
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "file data out of range")
+                })?;
+            Ok(PackFile {
+                path: String::from(entry.path),
+                data: data.to_vec(),
+            })
+        })
+        .collect::<Result<_, RMeshError>>()?;
+
+    Ok(RmPack { files })
+}
+
+/// Writes a .rmpack file.
+pub fn write_rmpack(pack: &RmPack) -> Result<Vec<u8>, RMeshError> {
+    let mut toc = Toc {
+        version: VERSION,
+        entries: pack
+            .files
+            .iter()
+            .map(|file| TocEntry {
+                path: file.path.as_str().into(),
+                offset: 0,
+                size: file.data.len() as u64,
+            })
+            .collect(),
+    };
+
+    // Measure the table of contents to know where the data starts
+    let mut bytes = Vec::new();
+    Cursor::new(&mut bytes).write_le(&toc)?;
+    let mut offset = bytes.len() as u64;
+    for entry in &mut toc.entries {
+        entry.offset = offset;
+        offset += entry.size;
+    }
+
+    bytes.clear();
+    Cursor::new(&mut bytes).write_le(&toc)?;
+    for file in &pack.files {
+        bytes.extend_from_slice(&file.data);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack() -> RmPack {
+        RmPack {
+            files: vec![
+                PackFile {
+                    path: "room.rmesh".into(),
+                    data: crate::write_rmesh(&Header::default()).unwrap(),
+                },
+                PackFile {
+                    path: "GFX/map/wall.jpg".into(),
+                    data: vec![0xff, 0xd8, 0xff],
+                },
+                PackFile {
+                    path: "props/empty.x".into(),
+                    data: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes = write_rmpack(&pack()).unwrap();
+        let read = read_rmpack(&bytes).unwrap();
+
+        assert_eq!(read, pack());
+        assert_eq!(
+            read.file("GFX/map/wall.jpg").unwrap().data,
+            [0xff, 0xd8, 0xff]
+        );
+        assert!(read.room().unwrap().meshes.is_empty());
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let mut bytes = write_rmpack(&pack()).unwrap();
+        bytes[4..8].copy_from_slice(&2u32.to_le_bytes());
+
+        assert!(matches!(
+            read_rmpack(&bytes),
+            Err(RMeshError::UnsupportedRmPackVersion(2))
+        ));
+    }
+
+    #[test]
+    fn corrupt_table_of_contents_fails() {
+        let bytes = write_rmpack(&pack()).unwrap();
+
+        let mut count = bytes.clone();
+        count[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(read_rmpack(&count), Err(RMeshError::Io(_))));
+
+        // Data of the last file cut off
+        let mut truncated = write_rmpack(&RmPack {
+            files: pack().files[..2].to_vec(),
+        })
+        .unwrap();
+        truncated.pop();
+        assert!(read_rmpack(&truncated).is_err());
+
+        assert!(read_rmpack(b"RMP").is_err());
+    }
+
+    #[test]
+    fn unpack_refuses_escaping_paths() {
+        assert!(safe_path("props/../chair.x").is_err());
+        assert!(safe_path("/etc/passwd").is_err());
+        assert_eq!(
+            safe_path("./GFX/map/a.jpg").unwrap(),
+            Path::new("./GFX/map/a.jpg")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! The `rooms.ini` SCP:CB pairs with its rooms: which mesh each room uses and how the map
+//! generator places it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::resolve::{find_path, normalize, same_path};
+use crate::RMeshError;
+
+/// Section listing the ambience sounds, which isn't a room.
+const AMBIENCE_SECTION: &str = "room ambience";
+
+/// How many neighbours a room connects to, which decides where the map generator puts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoomShape {
+    /// Dead end, `1`.
+    Room1,
+    /// Hallway, `2`.
+    Room2,
+    /// Corner, `2C`.
+    Room2C,
+    /// T junction, `3`.
+    Room3,
+    /// Crossing, `4`.
+    Room4,
+}
+
+impl FromStr for RoomShape {
+    type Err = RMeshError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "1" => Ok(Self::Room1),
+            "2" => Ok(Self::Room2),
+            "2C" => Ok(Self::Room2C),
+            "3" => Ok(Self::Room3),
+            "4" => Ok(Self::Room4),
+            _ => Err(RMeshError::InvalidIni(format!("unknown room shape {s:?}"))),
+        }
+    }
+}
+
+/// A room of `rooms.ini`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomTemplate {
+    /// Section name, which the game refers to the room by.
+    pub name: String,
+    /// Path of the rmesh file relative to the game directory, as written.
+    pub mesh_path: Option<String>,
+    pub shape: Option<RoomShape>,
+    /// Weight of the room when the generator fills a spot of its shape, 0 for rooms it only
+    /// places on purpose.
+    pub commonness: u32,
+    /// Zones the room can appear in, from 1 for light containment.
+    pub zones: Vec<u32>,
+    pub disable_decals: bool,
+    /// Every key of the section in lower case, including the ones above.
+    pub properties: BTreeMap<String, String>,
+}
+
+impl RoomTemplate {
+    fn new(name: String, properties: BTreeMap<String, String>) -> Result<Self, RMeshError> {
+        let number = |key: &str| -> Result<Option<u32>, RMeshError> {
+            properties
+                .get(key)
+                .map(|value| {
+                    value.parse().map_err(|_| {
+                        RMeshError::InvalidIni(format!("{name}: {key} is not a number: {value:?}"))
+                    })
+                })
+                .transpose()
+        };
+        let mut zones = vec![];
+        for zone in 1.. {
+            match number(&format!("zone{zone}"))? {
+                Some(value) => zones.push(value),
+                None => break,
+            }
+        }
+        Ok(Self {
+            mesh_path: properties.get("mesh path").cloned(),
+            shape: properties.get("shape").map(|s| s.parse()).transpose()?,
+            commonness: number("commonness")?.unwrap_or(0),
+            zones,
+            disable_decals: properties
+                .get("disabledecals")
+                .is_some_and(|value| value.eq_ignore_ascii_case("true")),
+            properties,
+            name,
+        })
+    }
+
+    /// Finds the rmesh file of the room under the game directory, ignoring case.
+    pub fn find_mesh(&self, game_dir: &Path) -> Option<PathBuf> {
+        let path = game_dir.join(normalize(self.mesh_path.as_deref()?));
+        find_path(&path).filter(|path| path.is_file())
+    }
+}
+
+/// A parsed `rooms.ini`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoomsIni {
+    /// Ambience sounds by index, see [`EntitySoundEmitter`](crate::EntitySoundEmitter).
+    pub ambience: Vec<String>,
+    pub rooms: Vec<RoomTemplate>,
+}
+
+impl RoomsIni {
+    pub fn parse(text: &str) -> Result<Self, RMeshError> {
+        let mut sections: Vec<(String, BTreeMap<String, String>)> = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push((name.trim().to_string(), BTreeMap::new()));
+                continue;
+            }
+            let (Some((key, value)), Some((_, properties))) =
+                (line.split_once('='), sections.last_mut())
+            else {
+                return Err(RMeshError::InvalidIni(format!(
+                    "line {} is neither a section nor a key: {line:?}",
+                    number + 1
+                )));
+            };
+            properties.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+
+        let mut ini = Self::default();
+        for (name, properties) in sections {
+            if name.eq_ignore_ascii_case(AMBIENCE_SECTION) {
+                // ambience1, ambience2, ... in any order
+                let mut ambience: Vec<_> = properties
+                    .into_iter()
+                    .filter_map(|(key, value)| {
+                        let index: u32 = key.strip_prefix("ambience")?.parse().ok()?;
+                        Some((index, value))
+                    })
+                    .collect();
+                ambience.sort();
+                ini.ambience = ambience.into_iter().map(|(_, value)| value).collect();
+                continue;
+            }
+            ini.rooms.push(RoomTemplate::new(name, properties)?);
+        }
+        Ok(ini)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, RMeshError> {
+        // Written by Windows tools, not always valid UTF-8
+        let bytes = std::fs::read(path)?;
+        Self::parse(&String::from_utf8_lossy(&bytes))
+    }
+
+    /// Looks up a room by name, ignoring case like the game.
+    pub fn room(&self, name: &str) -> Option<&RoomTemplate> {
+        self.rooms
+            .iter()
+            .find(|room| room.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds the rooms using an rmesh file, given relative to the game directory.
+    pub fn rooms_with_mesh(&self, mesh_path: &Path) -> impl Iterator<Item = &RoomTemplate> {
+        let mesh_path = mesh_path.to_string_lossy().into_owned();
+        self.rooms.iter().filter(move |room| {
+            room.mesh_path
+                .as_deref()
+                .is_some_and(|path| same_path(path, &mesh_path))
+        })
+    }
+}
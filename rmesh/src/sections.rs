@@ -0,0 +1,226 @@
+//! Locating sections of a file without decoding them.
+
+use std::io::Cursor;
+use std::ops::Range;
+
+use binrw::{BinReaderExt, BinResult};
+
+use crate::variant::{has_mesh_data, has_trigger_boxes, TRIANGLE_SIZE, VERTEX_SIZE};
+use crate::{compression, EntityData, FixedLengthString, RMeshError};
+
+/// Byte ranges of the parts of a .rmesh file, counts left out, see [`index`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionIndex {
+    /// The header tag, length prefix included.
+    pub tag: Range<usize>,
+    pub meshes: Vec<Range<usize>>,
+    pub colliders: Vec<Range<usize>>,
+    /// Empty for files whose tag has no trigger box section.
+    pub trigger_boxes: Vec<Range<usize>>,
+    pub entities: Vec<Range<usize>>,
+    /// Bytes after the entities, see [`Header::extra`](crate::Header::extra).
+    pub extra: Range<usize>,
+}
+
+/// Where a mesh is in the file.
+pub(crate) struct MeshRange {
+    /// The whole mesh, textures included.
+    pub mesh: Range<usize>,
+    /// The length-prefixed path of each texture slot that has one.
+    pub textures: [Option<Range<usize>>; 2],
+    /// The vertex data, after the vertex count.
+    #[cfg_attr(not(feature = "bytemuck"), allow(dead_code))]
+    pub vertices: Range<usize>,
+}
+
+/// Skips over a count followed by that many elements, returning the range of the elements.
+pub(crate) fn skip_counted(
+    cursor: &mut Cursor<&[u8]>,
+    element_size: u64,
+) -> BinResult<Range<usize>> {
+    let count: u32 = cursor.read_le()?;
+    let start = cursor.position();
+    let end = start + count as u64 * element_size;
+    if end > cursor.get_ref().len() as u64 {
+        return Err(binrw::Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    cursor.set_position(end);
+    Ok(start as usize..end as usize)
+}
+
+/// Ranges of the meshes of a file tagged `tag`, the cursor starting at the mesh count and left
+/// right after the last one.
+pub(crate) fn mesh_ranges(
+    cursor: &mut Cursor<&[u8]>,
+    tag: &FixedLengthString,
+) -> BinResult<Vec<MeshRange>> {
+    let mesh_count: u32 = cursor.read_le()?;
+    let mut ranges = Vec::with_capacity(mesh_count.min(1024) as usize);
+    for _ in 0..mesh_count {
+        let start = cursor.position() as usize;
+        let mut textures = [None, None];
+        for texture in &mut textures {
+            let blend_type: u8 = cursor.read_le()?;
+            if blend_type != 0 {
+                let path = skip_counted(cursor, 1)?;
+                *texture = Some(path.start - 4..path.end);
+            }
+        }
+        let vertices = skip_counted(cursor, VERTEX_SIZE)?;
+        skip_counted(cursor, TRIANGLE_SIZE)?;
+        if has_mesh_data(tag) {
+            skip_counted(cursor, 1)?;
+        }
+        ranges.push(MeshRange {
+            mesh: start..cursor.position() as usize,
+            textures,
+            vertices,
+        });
+    }
+    Ok(ranges)
+}
+
+/// Maps each mesh, collider, trigger box and entity of a .rmesh file to its byte range.
+///
+/// Only counts, texture paths and entities are decoded. Compressed files have no ranges to
+/// index, decompress them first.
+pub fn index(bytes: &[u8]) -> Result<SectionIndex, RMeshError> {
+    if compression::is_compressed(bytes) {
+        return Err(RMeshError::Compressed);
+    }
+    let mut cursor = Cursor::new(bytes);
+    let tag: FixedLengthString = cursor.read_le()?;
+    let mut index = SectionIndex {
+        tag: 0..cursor.position() as usize,
+        meshes: mesh_ranges(&mut cursor, &tag)?
+            .into_iter()
+            .map(|range| range.mesh)
+            .collect(),
+        ..Default::default()
+    };
+
+    let collider_count: u32 = cursor.read_le()?;
+    for _ in 0..collider_count {
+        let start = cursor.position() as usize;
+        skip_counted(&mut cursor, TRIANGLE_SIZE)?;
+        skip_counted(&mut cursor, TRIANGLE_SIZE)?;
+        index.colliders.push(start..cursor.position() as usize);
+    }
+
+    if has_trigger_boxes(&tag) {
+        let trigger_box_count: u32 = cursor.read_le()?;
+        for _ in 0..trigger_box_count {
+            let start = cursor.position() as usize;
+            let mesh_count: u32 = cursor.read_le()?;
+            for _ in 0..mesh_count {
+                skip_counted(&mut cursor, TRIANGLE_SIZE)?;
+                skip_counted(&mut cursor, TRIANGLE_SIZE)?;
+            }
+            skip_counted(&mut cursor, 1)?;
+            index.trigger_boxes.push(start..cursor.position() as usize);
+        }
+    }
+
+    let entity_count: u32 = cursor.read_le()?;
+    for _ in 0..entity_count {
+        let start = cursor.position() as usize;
+        let _entity: EntityData = cursor.read_le()?;
+        index.entities.push(start..cursor.position() as usize);
+    }
+
+    index.extra = cursor.position() as usize..bytes.len();
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        write_rmesh, ComplexMesh, EntityType, EntityWaypoint, Header, MeshExtension, SimpleMesh,
+        Texture, TextureBlendType, TriggerBox, Vertex,
+    };
+
+    #[test]
+    fn ranges_cover_the_file_in_order() {
+        let mesh = ComplexMesh {
+            textures: [
+                Texture {
+                    blend_type: TextureBlendType::Visible,
+                    path: Some("abc.jpg".into()),
+                },
+                Texture::default(),
+            ],
+            vertices: vec![Vertex::default(); 3],
+            triangles: vec![[0, 1, 2]],
+            extension: Some(MeshExtension { data: vec![9; 6] }),
+        };
+        let simple = SimpleMesh {
+            vertex_count: 2,
+            vertices: vec![[0.0; 3]; 2],
+            triangle_count: 0,
+            triangles: vec![],
+        };
+        let header = Header {
+            meshes: vec![mesh.clone(), mesh],
+            colliders: vec![simple.clone()],
+            trigger_boxes: vec![TriggerBox {
+                meshes: vec![simple],
+                name: "tb".into(),
+            }],
+            entities: vec![EntityType::WayPoint(EntityWaypoint { position: [0.0; 3] }).into()],
+            extra: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let bytes = write_rmesh(&header).unwrap();
+
+        let index = index(&bytes).unwrap();
+
+        let tag_len = 4 + "RoomMesh.HasTriggerBox.HasMeshData".len();
+        // Textures, vertices, triangles and extension, each with its count or length
+        let mesh_len = (1 + 4 + 7) + 1 + (4 + 3 * 31) + (4 + 12) + (4 + 6);
+        let simple_len = (4 + 2 * 12) + 4;
+        let trigger_box_len = 4 + simple_len + (4 + 2);
+        let entity_len = 4 + "waypoint".len() + 12;
+
+        let mesh_start = tag_len + 4;
+        let collider_start = mesh_start + 2 * mesh_len + 4;
+        let trigger_box_start = collider_start + simple_len + 4;
+        let entity_start = trigger_box_start + trigger_box_len + 4;
+        assert_eq!(index.tag, 0..tag_len);
+        assert_eq!(
+            index.meshes,
+            [
+                mesh_start..mesh_start + mesh_len,
+                mesh_start + mesh_len..mesh_start + 2 * mesh_len,
+            ]
+        );
+        assert_eq!(index.colliders.len(), 1);
+        assert_eq!(
+            index.colliders[0],
+            collider_start..collider_start + simple_len
+        );
+        assert_eq!(index.trigger_boxes.len(), 1);
+        assert_eq!(
+            index.trigger_boxes[0],
+            trigger_box_start..trigger_box_start + trigger_box_len
+        );
+        assert_eq!(index.entities.len(), 1);
+        assert_eq!(index.entities[0], entity_start..entity_start + entity_len);
+        assert_eq!(index.extra, bytes.len() - 3..bytes.len());
+        assert_eq!(&bytes[index.extra.clone()], [1, 2, 3]);
+    }
+
+    #[test]
+    fn truncated_counts_fail() {
+        let mut bytes = write_rmesh(&Header::default()).unwrap();
+        // A mesh count with no meshes after it
+        let mesh_count = 4 + "RoomMesh".len();
+        bytes[mesh_count] = 1;
+
+        assert!(index(&bytes).is_err());
+        assert!(matches!(
+            index(&[0x1f, 0x8b, 0]),
+            Err(RMeshError::Compressed)
+        ));
+    }
+}
@@ -0,0 +1,465 @@
+//! Bounding volume hierarchy over room triangles.
+
+use crate::math;
+use crate::{Bounds, ComplexMesh, Header, SimpleMesh};
+
+const LEAF_SIZE: usize = 4;
+
+/// Which list of a [`Header`] a triangle belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeshKind {
+    /// `Header::meshes`, including invisible collision surfaces.
+    Visual,
+    /// `Header::colliders`.
+    Collider,
+}
+
+/// A triangle stored in a [`Bvh`], remembering where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhTriangle {
+    pub kind: MeshKind,
+    /// Caller defined index of the source mesh.
+    pub mesh_index: usize,
+    /// Index of the triangle within the source mesh.
+    pub triangle_index: usize,
+    pub vertices: [[f32; 3]; 3],
+}
+
+impl BvhTriangle {
+    /// Collects the valid triangles of a visual mesh.
+    pub fn from_complex_mesh(
+        mesh_index: usize,
+        mesh: &ComplexMesh,
+    ) -> impl Iterator<Item = BvhTriangle> + '_ {
+        mesh.triangles_resolved()
+            .enumerate()
+            .filter_map(move |(triangle_index, corners)| {
+                let corners = corners.ok()?;
+                Some(BvhTriangle {
+                    kind: MeshKind::Visual,
+                    mesh_index,
+                    triangle_index,
+                    vertices: corners.map(|v| v.position),
+                })
+            })
+    }
+
+    /// Collects the valid triangles of a collision mesh.
+    pub fn from_simple_mesh(
+        mesh_index: usize,
+        mesh: &SimpleMesh,
+    ) -> impl Iterator<Item = BvhTriangle> + '_ {
+        mesh.triangles_resolved()
+            .enumerate()
+            .filter_map(move |(triangle_index, vertices)| {
+                Some(BvhTriangle {
+                    kind: MeshKind::Collider,
+                    mesh_index,
+                    triangle_index,
+                    vertices: vertices.ok()?,
+                })
+            })
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::from_points(self.vertices)
+    }
+
+    pub fn centroid(&self) -> [f32; 3] {
+        let [a, b, c] = self.vertices;
+        math::scale(math::add(math::add(a, b), c), 1.0 / 3.0)
+    }
+
+    /// Unit normal following the triangle winding.
+    pub fn normal(&self) -> [f32; 3] {
+        let [a, b, c] = self.vertices;
+        math::normalize(math::cross(math::sub(b, a), math::sub(c, a)))
+    }
+
+    /// Distance along the ray to the triangle, hitting both sides.
+    pub fn intersect_ray(&self, origin: [f32; 3], direction: [f32; 3]) -> Option<f32> {
+        math::ray_triangle(origin, direction, self.vertices)
+    }
+
+    /// Closest point on the triangle to `point`.
+    pub fn closest_point(&self, point: [f32; 3]) -> [f32; 3] {
+        let [a, b, c] = self.vertices;
+        math::closest_point_on_triangle(point, a, b, c)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum BvhNodeKind {
+    Leaf { start: usize, count: usize },
+    Inner { left: usize, right: usize },
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    bounds: Bounds,
+    kind: BvhNodeKind,
+}
+
+/// Result of [`Bvh::nearest`].
+#[derive(Debug, Clone, Copy)]
+pub struct NearestTriangle<'a> {
+    pub triangle: &'a BvhTriangle,
+    /// Closest point on the triangle.
+    pub point: [f32; 3],
+    pub distance: f32,
+}
+
+/// Result of a ray cast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub kind: MeshKind,
+    pub mesh_index: usize,
+    /// Index of the triangle within its mesh.
+    pub triangle: usize,
+    /// Distance along the normalized ray direction.
+    pub distance: f32,
+    pub point: [f32; 3],
+    /// Unit surface normal, facing back towards the ray origin.
+    pub normal: [f32; 3],
+}
+
+/// Bounding volume hierarchy for fast spatial queries against triangles.
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<BvhTriangle>,
+}
+
+impl Bvh {
+    /// Builds the hierarchy by splitting at the median along the longest axis.
+    pub fn new(triangles: impl IntoIterator<Item = BvhTriangle>) -> Self {
+        let mut bvh = Self {
+            nodes: vec![],
+            triangles: triangles.into_iter().collect(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    /// Builds a hierarchy over a single visual mesh.
+    pub fn from_complex_mesh(mesh: &ComplexMesh) -> Self {
+        Self::new(BvhTriangle::from_complex_mesh(0, mesh))
+    }
+
+    /// Builds a hierarchy over a single collision mesh.
+    pub fn from_simple_mesh(mesh: &SimpleMesh) -> Self {
+        Self::new(BvhTriangle::from_simple_mesh(0, mesh))
+    }
+
+    fn build(&mut self, start: usize, count: usize) -> usize {
+        let slice = &mut self.triangles[start..start + count];
+        let bounds = slice
+            .iter()
+            .fold(Bounds::EMPTY, |b, t| b.union(&t.bounds()));
+
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds,
+            kind: BvhNodeKind::Leaf { start, count },
+        });
+
+        if count > LEAF_SIZE {
+            let centroids = Bounds::from_points(slice.iter().map(|t| t.centroid()));
+            let size = centroids.size();
+            let axis = (0..3)
+                .max_by(|a, b| size[*a].total_cmp(&size[*b]))
+                .unwrap_or(0);
+
+            let mid = count / 2;
+            slice.select_nth_unstable_by(mid, |a, b| {
+                a.centroid()[axis].total_cmp(&b.centroid()[axis])
+            });
+
+            let left = self.build(start, mid);
+            let right = self.build(start + mid, count - mid);
+            self.nodes[index].kind = BvhNodeKind::Inner { left, right };
+        }
+
+        index
+    }
+
+    /// All triangles stored in the hierarchy.
+    pub fn triangles(&self) -> &[BvhTriangle] {
+        &self.triangles
+    }
+
+    /// Bounds of all triangles.
+    pub fn bounds(&self) -> Bounds {
+        self.nodes.first().map_or(Bounds::EMPTY, |n| n.bounds)
+    }
+
+    /// Triangles whose bounding box overlaps `bounds`.
+    pub fn query_aabb(&self, bounds: &Bounds) -> Vec<&BvhTriangle> {
+        let mut result = vec![];
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.intersects(bounds) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => result.extend(
+                    self.triangles[start..start + count]
+                        .iter()
+                        .filter(|t| t.bounds().intersects(bounds)),
+                ),
+                BvhNodeKind::Inner { left, right } => stack.extend([left, right]),
+            }
+        }
+
+        result
+    }
+
+    /// Closest triangle hit by the ray within `max_distance`.
+    pub fn raycast(
+        &self,
+        origin: [f32; 3],
+        direction: [f32; 3],
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        let direction = math::normalize(direction);
+        let inverse = direction.map(|d| 1.0 / d);
+        let mut best: Option<(&BvhTriangle, f32)> = None;
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let limit = best.map_or(max_distance, |(_, t)| t);
+            if !ray_hits_bounds(&node.bounds, origin, inverse, limit) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => {
+                    for triangle in &self.triangles[start..start + count] {
+                        if let Some(t) = triangle.intersect_ray(origin, direction) {
+                            if t <= best.map_or(max_distance, |(_, t)| t) {
+                                best = Some((triangle, t));
+                            }
+                        }
+                    }
+                }
+                BvhNodeKind::Inner { left, right } => stack.extend([left, right]),
+            }
+        }
+
+        best.map(|(triangle, distance)| {
+            let mut normal = triangle.normal();
+            if math::dot(normal, direction) > 0.0 {
+                normal = math::scale(normal, -1.0);
+            }
+            RayHit {
+                kind: triangle.kind,
+                mesh_index: triangle.mesh_index,
+                triangle: triangle.triangle_index,
+                distance,
+                point: math::add(origin, math::scale(direction, distance)),
+                normal,
+            }
+        })
+    }
+
+    /// Triangle closest to `point`, `None` if the hierarchy is empty.
+    pub fn nearest(&self, point: [f32; 3]) -> Option<NearestTriangle<'_>> {
+        let mut best: Option<NearestTriangle> = None;
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let best_distance = best.map_or(f32::INFINITY, |b| b.distance);
+            if distance_to_bounds(&node.bounds, point) > best_distance {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => {
+                    for triangle in &self.triangles[start..start + count] {
+                        let closest = triangle.closest_point(point);
+                        let distance = math::length(math::sub(closest, point));
+                        if best.is_none_or(|b| distance < b.distance) {
+                            best = Some(NearestTriangle {
+                                triangle,
+                                point: closest,
+                                distance,
+                            });
+                        }
+                    }
+                }
+                BvhNodeKind::Inner { left, right } => {
+                    // Visit the closer child first so the search prunes sooner
+                    let left_distance = distance_to_bounds(&self.nodes[left].bounds, point);
+                    let right_distance = distance_to_bounds(&self.nodes[right].bounds, point);
+                    if left_distance < right_distance {
+                        stack.extend([right, left]);
+                    } else {
+                        stack.extend([left, right]);
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+impl Header {
+    /// Builds a hierarchy over all visual meshes and colliders of the room.
+    pub fn bvh(&self) -> Bvh {
+        let meshes = self
+            .meshes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, mesh)| BvhTriangle::from_complex_mesh(i, mesh));
+        let colliders = self
+            .colliders
+            .iter()
+            .enumerate()
+            .flat_map(|(i, mesh)| BvhTriangle::from_simple_mesh(i, mesh));
+        Bvh::new(meshes.chain(colliders))
+    }
+
+    /// Closest surface hit by the ray, covering visual meshes, invisible collision and colliders.
+    ///
+    /// Builds a [`Bvh`] on every call, use [`Header::bvh`] when casting many rays.
+    pub fn raycast(&self, origin: [f32; 3], direction: [f32; 3]) -> Option<RayHit> {
+        self.bvh().raycast(origin, direction, f32::INFINITY)
+    }
+}
+
+/// Slab test of a ray against a box, given the inverse ray direction.
+fn ray_hits_bounds(bounds: &Bounds, origin: [f32; 3], inverse: [f32; 3], max: f32) -> bool {
+    let mut near = 0.0_f32;
+    let mut far = max;
+    for i in 0..3 {
+        let t0 = (bounds.min[i] - origin[i]) * inverse[i];
+        let t1 = (bounds.max[i] - origin[i]) * inverse[i];
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+    }
+    near <= far
+}
+
+fn distance_to_bounds(bounds: &Bounds, point: [f32; 3]) -> f32 {
+    let delta = [0, 1, 2].map(|i| {
+        (bounds.min[i] - point[i])
+            .max(point[i] - bounds.max[i])
+            .max(0.0)
+    });
+    math::length(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steps of a staircase, two triangles each, rising by 1 every 2 units along X.
+    fn stairs(steps: u32) -> SimpleMesh {
+        let vertices: Vec<_> = (0..steps)
+            .flat_map(|i| {
+                let (x, y) = (i as f32 * 2.0, i as f32);
+                [
+                    [x, y, 0.0],
+                    [x + 2.0, y, 0.0],
+                    [x + 2.0, y, 2.0],
+                    [x, y, 2.0],
+                ]
+            })
+            .collect();
+        let triangles: Vec<_> = (0..steps)
+            .flat_map(|i| {
+                let v = i * 4;
+                [[v, v + 2, v + 1], [v, v + 3, v + 2]]
+            })
+            .collect();
+        SimpleMesh {
+            vertex_count: vertices.len() as u32,
+            vertices,
+            triangle_count: triangles.len() as u32,
+            triangles,
+        }
+    }
+
+    #[test]
+    fn raycast_hits_the_step_below() {
+        let bvh = Bvh::from_simple_mesh(&stairs(16));
+
+        let hit = bvh
+            .raycast([13.0, 50.0, 1.0], [0.0, -1.0, 0.0], 100.0)
+            .unwrap();
+
+        assert_eq!(hit.triangle / 2, 6);
+        assert_eq!(hit.kind, MeshKind::Collider);
+        assert!((hit.distance - 44.0).abs() < 1e-4);
+        assert!((hit.normal[1] - 1.0).abs() < 1e-4);
+        assert!(bvh
+            .raycast([13.0, 50.0, 1.0], [0.0, -1.0, 0.0], 40.0)
+            .is_none());
+        assert!(bvh
+            .raycast([13.0, 50.0, 1.0], [0.0, 1.0, 0.0], 100.0)
+            .is_none());
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let bvh = Bvh::from_simple_mesh(&stairs(25));
+        let triangles = bvh.triangles();
+
+        for point in [
+            [-5.0, 3.0, 1.0],
+            [17.3, 2.0, -4.0],
+            [49.0, 30.0, 9.0],
+            [20.0, 10.0, 1.0],
+        ] {
+            let brute = triangles
+                .iter()
+                .map(|t| math::length(math::sub(t.closest_point(point), point)))
+                .fold(f32::INFINITY, f32::min);
+            let nearest = bvh.nearest(point).unwrap();
+            assert!((nearest.distance - brute).abs() < 1e-4, "{point:?}");
+        }
+
+        let query = Bounds::new([9.0, -1.0, -1.0], [15.0, 20.0, 0.5]);
+        let mut found: Vec<_> = bvh
+            .query_aabb(&query)
+            .iter()
+            .map(|t| t.triangle_index)
+            .collect();
+        found.sort_unstable();
+        let mut expected: Vec<_> = triangles
+            .iter()
+            .filter(|t| t.bounds().intersects(&query))
+            .map(|t| t.triangle_index)
+            .collect();
+        expected.sort_unstable();
+        assert_eq!(found, expected);
+        assert!(!found.is_empty());
+    }
+
+    #[test]
+    fn empty_and_broken_meshes() {
+        let mut mesh = stairs(1);
+        mesh.triangles[0][1] = 40;
+        let bvh = Bvh::from_simple_mesh(&mesh);
+
+        assert_eq!(bvh.triangles().len(), 1);
+        assert!(Bvh::default().nearest([0.0; 3]).is_none());
+        assert!(Header::default()
+            .raycast([0.0; 3], [1.0, 0.0, 0.0])
+            .is_none());
+    }
+}
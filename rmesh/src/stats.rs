@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use crate::math;
+use crate::{Bounds, Dependency, ExtMesh, Header, SimpleMesh};
+
+/// Summary numbers describing a room.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomStats {
+    pub mesh_count: usize,
+    pub collider_count: usize,
+    pub trigger_box_count: usize,
+    /// Vertices over all visual meshes.
+    pub vertex_count: usize,
+    /// Triangles over all visual meshes.
+    pub triangle_count: usize,
+    /// Surface area over all visual meshes.
+    pub surface_area: f32,
+    /// Unique diffuse and lightmap texture paths.
+    pub textures: Vec<String>,
+    /// Number of entities per type name, e.g. `"light"`.
+    pub entity_counts: BTreeMap<&'static str, usize>,
+    /// Bounds of all visual meshes, `None` when the room has no vertices.
+    pub bounds: Option<Bounds>,
+}
+
+impl SimpleMesh {
+    /// Signed volume enclosed by the mesh, positive when the triangles face outwards.
+    ///
+    /// Only meaningful for closed meshes.
+    pub fn signed_volume(&self) -> f32 {
+        self.triangles_resolved()
+            .flatten()
+            .map(|[v0, v1, v2]| math::dot(v0, math::cross(v1, v2)) / 6.0)
+            .sum()
+    }
+}
+
+/// Total area of all triangles, skipping ones with invalid indices.
+pub(crate) fn surface_area(positions: &[[f32; 3]], triangles: &[[u32; 3]]) -> f32 {
+    triangles
+        .iter()
+        .filter_map(|triangle| {
+            let [v0, v1, v2] = triangle.map(|i| positions.get(i as usize).copied());
+            Some(math::triangle_area(v0?, v1?, v2?))
+        })
+        .sum()
+}
+
+impl Header {
+    /// Collects summary numbers about the room.
+    pub fn stats(&self) -> RoomStats {
+        let mut entity_counts = BTreeMap::new();
+        for entity_type in self.entities.iter().filter_map(|e| e.entity_type.as_ref()) {
+            *entity_counts.entry(entity_type.name()).or_default() += 1;
+        }
+
+        let textures = self
+            .dependencies()
+            .into_iter()
+            .filter_map(|dependency| match dependency {
+                Dependency::Texture(path) | Dependency::Lightmap(path) => Some(path),
+                _ => None,
+            })
+            .collect();
+
+        let bounds = self
+            .meshes
+            .iter()
+            .filter(|mesh| !mesh.vertices.is_empty())
+            .map(|mesh| mesh.bounding_box())
+            .reduce(|a, b| a.union(&b));
+
+        RoomStats {
+            mesh_count: self.meshes.len(),
+            collider_count: self.colliders.len(),
+            trigger_box_count: self.trigger_boxes.len(),
+            vertex_count: self.meshes.iter().map(|m| m.vertices.len()).sum(),
+            triangle_count: self.meshes.iter().map(|m| m.triangles.len()).sum(),
+            surface_area: self.meshes.iter().map(|m| m.surface_area()).sum(),
+            textures,
+            entity_counts,
+            bounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComplexMesh, EntityType, EntityWaypoint, Texture, TextureBlendType, Vertex};
+
+    /// Corner of a unit right-angled tetrahedron with outward facing triangles.
+    fn tetrahedron() -> SimpleMesh {
+        SimpleMesh {
+            vertex_count: 4,
+            vertices: vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            triangle_count: 4,
+            triangles: vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]],
+        }
+    }
+
+    #[test]
+    fn tetrahedron_volume_and_area() {
+        let mesh = tetrahedron();
+
+        assert!((mesh.signed_volume() - 1.0 / 6.0).abs() < 1e-6);
+        let area = 1.5 + 3.0f32.sqrt() / 2.0;
+        assert!((mesh.surface_area() - area).abs() < 1e-5);
+
+        let mut inside_out = mesh;
+        for triangle in &mut inside_out.triangles {
+            triangle.swap(1, 2);
+        }
+        assert!(inside_out.signed_volume() < 0.0);
+
+        // A broken triangle adds nothing instead of panicking
+        inside_out.triangles.push([0, 1, 4]);
+        assert!((inside_out.surface_area() - area).abs() < 1e-5);
+    }
+
+    #[test]
+    fn room_stats() {
+        let floor = ComplexMesh {
+            textures: [
+                Texture {
+                    blend_type: TextureBlendType::Lightmap,
+                    path: Some("room_lm1.png".into()),
+                },
+                Texture {
+                    blend_type: TextureBlendType::Visible,
+                    path: Some("floor.jpg".into()),
+                },
+            ],
+            vertices: [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, 3.0]]
+                .map(|position| Vertex {
+                    position,
+                    ..Default::default()
+                })
+                .to_vec(),
+            // The second triangle is broken and skipped
+            triangles: vec![[0, 1, 2], [0, 1, 7]],
+            extension: None,
+        };
+        let header = Header {
+            meshes: vec![floor, ComplexMesh::default()],
+            colliders: vec![tetrahedron()],
+            entities: vec![
+                EntityType::WayPoint(EntityWaypoint { position: [0.0; 3] }).into(),
+                EntityType::WayPoint(EntityWaypoint { position: [1.0; 3] }).into(),
+            ],
+            ..Default::default()
+        };
+
+        let stats = header.stats();
+
+        assert_eq!(stats.mesh_count, 2);
+        assert_eq!(stats.collider_count, 1);
+        assert_eq!(stats.vertex_count, 3);
+        assert_eq!(stats.triangle_count, 2);
+        assert_eq!(stats.surface_area, 3.0);
+        assert_eq!(stats.textures.len(), 2);
+        assert_eq!(stats.entity_counts["waypoint"], 2);
+        assert_eq!(stats.bounds, Some(Bounds::new([0.0; 3], [2.0, 0.0, 3.0])));
+    }
+}
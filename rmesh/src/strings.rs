@@ -1,14 +1,72 @@
+use std::cell::RefCell;
 use std::fmt;
+use std::io::{Read, Seek};
 
-use binrw::{BinRead, BinWrite};
+use binrw::{BinRead, BinResult, BinWrite};
 
-#[derive(BinRead, BinWrite, Clone, Eq, PartialEq, Default)]
+/// Most strings are short, a corrupt length shouldn't allocate gigabytes up front.
+const MAX_PREALLOC: usize = 4096;
+
+thread_local! {
+    /// Shared by the strings only parsed for their numbers, so they don't allocate.
+    static SCRATCH: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Reads a length-prefixed string with a single read into `values`.
+fn read_prefixed<R: Read + Seek>(
+    reader: &mut R,
+    endian: binrw::Endian,
+    values: &mut Vec<u8>,
+) -> BinResult<()> {
+    let len = <u32>::read_options(reader, endian, ())? as usize;
+    values.clear();
+    values.reserve(len.min(MAX_PREALLOC));
+    reader.by_ref().take(len as u64).read_to_end(values)?;
+    if values.len() != len {
+        return Err(binrw::Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(())
+}
+
+/// Reads a length-prefixed text into the scratch buffer and parses it.
+fn parse_prefixed<R: Read + Seek, T>(
+    reader: &mut R,
+    endian: binrw::Endian,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> BinResult<T> {
+    let pos = reader.stream_position()?;
+    SCRATCH.with_borrow_mut(|scratch| {
+        read_prefixed(reader, endian, scratch)?;
+        std::str::from_utf8(scratch)
+            .map_err(|error| error.to_string())
+            .and_then(parse)
+            .map_err(|message| binrw::Error::AssertFail { pos, message })
+    })
+}
+
+#[derive(BinWrite, Clone, Eq, PartialEq, Default)]
 pub struct FixedLengthString {
     pub len: u32,
-    #[br(count = len)]
     pub values: Vec<u8>,
 }
 
+impl BinRead for FixedLengthString {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let mut values = vec![];
+        read_prefixed(reader, endian, &mut values)?;
+        Ok(Self {
+            len: values.len() as u32,
+            values,
+        })
+    }
+}
+
 impl fmt::Debug for FixedLengthString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "FixedLengthString(\"")?;
@@ -55,27 +113,22 @@ pub struct ThreeTypeString(pub Vec<u8>);
 impl BinRead for ThreeTypeString {
     type Args<'a> = ();
 
-    fn read_options<R: std::io::Read + std::io::Seek>(
+    fn read_options<R: Read + Seek>(
         reader: &mut R,
         endian: binrw::Endian,
         _args: Self::Args<'_>,
-    ) -> binrw::BinResult<Self> {
-        let len = <u32>::read_options(reader, endian, ())?;
-
-        let mut values = vec![];
-
-        for _ in 0..len {
-            let val = <u8>::read_options(reader, endian, ())?;
-            values.push(val);
-        }
-
-        let string = String::from_utf8(values).unwrap();
-        let stuff: Vec<_> = string
-            .split(' ')
-            .map(|c| c.parse::<u8>().unwrap())
-            .collect();
-
-        Ok(Self(stuff))
+    ) -> BinResult<Self> {
+        parse_prefixed(reader, endian, |string| {
+            string
+                .split_whitespace()
+                .map(|value| {
+                    value
+                        .parse::<u8>()
+                        .map_err(|_| format!("Invalid color component {:?}", value))
+                })
+                .collect::<Result<_, _>>()
+                .map(Self)
+        })
     }
 }
 
@@ -116,3 +169,142 @@ impl From<[u8; 3]> for ThreeTypeString {
         Self(value.to_vec())
     }
 }
+
+/// Space separated pitch, yaw and roll in degrees.
+///
+/// Keeps the text it was read from, which gets written back unchanged as long as the angles still
+/// match it. Edited or constructed angles are written with [`f32::to_string`].
+#[derive(Clone, Default, Debug)]
+pub struct AnglesString(pub [f32; 3], Option<Box<str>>);
+
+impl AnglesString {
+    fn parse(string: &str) -> Result<[f32; 3], String> {
+        let mut angles = [0.0; 3];
+        for (angle, value) in angles.iter_mut().zip(string.split_whitespace()) {
+            *angle = value
+                .parse()
+                .map_err(|_| format!("Invalid angle {:?}", value))?;
+        }
+        Ok(angles)
+    }
+
+    /// The text the angles were read from, if they haven't changed since.
+    pub fn original_text(&self) -> Option<&str> {
+        let text = self.1.as_deref()?;
+        let parsed = Self::parse(text).ok()?;
+        (parsed.map(f32::to_bits) == self.0.map(f32::to_bits)).then_some(text)
+    }
+}
+
+impl PartialEq for AnglesString {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl BinRead for AnglesString {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        parse_prefixed(reader, endian, |string| {
+            Self::parse(string).map(|angles| Self(angles, Some(string.into())))
+        })
+    }
+}
+
+impl BinWrite for AnglesString {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        let string = match self.original_text() {
+            Some(text) => text.to_owned(),
+            None => self
+                .0
+                .iter()
+                .map(|angle| angle.to_string())
+                .collect::<Vec<String>>()
+                .join(" "),
+        };
+
+        FixedLengthString::from(string).write_options(writer, endian, ())
+    }
+}
+
+impl From<[f32; 3]> for AnglesString {
+    fn from(value: [f32; 3]) -> Self {
+        Self(value, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binrw::{BinReaderExt, BinWriterExt};
+    use std::io::Cursor;
+
+    fn prefixed(text: &str) -> Vec<u8> {
+        let mut bytes = (text.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(text.as_bytes());
+        bytes
+    }
+
+    fn read<T: for<'a> BinRead<Args<'a> = ()>>(bytes: &[u8]) -> BinResult<T> {
+        Cursor::new(bytes).read_le()
+    }
+
+    #[test]
+    fn colors_split_on_any_whitespace() {
+        let color: ThreeTypeString = read(&prefixed(" 255  128\t0 ")).unwrap();
+        assert_eq!(color.0, [255, 128, 0]);
+
+        let mut written = Cursor::new(vec![]);
+        written.write_le(&color).unwrap();
+        assert_eq!(written.into_inner(), prefixed("255 128 0"));
+
+        assert!(read::<ThreeTypeString>(&prefixed("255 256 0")).is_err());
+    }
+
+    #[test]
+    fn angles_tolerate_short_and_padded_strings() {
+        let angles: AnglesString = read(&prefixed("90  -45.5")).unwrap();
+        assert_eq!(angles.0, [90.0, -45.5, 0.0]);
+
+        assert!(read::<AnglesString>(&prefixed("90 north 0")).is_err());
+    }
+
+    #[test]
+    fn angles_write_back_their_original_text() {
+        let write = |angles: &AnglesString| {
+            let mut written = Cursor::new(vec![]);
+            written.write_le(angles).unwrap();
+            written.into_inner()
+        };
+        let mut angles: AnglesString = read(&prefixed("0.1 -90.000 45")).unwrap();
+        assert_eq!(write(&angles), prefixed("0.1 -90.000 45"));
+
+        angles.0[1] = 90.0;
+        assert_eq!(angles.original_text(), None);
+        assert_eq!(write(&angles), prefixed("0.1 90 45"));
+    }
+
+    #[test]
+    fn short_string_is_an_error() {
+        let mut bytes = prefixed("GFX/map/wall.jpg");
+        bytes.truncate(10);
+
+        assert!(read::<FixedLengthString>(&bytes).is_err());
+        // A bogus length doesn't get allocated up front
+        assert!(read::<FixedLengthString>(&u32::MAX.to_le_bytes()).is_err());
+        let string: FixedLengthString = read(&prefixed("")).unwrap();
+        assert_eq!(string, FixedLengthString::from(""));
+    }
+}
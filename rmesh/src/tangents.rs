@@ -0,0 +1,132 @@
+use crate::{math, ComplexMesh, ExtMesh};
+
+/// Any unit tangent in the surface plane of each normal.
+pub(crate) fn perpendicular_tangents(normals: &[[f32; 3]]) -> Vec<[f32; 4]> {
+    normals
+        .iter()
+        .map(|normal| {
+            let [x, y, z] = math::perpendicular(*normal);
+            [x, y, z, 1.0]
+        })
+        .collect()
+}
+
+/// Tangents following the U direction of UV0.
+///
+/// Triangles with out of range indices are skipped.
+pub(crate) fn uv_tangents(mesh: &ComplexMesh) -> Vec<[f32; 4]> {
+    let mut tangents = vec![[0.0, 0.0, 0.0]; mesh.vertices.len()];
+    let mut bitangents = vec![[0.0, 0.0, 0.0]; mesh.vertices.len()];
+
+    // Accumulate per-face tangent directions derived from UV0
+    for (triangle, corners) in mesh.triangles.iter().zip(mesh.triangles_resolved()) {
+        let Ok([v0, v1, v2]) = corners else {
+            continue;
+        };
+
+        let edge1 = math::sub(v1.position, v0.position);
+        let edge2 = math::sub(v2.position, v0.position);
+        let [du1, dv1] = [
+            v1.tex_coords[0][0] - v0.tex_coords[0][0],
+            v1.tex_coords[0][1] - v0.tex_coords[0][1],
+        ];
+        let [du2, dv2] = [
+            v2.tex_coords[0][0] - v0.tex_coords[0][0],
+            v2.tex_coords[0][1] - v0.tex_coords[0][1],
+        ];
+
+        // Skip triangles with degenerate UVs, they carry no direction
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() <= f32::EPSILON {
+            continue;
+        }
+        let r = 1.0 / det;
+
+        let tangent = math::scale(
+            math::sub(math::scale(edge1, dv2), math::scale(edge2, dv1)),
+            r,
+        );
+        let bitangent = math::scale(
+            math::sub(math::scale(edge2, du1), math::scale(edge1, du2)),
+            r,
+        );
+
+        for index in triangle {
+            let index = *index as usize;
+            tangents[index] = math::add(tangents[index], tangent);
+            bitangents[index] = math::add(bitangents[index], bitangent);
+        }
+    }
+
+    // Orthogonalize against the normal and compute the bitangent sign
+    mesh.calculate_normals()
+        .into_iter()
+        .enumerate()
+        .map(|(i, normal)| {
+            let tangent = tangents[i];
+            let mut t = math::normalize(math::sub(
+                tangent,
+                math::scale(normal, math::dot(normal, tangent)),
+            ));
+            if math::length(t) == 0.0 {
+                t = math::perpendicular(normal);
+            }
+            let w = if math::dot(math::cross(normal, t), bitangents[i]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            [t[0], t[1], t[2], w]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SimpleMesh, Vertex};
+
+    #[test]
+    fn tangents_follow_u_and_skip_bad_triangles() {
+        // Floor facing up, U along X and V along Z
+        let vertex = |x: f32, z: f32| Vertex {
+            position: [x, 0.0, z],
+            tex_coords: [[x, z], [0.0; 2]],
+            ..Default::default()
+        };
+        let mesh = ComplexMesh {
+            vertices: vec![vertex(0.0, 0.0), vertex(0.0, 1.0), vertex(1.0, 0.0)],
+            triangles: vec![[0, 1, 2], [0, 2, 3]],
+            ..Default::default()
+        };
+
+        let tangents = mesh.calculate_tangents();
+
+        assert_eq!(tangents.len(), 3);
+        for tangent in tangents {
+            assert!((tangent[0] - 1.0).abs() < 1e-5, "{tangent:?}");
+            assert_eq!(tangent[3].abs(), 1.0);
+        }
+    }
+
+    #[test]
+    fn collider_tangents_are_perpendicular() {
+        let mesh = SimpleMesh {
+            vertex_count: 3,
+            vertices: vec![[0.0; 3], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            triangle_count: 1,
+            triangles: vec![[0, 1, 2]],
+        };
+
+        for (tangent, normal) in mesh
+            .calculate_tangents()
+            .iter()
+            .zip(mesh.calculate_normals())
+        {
+            let [x, y, z, w] = *tangent;
+            assert!(math::dot([x, y, z], normal).abs() < 1e-5);
+            assert!((math::length([x, y, z]) - 1.0).abs() < 1e-5);
+            assert_eq!(w, 1.0);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::math::{self, Mat4};
+use crate::{ComplexMesh, EntityType, Header, SimpleMesh};
+
+/// Whether the matrix mirrors geometry, which requires flipping triangle winding.
+fn is_mirror(matrix: &Mat4) -> bool {
+    math::determinant(&math::linear_part(matrix)) < 0.0
+}
+
+fn flip_winding(triangles: &mut [[u32; 3]]) {
+    for triangle in triangles {
+        triangle.swap(0, 2);
+    }
+}
+
+impl ComplexMesh {
+    /// Applies a column-major affine matrix to every vertex position.
+    ///
+    /// Mirroring matrices also flip triangle winding so generated normals keep facing outwards.
+    pub fn transform(&mut self, matrix: &[[f32; 4]; 4]) {
+        for vertex in &mut self.vertices {
+            vertex.position = math::transform_point(matrix, vertex.position);
+        }
+        if is_mirror(matrix) {
+            flip_winding(&mut self.triangles);
+        }
+    }
+}
+
+impl SimpleMesh {
+    /// Applies a column-major affine matrix to every vertex position.
+    ///
+    /// Mirroring matrices also flip triangle winding so generated normals keep facing outwards.
+    pub fn transform(&mut self, matrix: &[[f32; 4]; 4]) {
+        for vertex in &mut self.vertices {
+            *vertex = math::transform_point(matrix, *vertex);
+        }
+        if is_mirror(matrix) {
+            flip_winding(&mut self.triangles);
+        }
+    }
+}
+
+impl EntityType {
+    /// Applies a column-major affine matrix to the entity position, orientation and size.
+    pub fn transform(&mut self, matrix: &[[f32; 4]; 4]) {
+        let position = self.position_mut();
+        *position = math::transform_point(matrix, *position);
+
+        let linear = math::linear_part(matrix);
+        let column_scale = [0, 1, 2].map(|c| math::length([0, 1, 2].map(|r| linear[r][c])));
+        let uniform_scale = math::determinant(&linear).abs().cbrt();
+
+        // Pure rotation part of the matrix, with any mirror folded into the X axis
+        let mut rotation = [0, 1, 2].map(|r| {
+            [0, 1, 2].map(|c| {
+                if column_scale[c] != 0.0 {
+                    linear[r][c] / column_scale[c]
+                } else {
+                    0.0
+                }
+            })
+        });
+        let mirror = math::determinant(&rotation) < 0.0;
+        if mirror {
+            for row in &mut rotation {
+                row[0] = -row[0];
+            }
+        }
+        let rotate = |angles: [f32; 3]| {
+            math::matrix_to_euler(&math::mul_mat3(&rotation, &math::euler_to_matrix(angles)))
+        };
+
+        match self {
+            EntityType::Light(data) => data.range *= uniform_scale,
+            EntityType::SpotLight(data) => {
+                data.range *= uniform_scale;
+                data.angles.0 = rotate(data.angles.0);
+            }
+            EntityType::PlayerStart(data) => data.angles.0 = rotate(data.angles.0),
+            EntityType::Model(data) => {
+                data.rotation = rotate(data.rotation);
+                data.scale = [0, 1, 2].map(|i| data.scale[i] * column_scale[i]);
+                if mirror {
+                    data.scale[0] = -data.scale[0];
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Header {
+    /// Moves all geometry and entity positions by `offset`.
+    ///
+    /// Unlike [`Header::transform`] with a translation matrix, entity angles and sizes are left
+    /// untouched, so their strings are written back exactly as they were read.
+    pub fn translate(&mut self, offset: [f32; 3]) {
+        let translate = |position: &mut [f32; 3]| {
+            for (value, offset) in position.iter_mut().zip(offset) {
+                *value += offset;
+            }
+        };
+        for vertex in self.meshes.iter_mut().flat_map(|mesh| &mut mesh.vertices) {
+            translate(&mut vertex.position);
+        }
+        let simple_meshes = self.colliders.iter_mut().chain(
+            self.trigger_boxes
+                .iter_mut()
+                .flat_map(|trigger_box| &mut trigger_box.meshes),
+        );
+        for vertex in simple_meshes.flat_map(|mesh| &mut mesh.vertices) {
+            translate(vertex);
+        }
+        for entity_type in self.entities.iter_mut().flat_map(|e| &mut e.entity_type) {
+            translate(entity_type.position_mut());
+        }
+    }
+
+    /// Applies a column-major affine matrix to all geometry and entities of the room.
+    pub fn transform(&mut self, matrix: &[[f32; 4]; 4]) {
+        for mesh in &mut self.meshes {
+            mesh.transform(matrix);
+        }
+        for collider in &mut self.colliders {
+            collider.transform(matrix);
+        }
+        for trigger_box in &mut self.trigger_boxes {
+            for mesh in &mut trigger_box.meshes {
+                mesh.transform(matrix);
+            }
+        }
+        for entity in &mut self.entities {
+            if let Some(entity_type) = &mut entity.entity_type {
+                entity_type.transform(matrix);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityLight, EntityModel, EntitySpotlight};
+
+    fn scale_matrix(s: f32) -> Mat4 {
+        [
+            [s, 0.0, 0.0, 0.0],
+            [0.0, s, 0.0, 0.0],
+            [0.0, 0.0, s, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+
+    #[test]
+    fn scaling_scales_light_ranges() {
+        let mut light = EntityType::Light(EntityLight {
+            position: [10.0, 20.0, 30.0],
+            range: 500.0,
+            color: [255, 255, 255].into(),
+            intensity: 1.0,
+        });
+        let mut spot = EntityType::SpotLight(EntitySpotlight {
+            position: [0.0; 3],
+            range: 250.0,
+            color: [255, 255, 255].into(),
+            intensity: 1.0,
+            angles: [0.0; 3].into(),
+            inner_cone_angle: 10.0,
+            outer_cone_angle: 30.0,
+        });
+
+        light.transform(&scale_matrix(0.5));
+        spot.transform(&scale_matrix(4.0));
+
+        let EntityType::Light(light) = light else {
+            unreachable!()
+        };
+        assert_eq!(light.position, [5.0, 10.0, 15.0]);
+        assert!((light.range - 250.0).abs() < 1e-3);
+        let EntityType::SpotLight(spot) = spot else {
+            unreachable!()
+        };
+        assert!((spot.range - 1000.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn mirror_flips_winding_and_model_scale() {
+        let mut mesh = SimpleMesh {
+            vertex_count: 3,
+            vertices: vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            triangle_count: 1,
+            triangles: vec![[0, 1, 2]],
+        };
+        let mut mirror = scale_matrix(1.0);
+        mirror[0][0] = -1.0;
+
+        mesh.transform(&mirror);
+
+        assert_eq!(mesh.vertices[0], [-1.0, 0.0, 0.0]);
+        assert_eq!(mesh.triangles, [[2, 1, 0]]);
+
+        let mut model = EntityType::Model(EntityModel {
+            name: "chair.x".into(),
+            position: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: [2.0, 2.0, 2.0],
+        });
+        model.transform(&mirror);
+        let EntityType::Model(model) = model else {
+            unreachable!()
+        };
+        assert_eq!(model.scale, [-2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn translate_moves_every_position() {
+        let mut header = Header {
+            colliders: vec![SimpleMesh {
+                vertex_count: 1,
+                vertices: vec![[1.0, 1.0, 1.0]],
+                triangle_count: 0,
+                triangles: vec![],
+            }],
+            entities: vec![EntityType::Light(EntityLight {
+                position: [0.0; 3],
+                range: 100.0,
+                color: [1, 2, 3].into(),
+                intensity: 0.5,
+            })
+            .into()],
+            ..Default::default()
+        };
+
+        header.translate([2.0, -1.0, 0.5]);
+
+        assert_eq!(header.colliders[0].vertices[0], [3.0, 0.0, 1.5]);
+        let entity = header.entities[0].entity_type.as_ref().unwrap();
+        assert_eq!(entity.position(), [2.0, -1.0, 0.5]);
+    }
+}
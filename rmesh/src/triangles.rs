@@ -0,0 +1,44 @@
+use crate::{ComplexMesh, RMeshError, SimpleMesh, Vertex};
+
+/// Looks up the three corners of a triangle, failing on out of range indices.
+fn resolve<T>(vertices: &[T], triangle: usize, indices: [u32; 3]) -> Result<[&T; 3], RMeshError> {
+    let get = |index: u32| {
+        vertices
+            .get(index as usize)
+            .ok_or(RMeshError::InvalidIndex {
+                triangle,
+                index,
+                vertex_count: vertices.len(),
+            })
+    };
+    Ok([get(indices[0])?, get(indices[1])?, get(indices[2])?])
+}
+
+impl ComplexMesh {
+    /// Iterates over the triangles with their vertices looked up.
+    pub fn triangles_resolved(
+        &self,
+    ) -> impl Iterator<Item = Result<[&Vertex; 3], RMeshError>> + '_ {
+        self.triangles
+            .iter()
+            .enumerate()
+            .map(|(i, triangle)| resolve(&self.vertices, i, *triangle))
+    }
+
+    /// Fails on the first triangle referencing a vertex out of range.
+    pub fn check_indices(&self) -> Result<(), RMeshError> {
+        self.triangles_resolved()
+            .try_for_each(|corners| corners.map(drop))
+    }
+}
+
+impl SimpleMesh {
+    /// Iterates over the triangles with their vertex positions looked up.
+    pub fn triangles_resolved(
+        &self,
+    ) -> impl Iterator<Item = Result<[[f32; 3]; 3], RMeshError>> + '_ {
+        self.triangles.iter().enumerate().map(|(i, triangle)| {
+            resolve(&self.vertices, i, *triangle).map(|corners| corners.map(|v| *v))
+        })
+    }
+}
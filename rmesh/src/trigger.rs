@@ -0,0 +1,48 @@
+use crate::math;
+use crate::{Bounds, ExtMesh, TriggerBox};
+
+impl TriggerBox {
+    /// Bounds of all meshes making up the trigger volume.
+    pub fn bounding_box(&self) -> Bounds {
+        self.meshes
+            .iter()
+            .map(|mesh| mesh.bounding_box())
+            .fold(Bounds::EMPTY, |a, b| a.union(&b))
+    }
+
+    /// Whether the point lies inside any of the closed trigger meshes.
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        if !self.bounding_box().contains_point(point) {
+            return false;
+        }
+
+        // Odd number of crossings along a ray means inside, the direction is skewed
+        // slightly to avoid grazing edges of axis aligned boxes
+        let direction = math::normalize([1.0, 1e-3, 2e-3]);
+        self.meshes.iter().any(|mesh| {
+            let crossings = mesh
+                .triangles_resolved()
+                .flatten()
+                .filter(|triangle| math::ray_triangle(point, direction, *triangle).is_some())
+                .count();
+            crossings % 2 == 1
+        })
+    }
+
+    /// Whether the trigger volume overlaps the box.
+    pub fn intersects_aabb(&self, bounds: &Bounds) -> bool {
+        if !self.bounding_box().intersects(bounds) {
+            return false;
+        }
+
+        // Either a surface crosses the box or the box lies completely inside the volume
+        let center = bounds.center();
+        let half_extents = bounds.half_extents();
+        let surface_overlap = self.meshes.iter().any(|mesh| {
+            mesh.triangles_resolved()
+                .flatten()
+                .any(|triangle| math::triangle_intersects_box(triangle, center, half_extents))
+        });
+        surface_overlap || self.contains_point(center)
+    }
+}
@@ -0,0 +1,289 @@
+//! Lightmap UV generation by planar charting and shelf packing.
+
+use crate::math;
+use crate::{ComplexMesh, RMeshError};
+
+/// Padding between charts in texels, avoids bleeding when the lightmap is filtered.
+const PADDING: f32 = 2.0;
+
+/// Size of the lightmap the generated UVs were packed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightmapLayout {
+    pub width: u32,
+    pub height: u32,
+    /// Number of charts the mesh was cut into.
+    pub chart_count: usize,
+}
+
+/// Triangles projected along the same axis and connected through shared vertices.
+struct Chart {
+    triangles: Vec<usize>,
+    axis: usize,
+    min: [f32; 2],
+    size: [f32; 2],
+    offset: [f32; 2],
+}
+
+/// Drops the dominant axis, keeping the two in-plane coordinates.
+fn project(position: [f32; 3], axis: usize) -> [f32; 2] {
+    match axis {
+        0 => [position[2], position[1]],
+        1 => [position[0], position[2]],
+        _ => [position[0], position[1]],
+    }
+}
+
+impl ComplexMesh {
+    /// Generates non-overlapping lightmap coordinates in the second UV channel.
+    ///
+    /// `texel_density` is the number of lightmap texels per room unit. Vertices shared
+    /// between charts are split, so the vertex count may grow. Fails without touching the mesh
+    /// when a triangle references a vertex out of range.
+    pub fn generate_lightmap_uvs(
+        &mut self,
+        texel_density: f32,
+    ) -> Result<LightmapLayout, RMeshError> {
+        if !(texel_density.is_finite() && texel_density > 0.0) {
+            return Err(RMeshError::InvalidTexelDensity(texel_density));
+        }
+        self.check_indices()?;
+
+        let axes: Vec<usize> = self
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| self.vertices[i as usize].position);
+                let normal = math::cross(math::sub(b, a), math::sub(c, a)).map(f32::abs);
+                (0..3)
+                    .max_by(|x, y| normal[*x].total_cmp(&normal[*y]))
+                    .unwrap_or(2)
+            })
+            .collect();
+
+        let mut vertex_triangles = vec![vec![]; self.vertices.len()];
+        for (t, triangle) in self.triangles.iter().enumerate() {
+            for index in triangle {
+                vertex_triangles[*index as usize].push(t);
+            }
+        }
+
+        // Flood fill triangles sharing vertices and a projection axis into charts
+        let mut charts = vec![];
+        let mut visited = vec![false; self.triangles.len()];
+        for seed in 0..self.triangles.len() {
+            if visited[seed] {
+                continue;
+            }
+            visited[seed] = true;
+            let mut stack = vec![seed];
+            let mut triangles = vec![];
+            while let Some(t) = stack.pop() {
+                triangles.push(t);
+                for index in self.triangles[t] {
+                    for neighbour in &vertex_triangles[index as usize] {
+                        if !visited[*neighbour] && axes[*neighbour] == axes[seed] {
+                            visited[*neighbour] = true;
+                            stack.push(*neighbour);
+                        }
+                    }
+                }
+            }
+
+            let points = triangles
+                .iter()
+                .flat_map(|t| self.triangles[*t])
+                .map(|i| project(self.vertices[i as usize].position, axes[seed]));
+            let (min, max) = points.fold(
+                ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]),
+                |(min, max), p| {
+                    (
+                        [min[0].min(p[0]), min[1].min(p[1])],
+                        [max[0].max(p[0]), max[1].max(p[1])],
+                    )
+                },
+            );
+            // A NaN or infinite extent would let the shelf packer overlap charts
+            let extent = |i: usize| {
+                let extent = (max[i] - min[i]) * texel_density;
+                if extent.is_finite() {
+                    extent.max(0.0)
+                } else {
+                    0.0
+                }
+            };
+            charts.push(Chart {
+                triangles,
+                axis: axes[seed],
+                min,
+                size: [extent(0) + PADDING * 2.0, extent(1) + PADDING * 2.0],
+                offset: [0.0, 0.0],
+            });
+        }
+
+        // Shelf packing, tallest charts first, into a roughly square atlas
+        let area: f32 = charts.iter().map(|c| c.size[0] * c.size[1]).sum();
+        let widest = charts.iter().map(|c| c.size[0]).fold(0.0, f32::max);
+        let width = (area.sqrt() * 1.1).max(widest).ceil();
+        let mut order: Vec<usize> = (0..charts.len()).collect();
+        order.sort_by(|a, b| charts[*b].size[1].total_cmp(&charts[*a].size[1]));
+
+        let (mut x, mut y, mut shelf_height) = (0.0_f32, 0.0_f32, 0.0_f32);
+        for index in order {
+            let chart = &mut charts[index];
+            if x + chart.size[0] > width {
+                x = 0.0;
+                y += shelf_height;
+                shelf_height = 0.0;
+            }
+            chart.offset = [x, y];
+            x += chart.size[0];
+            shelf_height = shelf_height.max(chart.size[1]);
+        }
+        let height = (y + shelf_height).ceil().max(1.0);
+        let width = width.max(1.0);
+
+        // Give every chart its own copy of the vertices it uses
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut triangles = self.triangles.clone();
+        for chart in &charts {
+            let mut remap = std::collections::HashMap::new();
+            for t in &chart.triangles {
+                for (corner, source) in self.triangles[*t].into_iter().enumerate() {
+                    triangles[*t][corner] = *remap.entry(source).or_insert_with(|| {
+                        let mut vertex = self.vertices[source as usize].clone();
+                        let p = project(vertex.position, chart.axis);
+                        vertex.tex_coords[1] = [
+                            (chart.offset[0] + PADDING + (p[0] - chart.min[0]) * texel_density)
+                                / width,
+                            (chart.offset[1] + PADDING + (p[1] - chart.min[1]) * texel_density)
+                                / height,
+                        ];
+                        vertices.push(vertex);
+                        vertices.len() as u32 - 1
+                    });
+                }
+            }
+        }
+        self.vertices = vertices;
+        self.triangles = triangles;
+
+        debug_assert!(!any_overlap(&charts), "lightmap charts overlap");
+        Ok(LightmapLayout {
+            width: width as u32,
+            height: height as u32,
+            chart_count: charts.len(),
+        })
+    }
+}
+
+/// Whether any two packed charts share texels, sweeping along X.
+fn any_overlap(charts: &[Chart]) -> bool {
+    let mut order: Vec<&Chart> = charts.iter().collect();
+    order.sort_by(|a, b| a.offset[0].total_cmp(&b.offset[0]));
+    order.iter().enumerate().any(|(i, a)| {
+        order[i + 1..]
+            .iter()
+            .take_while(|b| b.offset[0] < a.offset[0] + a.size[0])
+            .any(|b| b.offset[1] < a.offset[1] + a.size[1] && a.offset[1] < b.offset[1] + b.size[1])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vertex;
+
+    /// A 32 unit cube sharing its 8 corners between faces.
+    fn cube() -> ComplexMesh {
+        let vertices = (0..8)
+            .map(|i| Vertex {
+                position: [i & 1, (i >> 1) & 1, (i >> 2) & 1].map(|bit| bit as f32 * 32.0),
+                ..Default::default()
+            })
+            .collect();
+        let quads = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        ComplexMesh {
+            vertices,
+            triangles: quads
+                .iter()
+                .flat_map(|[a, b, c, d]| [[*a, *b, *c], [*a, *c, *d]])
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cube_faces_become_separate_charts() {
+        let mut mesh = cube();
+        let corners: Vec<_> = mesh
+            .triangles_resolved()
+            .map(|t| t.unwrap().map(|v| v.position))
+            .collect();
+
+        let layout = mesh.generate_lightmap_uvs(0.5).unwrap();
+
+        assert_eq!(layout.chart_count, 6);
+        // Each face gets its own copy of its 4 corners
+        assert_eq!(mesh.vertices.len(), 24);
+        let after: Vec<_> = mesh
+            .triangles_resolved()
+            .map(|t| t.unwrap().map(|v| v.position))
+            .collect();
+        assert_eq!(after, corners);
+        for vertex in &mesh.vertices {
+            assert!(vertex.tex_coords[1]
+                .iter()
+                .all(|uv| (0.0..=1.0).contains(uv)));
+        }
+    }
+
+    #[test]
+    fn bad_input_is_rejected_untouched() {
+        let mut mesh = cube();
+        mesh.triangles[3][1] = 8;
+
+        assert!(matches!(
+            mesh.generate_lightmap_uvs(1.0),
+            Err(RMeshError::InvalidIndex { triangle: 3, .. })
+        ));
+        assert_eq!(mesh.vertices.len(), 8);
+
+        for density in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                cube().generate_lightmap_uvs(density),
+                Err(RMeshError::InvalidTexelDensity(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn overlap_sweep() {
+        let chart = |offset: [f32; 2], size: [f32; 2]| Chart {
+            triangles: vec![],
+            axis: 0,
+            min: [0.0; 2],
+            size,
+            offset,
+        };
+        let side_by_side = [
+            chart([0.0, 0.0], [4.0, 4.0]),
+            chart([4.0, 0.0], [4.0, 4.0]),
+            chart([0.0, 4.0], [8.0, 1.0]),
+        ];
+        assert!(!any_overlap(&side_by_side));
+
+        let stacked = [
+            chart([0.0, 0.0], [4.0, 4.0]),
+            chart([8.0, 0.0], [2.0, 2.0]),
+            chart([3.0, 3.0], [2.0, 2.0]),
+        ];
+        assert!(any_overlap(&stacked));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::resolve::same_path;
+use crate::{Header, Texture, TextureBlendType};
+
+/// Which meshes of a room use a texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureUsage {
+    /// Path as first written in the room.
+    pub path: String,
+    /// Other spellings of the same path, differing in case or slashes. Each one is a separate
+    /// texture to engines with case sensitive file systems or caches keyed by path.
+    pub aliases: Vec<String>,
+    /// Whether the texture is used as a lightmap rather than a diffuse texture.
+    pub lightmap: bool,
+    /// Indices into [`Header::meshes`], in order.
+    pub meshes: Vec<usize>,
+    /// Triangles over all of those meshes.
+    pub triangle_count: usize,
+}
+
+/// What [`Header::texture_usage`] found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextureUsageReport {
+    /// One entry per unique texture path, in order of first use.
+    pub textures: Vec<TextureUsage>,
+    /// Groups of meshes with the same textures and blend types, ignoring path case. Merging
+    /// each group, like [`Header::split_by_texture`] does once the paths agree, saves draw calls.
+    pub mergeable: Vec<Vec<usize>>,
+}
+
+/// Whether two meshes use the same textures with the same blend types, see [`same_path`].
+fn same_textures(a: &[Texture; 2], b: &[Texture; 2]) -> bool {
+    a.iter().zip(b).all(|(a, b)| {
+        a.blend_type == b.blend_type
+            && match (&a.path, &b.path) {
+                (Some(a), Some(b)) => same_path(&String::from(a), &String::from(b)),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    })
+}
+
+impl Header {
+    /// Lists which meshes use each texture and which meshes could be merged.
+    pub fn texture_usage(&self) -> TextureUsageReport {
+        let mut report = TextureUsageReport::default();
+        let mut groups: Vec<(&[Texture; 2], Vec<usize>)> = vec![];
+
+        for (index, mesh) in self.meshes.iter().enumerate() {
+            for texture in &mesh.textures {
+                let Some(path) = texture.path.as_ref().map(String::from) else {
+                    continue;
+                };
+                let existing = report
+                    .textures
+                    .iter()
+                    .position(|usage| same_path(&usage.path, &path));
+                let usage = match existing {
+                    Some(i) => &mut report.textures[i],
+                    None => {
+                        report.textures.push(TextureUsage {
+                            path: path.clone(),
+                            aliases: vec![],
+                            lightmap: texture.blend_type == TextureBlendType::Lightmap,
+                            meshes: vec![],
+                            triangle_count: 0,
+                        });
+                        report.textures.last_mut().unwrap()
+                    }
+                };
+                if path != usage.path && !usage.aliases.contains(&path) {
+                    usage.aliases.push(path);
+                }
+                // A mesh using the same texture in both slots counts once
+                if usage.meshes.last() != Some(&index) {
+                    usage.meshes.push(index);
+                    usage.triangle_count += mesh.triangles.len();
+                }
+            }
+
+            match groups
+                .iter_mut()
+                .find(|(other, _)| same_textures(other, &mesh.textures))
+            {
+                Some((_, meshes)) => meshes.push(index),
+                None => groups.push((&mesh.textures, vec![index])),
+            }
+        }
+
+        report.mergeable = groups
+            .into_iter()
+            .map(|(_, meshes)| meshes)
+            .filter(|meshes| meshes.len() > 1)
+            .collect();
+        report
+    }
+}
@@ -0,0 +1,30 @@
+use crate::ComplexMesh;
+
+impl ComplexMesh {
+    /// Scales, rotates and offsets one texture coordinate channel.
+    ///
+    /// Channel `0` holds the diffuse coordinates and `1` the lightmap coordinates.
+    /// Coordinates are scaled first, then rotated counter-clockwise by `rotation` radians
+    /// around the origin and finally offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not `0` or `1`.
+    pub fn transform_uvs(
+        &mut self,
+        channel: usize,
+        scale: [f32; 2],
+        offset: [f32; 2],
+        rotation: f32,
+    ) {
+        assert!(channel < 2, "Invalid texture coordinate channel {channel}");
+
+        let (sin, cos) = rotation.sin_cos();
+        for vertex in &mut self.vertices {
+            let [u, v] = vertex.tex_coords[channel];
+            let [u, v] = [u * scale[0], v * scale[1]];
+            vertex.tex_coords[channel] =
+                [u * cos - v * sin + offset[0], u * sin + v * cos + offset[1]];
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::io::Cursor;
+
+use binrw::{BinReaderExt, BinResult};
+
+use crate::compression;
+use crate::sections::skip_counted;
+use crate::{ComplexMesh, EntityData, FixedLengthString, Texture, TextureBlendType};
+
+/// Size of a [`Vertex`](crate::Vertex) in the file.
+pub(crate) const VERTEX_SIZE: u64 = 31;
+/// Size of a triangle or a [`SimpleMesh`](crate::SimpleMesh) vertex in the file.
+pub(crate) const TRIANGLE_SIZE: u64 = 12;
+
+/// Tag suffix of files with a trigger box section.
+const TRIGGER_BOX_SUFFIX: &str = ".HasTriggerBox";
+/// Tag suffix CBRE-EX adds when every mesh is followed by a [`MeshExtension`](crate::MeshExtension).
+const MESH_DATA_SUFFIX: &str = ".HasMeshData";
+
+/// Flavour of rmesh file, detected from the header tag and editor fingerprints.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FormatVariant {
+    /// Plain `RoomMesh`, as exported by the original room tools.
+    #[default]
+    RoomMesh,
+    /// `RoomMesh.HasTriggerBox`, which adds the trigger box section.
+    HasTriggerBox,
+    /// Written by CBRE or CBRE-EX, recognised by the lightmap naming or trailing data.
+    Cbre,
+    /// Written by CBRE-EX with per-mesh data, tagged `.HasMeshData`.
+    CbreEx,
+    /// Any other tag. [`read_rmesh`](crate::read_rmesh) rejects such files,
+    /// [`read_rmesh_lenient`](crate::read_rmesh_lenient) reads them as a plain `RoomMesh`.
+    Unknown(String),
+}
+
+impl FormatVariant {
+    /// Classifies a parsed file.
+    pub fn detect(tag: &[u8], meshes: &[ComplexMesh], extra: &[u8]) -> Self {
+        let cbre = !extra.is_empty() || meshes.iter().any(|m| has_cbre_lightmap(&m.textures));
+        Self::classify(tag, cbre)
+    }
+
+    fn classify(tag: &[u8], cbre: bool) -> Self {
+        match tag_sections(tag) {
+            None => Self::Unknown(String::from_utf8_lossy(tag).into_owned()),
+            Some((_, true)) => Self::CbreEx,
+            Some(_) if cbre => Self::Cbre,
+            Some((true, false)) => Self::HasTriggerBox,
+            Some((false, false)) => Self::RoomMesh,
+        }
+    }
+}
+
+/// Whether a tag announces trigger boxes and per-mesh data, `None` for tags this crate can't
+/// read.
+pub(crate) fn tag_sections(tag: &[u8]) -> Option<(bool, bool)> {
+    let rest = tag.strip_prefix(b"RoomMesh")?;
+    let (trigger_boxes, rest) = match rest.strip_prefix(TRIGGER_BOX_SUFFIX.as_bytes()) {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let (mesh_data, rest) = match rest.strip_prefix(MESH_DATA_SUFFIX.as_bytes()) {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    rest.is_empty().then_some((trigger_boxes, mesh_data))
+}
+
+/// Header tag of a room with these sections.
+pub(crate) fn section_tag(trigger_boxes: bool, mesh_data: bool) -> FixedLengthString {
+    let mut tag = String::from("RoomMesh");
+    if trigger_boxes {
+        tag.push_str(TRIGGER_BOX_SUFFIX);
+    }
+    if mesh_data {
+        tag.push_str(MESH_DATA_SUFFIX);
+    }
+    tag.as_str().into()
+}
+
+/// Whether files with this header tag contain the trigger box section.
+pub(crate) fn has_trigger_boxes(tag: &FixedLengthString) -> bool {
+    tag_sections(&tag.values).is_some_and(|(trigger_boxes, _)| trigger_boxes)
+}
+
+/// Whether every mesh of files with this header tag is followed by a
+/// [`MeshExtension`](crate::MeshExtension).
+pub(crate) fn has_mesh_data(tag: &FixedLengthString) -> bool {
+    tag_sections(&tag.values).is_some_and(|(_, mesh_data)| mesh_data)
+}
+
+/// CBRE names its lightmaps `<room>_lm<n>`.
+fn has_cbre_lightmap(textures: &[Texture; 2]) -> bool {
+    textures.iter().any(|texture| {
+        let Some(path) = &texture.path else {
+            return false;
+        };
+        let path = String::from(path);
+        let stem = path
+            .rsplit_once('.')
+            .map_or(path.as_str(), |(stem, _)| stem);
+        texture.blend_type == TextureBlendType::Lightmap
+            && stem.rsplit_once("_lm").is_some_and(|(_, index)| {
+                !index.is_empty() && index.bytes().all(|c| c.is_ascii_digit())
+            })
+    })
+}
+
+/// Detects the variant of an rmesh file without fully parsing it.
+///
+/// Only the texture paths and entities are decoded, vertex and triangle data is skipped.
+/// Compressed files are unwrapped first. Files too short to hold a header tag, or
+/// compressed without the matching feature, are reported as [`FormatVariant::Unknown`].
+pub fn detect_variant(bytes: &[u8]) -> FormatVariant {
+    let Ok(bytes) = compression::decompress(bytes) else {
+        return FormatVariant::Unknown(String::new());
+    };
+    let mut cursor = Cursor::new(&bytes[..]);
+    let Ok(tag) = cursor.read_le::<FixedLengthString>() else {
+        return FormatVariant::Unknown(String::new());
+    };
+    // Truncated files are classified by their tag alone
+    let cbre = scan_for_cbre(&mut cursor, &tag).unwrap_or(false);
+    FormatVariant::classify(&tag.values, cbre)
+}
+
+fn scan_for_cbre(cursor: &mut Cursor<&[u8]>, tag: &FixedLengthString) -> BinResult<bool> {
+    let len = cursor.get_ref().len() as u64;
+    let mesh_count: u32 = cursor.read_le()?;
+    for _ in 0..mesh_count {
+        let textures: [Texture; 2] = cursor.read_le()?;
+        if has_cbre_lightmap(&textures) {
+            return Ok(true);
+        }
+        skip_counted(cursor, VERTEX_SIZE)?;
+        skip_counted(cursor, TRIANGLE_SIZE)?;
+        if has_mesh_data(tag) {
+            skip_counted(cursor, 1)?;
+        }
+    }
+
+    let collider_count: u32 = cursor.read_le()?;
+    for _ in 0..collider_count {
+        skip_counted(cursor, TRIANGLE_SIZE)?;
+        skip_counted(cursor, TRIANGLE_SIZE)?;
+    }
+
+    if has_trigger_boxes(tag) {
+        let trigger_box_count: u32 = cursor.read_le()?;
+        for _ in 0..trigger_box_count {
+            let mesh_count: u32 = cursor.read_le()?;
+            for _ in 0..mesh_count {
+                skip_counted(cursor, TRIANGLE_SIZE)?;
+                skip_counted(cursor, TRIANGLE_SIZE)?;
+            }
+            let _name: FixedLengthString = cursor.read_le()?;
+        }
+    }
+
+    let entity_count: u32 = cursor.read_le()?;
+    for _ in 0..entity_count {
+        let _entity: EntityData = cursor.read_le()?;
+    }
+
+    // Anything after the entities is an editor extension
+    Ok(cursor.position() < len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_rmesh, Header, TriggerBox};
+
+    fn lightmapped(lightmap: &str) -> Header {
+        Header {
+            meshes: vec![ComplexMesh {
+                textures: [
+                    Texture {
+                        blend_type: TextureBlendType::Lightmap,
+                        path: Some(lightmap.into()),
+                    },
+                    Texture::default(),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detects_without_parsing_geometry() {
+        let detect = |header: &Header| detect_variant(&write_rmesh(header).unwrap());
+
+        assert_eq!(detect(&lightmapped("room_lm.png")), FormatVariant::RoomMesh);
+        assert_eq!(detect(&lightmapped("room_lm12.png")), FormatVariant::Cbre);
+
+        let mut trigger = lightmapped("room_lmx.png");
+        trigger.trigger_boxes.push(TriggerBox {
+            meshes: vec![],
+            name: "t".into(),
+        });
+        assert_eq!(detect(&trigger), FormatVariant::HasTriggerBox);
+
+        assert_eq!(
+            detect_variant(b"\x02\0"),
+            FormatVariant::Unknown(String::new())
+        );
+    }
+
+    #[test]
+    fn tags() {
+        assert_eq!(tag_sections(b"RoomMesh"), Some((false, false)));
+        assert_eq!(
+            tag_sections(b"RoomMesh.HasTriggerBox.HasMeshData"),
+            Some((true, true))
+        );
+        // The suffixes only come in this order
+        assert_eq!(tag_sections(b"RoomMesh.HasMeshData.HasTriggerBox"), None);
+        assert_eq!(tag_sections(b"roommesh"), None);
+        assert_eq!(
+            String::from(section_tag(false, true)),
+            "RoomMesh.HasMeshData"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Browser bindings, enabled by the `wasm-bindgen` feature.
+//!
+//! Packaged by the `rmesh_wasm` crate with `wasm-pack build rmesh_wasm --target web`, see the
+//! web viewer in `rmesh_wasm/web`.
+
+use js_sys::{Float32Array, JSON};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::{read_rmesh, Header};
+
+fn read(bytes: &[u8]) -> Result<Header, JsValue> {
+    read_rmesh(bytes).map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+/// Summarizes a room as a plain object, throwing the parse error as a string.
+///
+/// Sites accepting uploaded rooms can check a file parses and show what's in it.
+#[wasm_bindgen]
+pub fn parse(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    JSON::parse(&summary(&read(bytes)?).to_string())
+}
+
+/// Corners of every visible triangle, three floats each, in room space for previews.
+#[wasm_bindgen]
+pub fn triangles(bytes: &[u8]) -> Result<Float32Array, JsValue> {
+    Ok(Float32Array::from(&corners(&read(bytes)?)[..]))
+}
+
+fn summary(header: &Header) -> Value {
+    let stats = header.stats();
+    let entities: Vec<_> = header
+        .entities
+        .iter()
+        .filter_map(|entity| entity.entity_type.as_ref())
+        .map(|entity| json!({ "type": entity.name(), "position": entity.position() }))
+        .collect();
+    json!({
+        "variant": format!("{:?}", header.variant),
+        "meshes": stats.mesh_count,
+        "colliders": stats.collider_count,
+        "triggerBoxes": stats.trigger_box_count,
+        "vertices": stats.vertex_count,
+        "triangles": stats.triangle_count,
+        "textures": stats.textures,
+        "entities": entities,
+        "bounds": stats.bounds.map(|bounds| json!({ "min": bounds.min, "max": bounds.max })),
+    })
+}
+
+fn corners(header: &Header) -> Vec<f32> {
+    header
+        .meshes
+        .iter()
+        .filter(|mesh| !mesh.is_invisible())
+        .flat_map(|mesh| mesh.triangles_resolved().flatten())
+        .flatten()
+        .flat_map(|vertex| vertex.position)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComplexMesh, EntityType, EntityWaypoint, Texture, TextureBlendType, Vertex};
+
+    fn room() -> Header {
+        let mesh = |blend_type| ComplexMesh {
+            textures: [
+                Texture {
+                    blend_type,
+                    path: Some("wall.jpg".into()),
+                },
+                Texture::default(),
+            ],
+            vertices: (0..3)
+                .map(|i| Vertex {
+                    position: [i as f32, 2.0, 0.0],
+                    ..Default::default()
+                })
+                .collect(),
+            triangles: vec![[0, 1, 2]],
+            ..Default::default()
+        };
+        Header {
+            meshes: vec![
+                mesh(TextureBlendType::Visible),
+                mesh(TextureBlendType::None),
+            ],
+            entities: vec![EntityType::WayPoint(EntityWaypoint {
+                position: [1.0, 2.0, 3.0],
+            })
+            .into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn summary_lists_the_room_contents() {
+        let summary = summary(&room());
+
+        assert_eq!(summary["variant"], "RoomMesh");
+        assert_eq!(summary["meshes"], 2);
+        assert_eq!(summary["textures"], json!(["wall.jpg"]));
+        assert_eq!(
+            summary["entities"],
+            json!([{ "type": "waypoint", "position": [1.0, 2.0, 3.0] }])
+        );
+    }
+
+    #[test]
+    fn corners_skip_invisible_meshes() {
+        assert_eq!(
+            corners(&room()),
+            [0.0, 2.0, 0.0, 1.0, 2.0, 0.0, 2.0, 2.0, 0.0]
+        );
+    }
+}
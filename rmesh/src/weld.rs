@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::{ComplexMesh, RMeshError, Vertex};
+
+/// Controls which vertices [`ComplexMesh::weld_with`] merges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeldOptions {
+    /// Maximum distance per axis between merged positions (and texture coordinates).
+    pub tolerance: f32,
+    /// Only merge vertices whose texture coordinates also match.
+    pub match_uvs: bool,
+    /// Only merge vertices whose colors are identical.
+    pub match_color: bool,
+}
+
+impl Default for WeldOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.0,
+            match_uvs: true,
+            match_color: true,
+        }
+    }
+}
+
+impl ComplexMesh {
+    /// Merges vertices closer than `tolerance` that share texture coordinates and color.
+    ///
+    /// Returns the number of vertices removed.
+    pub fn weld(&mut self, tolerance: f32) -> Result<usize, RMeshError> {
+        self.weld_with(&WeldOptions {
+            tolerance,
+            ..Default::default()
+        })
+    }
+
+    /// Merges vertices according to `options`, remapping the triangle indices.
+    ///
+    /// Returns the number of vertices removed. Fails without touching the mesh when a triangle
+    /// references a vertex out of range.
+    pub fn weld_with(&mut self, options: &WeldOptions) -> Result<usize, RMeshError> {
+        self.check_indices()?;
+
+        let tolerance = options.tolerance.max(0.0);
+        let cell_size = tolerance.max(f32::EPSILON) * 2.0;
+        let cell = |p: [f32; 3]| p.map(|c| (c / cell_size).floor() as i64);
+
+        let is_match = |a: &Vertex, b: &Vertex| {
+            let close = |x: f32, y: f32| (x - y).abs() <= tolerance;
+            (0..3).all(|i| close(a.position[i], b.position[i]))
+                && (!options.match_uvs
+                    || (0..2)
+                        .all(|c| (0..2).all(|i| close(a.tex_coords[c][i], b.tex_coords[c][i]))))
+                && (!options.match_color || a.color == b.color)
+        };
+
+        let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+        let mut vertices: Vec<Vertex> = Vec::with_capacity(self.vertices.len());
+        let mut remap = Vec::with_capacity(self.vertices.len());
+
+        for vertex in self.vertices.drain(..) {
+            let [x, y, z] = cell(vertex.position);
+
+            // Candidates can only live in this or a neighbouring cell
+            let existing = (-1..=1)
+                .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [dx, dy, dz])))
+                .filter_map(|[dx, dy, dz]| grid.get(&[x + dx, y + dy, z + dz]))
+                .flatten()
+                .copied()
+                .find(|i| is_match(&vertices[*i as usize], &vertex));
+
+            match existing {
+                Some(index) => remap.push(index),
+                None => {
+                    let index = vertices.len() as u32;
+                    grid.entry([x, y, z]).or_default().push(index);
+                    vertices.push(vertex);
+                    remap.push(index);
+                }
+            }
+        }
+
+        for triangle in &mut self.triangles {
+            for index in triangle.iter_mut() {
+                *index = remap[*index as usize];
+            }
+        }
+
+        let removed = remap.len() - vertices.len();
+        self.vertices = vertices;
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3], u: f32) -> Vertex {
+        Vertex {
+            position,
+            tex_coords: [[u, 0.0], [0.0, 0.0]],
+            color: [128, 128, 128],
+        }
+    }
+
+    /// Two triangles of a quad, each with its own copy of the shared edge.
+    fn split_quad() -> ComplexMesh {
+        ComplexMesh {
+            vertices: vec![
+                vertex([0.0, 0.0, 0.0], 0.0),
+                vertex([1.0, 0.0, 0.0], 1.0),
+                vertex([1.0, 0.0, 1.0], 1.0),
+                vertex([0.0, 0.0, 0.0], 0.0),
+                vertex([1.0, 0.0, 1.0], 1.0),
+                vertex([0.0, 0.0, 1.0], 0.0),
+            ],
+            triangles: vec![[0, 1, 2], [3, 4, 5]],
+            ..Default::default()
+        }
+    }
+
+    fn corners(mesh: &ComplexMesh) -> Vec<[[f32; 3]; 3]> {
+        mesh.triangles
+            .iter()
+            .map(|triangle| triangle.map(|i| mesh.vertices[i as usize].position))
+            .collect()
+    }
+
+    #[test]
+    fn weld_keeps_triangles_on_the_same_positions() {
+        let mut mesh = split_quad();
+        let before = corners(&mesh);
+
+        assert_eq!(mesh.weld(0.0).unwrap(), 2);
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(corners(&mesh), before);
+    }
+
+    #[test]
+    fn tolerance_and_uv_matching() {
+        let mut mesh = split_quad();
+        mesh.vertices[3].position[0] = 0.01;
+        mesh.vertices[4].tex_coords[0][0] = 0.5;
+
+        assert_eq!(mesh.clone().weld(0.0).unwrap(), 0);
+        assert_eq!(mesh.clone().weld(0.02).unwrap(), 1);
+        let loose = WeldOptions {
+            tolerance: 0.02,
+            match_uvs: false,
+            ..Default::default()
+        };
+        assert_eq!(mesh.weld_with(&loose).unwrap(), 2);
+    }
+
+    #[test]
+    fn out_of_range_index_leaves_the_mesh_alone() {
+        let mut mesh = split_quad();
+        mesh.triangles[1][2] = 6;
+
+        assert!(matches!(
+            mesh.weld(0.0),
+            Err(RMeshError::InvalidIndex {
+                triangle: 1,
+                index: 6,
+                vertex_count: 6
+            })
+        ));
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.triangles, [[0, 1, 2], [3, 4, 6]]);
+    }
+}
@@ -0,0 +1,745 @@
+//! Reader for DirectX `.x` models in text, binary and compressed form.
+//!
+//! The file is read into a generic tree of data objects, which
+//! [`XFile::to_complex_meshes`] turns into geometry. Templates and animations are skipped.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use binrw::{BinReaderExt, BinResult};
+
+use crate::math::{self, Mat4};
+use crate::{ComplexMesh, RMeshError, Texture, TextureBlendType, Vertex};
+
+/// Encoding of an `.x` file, taken from its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XFormat {
+    Text,
+    Binary,
+    /// MSZIP compressed text.
+    CompressedText,
+    /// MSZIP compressed binary.
+    CompressedBinary,
+}
+
+/// A parsed `.x` file.
+#[derive(Debug, Clone)]
+pub struct XFile {
+    pub format: XFormat,
+    /// Top level data objects.
+    pub objects: Vec<XObject>,
+}
+
+/// A data object such as `Frame`, `Mesh` or `Material`.
+#[derive(Debug, Clone, Default)]
+pub struct XObject {
+    /// Name of the template the object is an instance of.
+    pub template: String,
+    pub name: Option<String>,
+    /// Members in declaration order, with arrays flattened.
+    pub values: Vec<XValue>,
+    /// Nested data objects.
+    pub children: Vec<XObject>,
+    /// Names of objects referenced with `{ name }`.
+    pub references: Vec<String>,
+}
+
+/// A member value of a data object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XValue {
+    Number(f64),
+    String(String),
+}
+
+/// Deepest nesting of data objects accepted, real files stay well below it.
+const MAX_DEPTH: usize = 64;
+
+/// Largest block MSZIP writes, bounding how much a file can inflate to.
+#[cfg(feature = "mszip")]
+const MSZIP_BLOCK_SIZE: usize = 32 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Value(XValue),
+    OpenBrace,
+    CloseBrace,
+}
+
+/// Reads a .x file.
+pub fn read_x(bytes: &[u8]) -> Result<XFile, RMeshError> {
+    if bytes.len() < 16 || &bytes[0..4] != b"xof " {
+        return Err(RMeshError::InvalidX("missing `xof` header".into()));
+    }
+
+    let format = match &bytes[8..12] {
+        b"txt " => XFormat::Text,
+        b"bin " => XFormat::Binary,
+        b"tzip" => XFormat::CompressedText,
+        b"bzip" => XFormat::CompressedBinary,
+        other => {
+            return Err(RMeshError::InvalidX(format!(
+                "unknown format `{}`",
+                String::from_utf8_lossy(other)
+            )))
+        }
+    };
+    let double_floats = &bytes[12..16] == b"0064";
+
+    let decompressed;
+    let body = match format {
+        XFormat::Text | XFormat::Binary => &bytes[16..],
+        XFormat::CompressedText | XFormat::CompressedBinary => {
+            decompressed = decompress_mszip(&bytes[16..])?;
+            &decompressed[..]
+        }
+    };
+
+    let tokens = match format {
+        XFormat::Text | XFormat::CompressedText => text_tokens(body),
+        XFormat::Binary | XFormat::CompressedBinary => binary_tokens(body, double_floats)?,
+    };
+
+    let mut objects = vec![];
+    let mut pos = 0;
+    while pos < tokens.len() {
+        if let Some(object) = parse_object(&tokens, &mut pos, 0)? {
+            objects.push(object);
+        }
+    }
+
+    Ok(XFile { format, objects })
+}
+
+#[cfg(feature = "mszip")]
+fn decompress_mszip(bytes: &[u8]) -> Result<Vec<u8>, RMeshError> {
+    use miniz_oxide::inflate::core::{decompress, inflate_flags, DecompressorOxide};
+    use miniz_oxide::inflate::TINFLStatus;
+
+    let mut cursor = Cursor::new(bytes);
+    // The stored size includes the 16 byte file header, each block header takes 6 bytes
+    let size: u32 = cursor.read_le()?;
+    let size = (size as usize)
+        .saturating_sub(16)
+        .min(bytes.len().div_ceil(6) * MSZIP_BLOCK_SIZE);
+    let mut out = vec![];
+    out.try_reserve_exact(size).map_err(|_| {
+        RMeshError::InvalidX(format!("can't allocate {size} bytes to inflate into"))
+    })?;
+    out.resize(size, 0);
+    let mut out_pos = 0;
+
+    while (cursor.position() as usize) < bytes.len() && out_pos < out.len() {
+        let _uncompressed_size: u16 = cursor.read_le()?;
+        let compressed_size: u16 = cursor.read_le()?;
+        let signature: [u8; 2] = cursor.read_le()?;
+        if &signature != b"CK" {
+            return Err(RMeshError::InvalidX("missing MSZIP block signature".into()));
+        }
+
+        let start = cursor.position() as usize;
+        let end = (start + compressed_size as usize)
+            .saturating_sub(2)
+            .min(bytes.len());
+
+        // Blocks share one dictionary, so keep earlier output in the same buffer
+        let mut decompressor = DecompressorOxide::new();
+        let (status, _, written) = decompress(
+            &mut decompressor,
+            &bytes[start..end],
+            &mut out,
+            out_pos,
+            inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF,
+        );
+        if status != TINFLStatus::Done {
+            return Err(RMeshError::InvalidX(format!(
+                "failed to inflate MSZIP block: {status:?}"
+            )));
+        }
+
+        out_pos += written;
+        cursor.set_position(end as u64);
+    }
+
+    out.truncate(out_pos);
+    Ok(out)
+}
+
+#[cfg(not(feature = "mszip"))]
+fn decompress_mszip(_bytes: &[u8]) -> Result<Vec<u8>, RMeshError> {
+    Err(RMeshError::InvalidX(
+        "compressed files need the `mszip` feature".into(),
+    ))
+}
+
+fn text_tokens(body: &[u8]) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut pos = 0;
+
+    while let Some(&c) = body.get(pos) {
+        let start = pos;
+        pos += 1;
+        match c {
+            b'{' => tokens.push(Token::OpenBrace),
+            b'}' => tokens.push(Token::CloseBrace),
+            b'#' => pos = skip_line(body, pos),
+            b'/' if body.get(pos) == Some(&b'/') => pos = skip_line(body, pos),
+            // Guids are only needed to identify templates
+            b'<' => pos = skip_past(body, pos, b'>'),
+            b'"' => {
+                let end = skip_past(body, pos, b'"');
+                let string = String::from_utf8_lossy(&body[pos..end.saturating_sub(1)]);
+                tokens.push(Token::Value(XValue::String(string.into_owned())));
+                pos = end;
+            }
+            b'0'..=b'9' | b'-' | b'+' | b'.' => {
+                while body
+                    .get(pos)
+                    .is_some_and(|c| c.is_ascii_digit() || b".eE+-".contains(c))
+                {
+                    pos += 1;
+                }
+                let number = std::str::from_utf8(&body[start..pos])
+                    .ok()
+                    .and_then(|s| s.parse().ok());
+                if let Some(number) = number {
+                    tokens.push(Token::Value(XValue::Number(number)));
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while body
+                    .get(pos)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || b"_-.".contains(c))
+                {
+                    pos += 1;
+                }
+                let name = String::from_utf8_lossy(&body[start..pos]);
+                tokens.push(Token::Name(name.into_owned()));
+            }
+            // Separators and template restrictions carry no data
+            _ => (),
+        }
+    }
+
+    tokens
+}
+
+fn skip_line(body: &[u8], pos: usize) -> usize {
+    skip_past(body, pos, b'\n')
+}
+
+fn skip_past(body: &[u8], pos: usize, end: u8) -> usize {
+    body[pos..]
+        .iter()
+        .position(|c| *c == end)
+        .map_or(body.len(), |offset| pos + offset + 1)
+}
+
+fn binary_tokens(body: &[u8], double_floats: bool) -> BinResult<Vec<Token>> {
+    let mut cursor = Cursor::new(body);
+    let mut tokens = vec![];
+
+    let read_text = |cursor: &mut Cursor<&[u8]>| -> BinResult<String> {
+        let len: u32 = cursor.read_le()?;
+        let bytes: Vec<u8> = (0..len)
+            .map(|_| cursor.read_le())
+            .collect::<BinResult<_>>()?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    };
+
+    while (cursor.position() as usize) < body.len() {
+        let token: u16 = cursor.read_le()?;
+        match token {
+            // Name
+            0x01 => tokens.push(Token::Name(read_text(&mut cursor)?)),
+            // String, followed by its terminator token
+            0x02 => {
+                let string = read_text(&mut cursor)?;
+                let _terminator: u16 = cursor.read_le()?;
+                tokens.push(Token::Value(XValue::String(string)));
+            }
+            // Integer
+            0x03 => {
+                let value: u32 = cursor.read_le()?;
+                tokens.push(Token::Value(XValue::Number(value as f64)));
+            }
+            // Guid
+            0x05 => {
+                let _guid: [u8; 16] = cursor.read_le()?;
+            }
+            // Integer list
+            0x06 => {
+                let count: u32 = cursor.read_le()?;
+                for _ in 0..count {
+                    let value: u32 = cursor.read_le()?;
+                    tokens.push(Token::Value(XValue::Number(value as f64)));
+                }
+            }
+            // Float list
+            0x07 => {
+                let count: u32 = cursor.read_le()?;
+                for _ in 0..count {
+                    let value = if double_floats {
+                        cursor.read_le::<f64>()?
+                    } else {
+                        cursor.read_le::<f32>()? as f64
+                    };
+                    tokens.push(Token::Value(XValue::Number(value)));
+                }
+            }
+            0x0a => tokens.push(Token::OpenBrace),
+            0x0b => tokens.push(Token::CloseBrace),
+            0x1f => tokens.push(Token::Name("template".into())),
+            // Punctuation and template member types carry no data
+            _ => (),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses the object starting at `pos`, nested `depth` objects deep, returning `None` for
+/// templates.
+fn parse_object(
+    tokens: &[Token],
+    pos: &mut usize,
+    depth: usize,
+) -> Result<Option<XObject>, RMeshError> {
+    if depth >= MAX_DEPTH {
+        return Err(RMeshError::InvalidX(format!(
+            "objects nested deeper than {MAX_DEPTH}"
+        )));
+    }
+    let Some(Token::Name(template)) = tokens.get(*pos) else {
+        return Err(RMeshError::InvalidX(format!(
+            "expected a template name, found {:?}",
+            tokens.get(*pos)
+        )));
+    };
+    *pos += 1;
+
+    let mut object = XObject {
+        template: template.clone(),
+        ..Default::default()
+    };
+    if let Some(Token::Name(name)) = tokens.get(*pos) {
+        object.name = Some(name.clone());
+        *pos += 1;
+    }
+    if tokens.get(*pos) != Some(&Token::OpenBrace) {
+        return Err(RMeshError::InvalidX(format!(
+            "expected `{{` after `{}`",
+            object.template
+        )));
+    }
+    *pos += 1;
+
+    if object.template == "template" {
+        let mut depth = 1;
+        while depth > 0 {
+            match tokens.get(*pos) {
+                Some(Token::OpenBrace) => depth += 1,
+                Some(Token::CloseBrace) => depth -= 1,
+                Some(_) => (),
+                None => return Err(RMeshError::InvalidX("unterminated template".into())),
+            }
+            *pos += 1;
+        }
+        return Ok(None);
+    }
+
+    loop {
+        match tokens.get(*pos) {
+            Some(Token::CloseBrace) => {
+                *pos += 1;
+                return Ok(Some(object));
+            }
+            Some(Token::OpenBrace) => {
+                let Some(Token::Name(name)) = tokens.get(*pos + 1) else {
+                    return Err(RMeshError::InvalidX("expected a reference name".into()));
+                };
+                if tokens.get(*pos + 2) != Some(&Token::CloseBrace) {
+                    return Err(RMeshError::InvalidX(format!(
+                        "unterminated reference to `{name}`"
+                    )));
+                }
+                object.references.push(name.clone());
+                *pos += 3;
+            }
+            Some(Token::Name(_)) => object
+                .children
+                .extend(parse_object(tokens, pos, depth + 1)?),
+            Some(Token::Value(value)) => {
+                object.values.push(value.clone());
+                *pos += 1;
+            }
+            None => {
+                return Err(RMeshError::InvalidX(format!(
+                    "unterminated `{}` object",
+                    object.template
+                )))
+            }
+        }
+    }
+}
+
+impl XObject {
+    /// First child instancing `template`.
+    pub fn child(&self, template: &str) -> Option<&XObject> {
+        self.children.iter().find(|c| c.template == template)
+    }
+
+    fn numbers(&self) -> Numbers<'_> {
+        Numbers {
+            object: self,
+            values: self.values.iter(),
+        }
+    }
+}
+
+/// Reads the numeric members of an object in order.
+struct Numbers<'a> {
+    object: &'a XObject,
+    values: std::slice::Iter<'a, XValue>,
+}
+
+impl Numbers<'_> {
+    fn next(&mut self) -> Result<f64, RMeshError> {
+        loop {
+            match self.values.next() {
+                Some(XValue::Number(value)) => return Ok(*value),
+                Some(XValue::String(_)) => (),
+                None => {
+                    return Err(RMeshError::InvalidX(format!(
+                        "`{}` object ends early",
+                        self.object.template
+                    )))
+                }
+            }
+        }
+    }
+
+    fn f32(&mut self) -> Result<f32, RMeshError> {
+        Ok(self.next()? as f32)
+    }
+
+    fn u32(&mut self) -> Result<u32, RMeshError> {
+        Ok(self.next()? as u32)
+    }
+
+    /// Members left, an upper bound on how many more numbers can be read.
+    fn remaining(&self) -> usize {
+        self.values.len()
+    }
+}
+
+/// Diffuse color and texture of a `Material`.
+struct Material {
+    color: [f32; 4],
+    texture: Option<String>,
+}
+
+impl Material {
+    fn from_object(object: &XObject) -> Result<Self, RMeshError> {
+        let mut numbers = object.numbers();
+        let color = [
+            numbers.f32()?,
+            numbers.f32()?,
+            numbers.f32()?,
+            numbers.f32()?,
+        ];
+        let texture = object
+            .child("TextureFilename")
+            .and_then(|t| t.values.first())
+            .and_then(|value| match value {
+                XValue::String(path) => Some(path.clone()),
+                XValue::Number(_) => None,
+            });
+        Ok(Self { color, texture })
+    }
+}
+
+impl XFile {
+    /// Flattens the frame hierarchy into one [`ComplexMesh`] per material, in model space.
+    ///
+    /// Material textures fill the diffuse slot and vertex colors are multiplied with the
+    /// material color. Polygons are triangulated as fans.
+    pub fn to_complex_meshes(&self) -> Result<Vec<ComplexMesh>, RMeshError> {
+        let mut materials = HashMap::new();
+        collect_materials(&self.objects, &mut materials);
+
+        let mut meshes = vec![];
+        for object in &self.objects {
+            collect_meshes(object, &math::IDENTITY, &materials, &mut meshes)?;
+        }
+        Ok(meshes)
+    }
+}
+
+fn collect_materials<'a>(objects: &'a [XObject], materials: &mut HashMap<&'a str, &'a XObject>) {
+    for object in objects {
+        if let (Some(name), "Material") = (&object.name, object.template.as_str()) {
+            materials.insert(name, object);
+        }
+        collect_materials(&object.children, materials);
+    }
+}
+
+fn collect_meshes(
+    object: &XObject,
+    parent: &Mat4,
+    materials: &HashMap<&str, &XObject>,
+    meshes: &mut Vec<ComplexMesh>,
+) -> Result<(), RMeshError> {
+    match object.template.as_str() {
+        "Frame" => {
+            let matrix = match object.child("FrameTransformMatrix") {
+                Some(transform) => {
+                    let mut numbers = transform.numbers();
+                    let mut local = math::IDENTITY;
+                    // Stored row by row for row vectors, which is our column-major layout
+                    for value in local.iter_mut().flatten() {
+                        *value = numbers.f32()?;
+                    }
+                    math::mul_mat4(parent, &local)
+                }
+                None => *parent,
+            };
+            for child in &object.children {
+                collect_meshes(child, &matrix, materials, meshes)?;
+            }
+        }
+        "Mesh" => {
+            for mut mesh in mesh_to_complex_meshes(object, materials)? {
+                mesh.transform(parent);
+                meshes.push(mesh);
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+fn mesh_to_complex_meshes(
+    object: &XObject,
+    materials: &HashMap<&str, &XObject>,
+) -> Result<Vec<ComplexMesh>, RMeshError> {
+    let mut numbers = object.numbers();
+    let vertex_count = numbers.u32()? as usize;
+    let positions = (0..vertex_count)
+        .map(|_| Ok([numbers.f32()?, numbers.f32()?, numbers.f32()?]))
+        .collect::<Result<Vec<_>, RMeshError>>()?;
+
+    let face_count = numbers.u32()? as usize;
+    // Every face takes at least its corner count, so a bogus count can't allocate more
+    let mut faces = Vec::with_capacity(face_count.min(numbers.remaining()));
+    for _ in 0..face_count {
+        let corner_count = numbers.u32()?;
+        let face = (0..corner_count)
+            .map(|_| numbers.u32())
+            .collect::<Result<Vec<_>, _>>()?;
+        faces.push(face);
+    }
+
+    let mut tex_coords = vec![[0.0; 2]; vertex_count];
+    if let Some(coords) = object.child("MeshTextureCoords") {
+        let mut numbers = coords.numbers();
+        let count = numbers.u32()? as usize;
+        for i in 0..count {
+            let uv = [numbers.f32()?, numbers.f32()?];
+            if let Some(tex_coord) = tex_coords.get_mut(i) {
+                *tex_coord = uv;
+            }
+        }
+    }
+
+    let mut colors = vec![[1.0; 4]; vertex_count];
+    if let Some(vertex_colors) = object.child("MeshVertexColors") {
+        let mut numbers = vertex_colors.numbers();
+        let count = numbers.u32()?;
+        for _ in 0..count {
+            let index = numbers.u32()? as usize;
+            let color = [
+                numbers.f32()?,
+                numbers.f32()?,
+                numbers.f32()?,
+                numbers.f32()?,
+            ];
+            if let Some(vertex_color) = colors.get_mut(index) {
+                *vertex_color = color;
+            }
+        }
+    }
+
+    // Faces index materials listed inline or referenced by name
+    let mut face_materials = vec![0; faces.len()];
+    let mut mesh_materials = vec![];
+    if let Some(list) = object.child("MeshMaterialList") {
+        let mut numbers = list.numbers();
+        let _material_count = numbers.u32()?;
+        let index_count = numbers.u32()? as usize;
+        for i in 0..index_count {
+            let material = numbers.u32()? as usize;
+            if let Some(face_material) = face_materials.get_mut(i) {
+                *face_material = material;
+            }
+        }
+        // Some exporters write a single index for all faces
+        if index_count == 1 {
+            let material = face_materials.first().copied().unwrap_or_default();
+            face_materials.fill(material);
+        }
+
+        for name in &list.references {
+            if let Some(material) = materials.get(name.as_str()) {
+                mesh_materials.push(Material::from_object(material)?);
+            }
+        }
+        for child in list.children.iter().filter(|c| c.template == "Material") {
+            mesh_materials.push(Material::from_object(child)?);
+        }
+    }
+
+    let group_count = mesh_materials.len().max(1);
+    let mut meshes = Vec::with_capacity(group_count);
+    for group in 0..group_count {
+        let material = mesh_materials.get(group);
+        let material_color = material.map_or([1.0; 4], |m| m.color);
+
+        let mut mesh = ComplexMesh::default();
+        if let Some(path) = material.and_then(|m| m.texture.as_ref()) {
+            mesh.textures[1] = Texture {
+                blend_type: if material_color[3] < 1.0 {
+                    TextureBlendType::Transparent
+                } else {
+                    TextureBlendType::Visible
+                },
+                path: Some(path.as_str().into()),
+            };
+        }
+
+        let mut remap = vec![u32::MAX; vertex_count];
+        for (face, _) in faces
+            .iter()
+            .zip(&face_materials)
+            .filter(|(_, m)| **m == group || (group == 0 && **m >= group_count))
+        {
+            if face.iter().any(|i| *i as usize >= vertex_count) {
+                continue;
+            }
+
+            let face: Vec<u32> = face
+                .iter()
+                .map(|&i| {
+                    let i = i as usize;
+                    if remap[i] == u32::MAX {
+                        remap[i] = mesh.vertices.len() as u32;
+                        mesh.vertices.push(Vertex {
+                            position: positions[i],
+                            tex_coords: [tex_coords[i], [0.0; 2]],
+                            color: [0, 1, 2].map(|c| {
+                                (colors[i][c] * material_color[c] * 255.0)
+                                    .round()
+                                    .clamp(0.0, 255.0) as u8
+                            }),
+                        });
+                    }
+                    remap[i]
+                })
+                .collect();
+
+            for k in 1..face.len().saturating_sub(1) {
+                mesh.triangles.push([face[0], face[k], face[k + 1]]);
+            }
+        }
+
+        if !mesh.triangles.is_empty() {
+            meshes.push(mesh);
+        }
+    }
+
+    Ok(meshes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD: &str = "
+        // A unit quad, moved up by its frame
+        Frame Root {
+            FrameTransformMatrix {
+                1.0, 0.0, 0.0, 0.0,
+                0.0, 1.0, 0.0, 0.0,
+                0.0, 0.0, 1.0, 0.0,
+                0.0, 5.0, 0.0, 1.0;;
+            }
+            Mesh Quad {
+                4;
+                0.0; 0.0; 0.0;,
+                1.0; 0.0; 0.0;,
+                1.0; 0.0; 1.0;,
+                0.0; 0.0; 1.0;;
+                1;
+                4; 0, 1, 2, 3;;
+            }
+        }
+    ";
+
+    fn text(body: &str) -> Vec<u8> {
+        [b"xof 0303txt 0032".as_slice(), body.as_bytes()].concat()
+    }
+
+    #[test]
+    fn text_mesh_is_placed_and_triangulated() {
+        let file = read_x(&text(QUAD)).unwrap();
+        let meshes = file.to_complex_meshes().unwrap();
+
+        assert_eq!(file.format, XFormat::Text);
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].triangles, [[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(meshes[0].vertices[2].position, [1.0, 5.0, 1.0]);
+    }
+
+    #[test]
+    fn deep_nesting_fails_instead_of_overflowing() {
+        let body = "Frame {".repeat(MAX_DEPTH + 1) + &"}".repeat(MAX_DEPTH + 1);
+
+        assert!(matches!(
+            read_x(&text(&body)),
+            Err(RMeshError::InvalidX(message)) if message.contains("nested")
+        ));
+        let shallow = "Frame {".repeat(MAX_DEPTH) + &"}".repeat(MAX_DEPTH);
+        assert!(read_x(&text(&shallow)).is_ok());
+    }
+
+    #[test]
+    fn huge_counts_fail_without_allocating() {
+        let body = "Mesh { 4000000000; 0.0; 0.0; 0.0;; }";
+
+        assert!(read_x(&text(body)).unwrap().to_complex_meshes().is_err());
+        assert!(read_x(b"xof 0303abc 0032").is_err());
+    }
+
+    #[cfg(feature = "mszip")]
+    #[test]
+    fn mszip_matches_text() {
+        let body = QUAD.as_bytes();
+        let deflated = miniz_oxide::deflate::compress_to_vec(body, 6);
+        let mut bytes = b"xof 0303tzip0032".to_vec();
+        // A stored size far beyond the data must not be allocated up front
+        bytes.extend(u32::MAX.to_le_bytes());
+        bytes.extend((body.len() as u16).to_le_bytes());
+        bytes.extend((deflated.len() as u16 + 2).to_le_bytes());
+        bytes.extend(b"CK");
+        bytes.extend(deflated);
+
+        let file = read_x(&bytes).unwrap();
+
+        assert_eq!(file.format, XFormat::CompressedText);
+        let text_meshes = read_x(&text(QUAD)).unwrap().to_complex_meshes().unwrap();
+        let meshes = file.to_complex_meshes().unwrap();
+        assert_eq!(meshes[0].triangles, text_meshes[0].triangles);
+        assert_eq!(meshes[0].vertices.len(), 4);
+    }
+}
@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use rmesh::{Header, PlyFormat};
+
+/// Writes `header` in the format picked by the extension of `output`.
+pub fn convert(header: &Header, output: &Path) -> anyhow::Result<()> {
+    let extension = output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "gltf" => write(output, crate::gltf::to_gltf(header)?.into_bytes()),
+        "glb" => write(output, crate::gltf::to_glb(header)?),
+        "obj" => {
+            let mtl = output.with_extension("mtl");
+            let mtl_name = mtl
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let export = header.to_obj(&mtl_name);
+            write(output, export.obj.into_bytes())?;
+            write(&mtl, export.mtl.into_bytes())
+        }
+        "ply" => write(output, header.to_ply(PlyFormat::BinaryLittleEndian)),
+        "json" => write(
+            output,
+            serde_json::to_vec_pretty(&crate::json::to_json(header))?,
+        ),
+        "map" => {
+            let export = header.to_map();
+            write(output, export.map.into_bytes())?;
+            if let Some(reference) = export.reference {
+                let obj = output.with_file_name("reference.obj");
+                write(&obj, reference.obj.into_bytes())?;
+                write(&obj.with_extension("mtl"), reference.mtl.into_bytes())?;
+            }
+            Ok(())
+        }
+        "rmesh" => write(output, rmesh::write_rmesh(header)?),
+        _ => bail!("unsupported output format {:?}", output.display()),
+    }
+}
+
+fn write(path: &Path, bytes: Vec<u8>) -> anyhow::Result<()> {
+    fs::write(path, bytes).with_context(|| format!("writing {}", path.display()))
+}
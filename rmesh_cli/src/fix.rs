@@ -0,0 +1,198 @@
+use std::fmt;
+
+use rmesh::{FixedLengthString, Header, RMeshError, SimpleMesh, TextureBlendType, WeldOptions};
+
+use crate::math::{cross, sub};
+
+/// Counts of everything [`fix`] changed.
+#[derive(Debug, Default)]
+pub struct Repairs {
+    pub clamped_indices: usize,
+    pub welded_vertices: usize,
+    pub degenerate_triangles: usize,
+    pub texture_paths: usize,
+}
+
+impl Repairs {
+    pub fn is_empty(&self) -> bool {
+        self.clamped_indices == 0
+            && self.welded_vertices == 0
+            && self.degenerate_triangles == 0
+            && self.texture_paths == 0
+    }
+}
+
+impl fmt::Display for Repairs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Clamped indices:      {}", self.clamped_indices)?;
+        writeln!(f, "Welded vertices:      {}", self.welded_vertices)?;
+        writeln!(f, "Degenerate triangles: {}", self.degenerate_triangles)?;
+        write!(f, "Texture paths:        {}", self.texture_paths)
+    }
+}
+
+/// Applies every automatic repair to the room.
+///
+/// Out of range indices are clamped first so the triangles they collapse get removed
+/// together with the ones welding collapses. Meshes with triangles but no vertices can't be
+/// repaired and fail.
+pub fn fix(header: &mut Header, weld_tolerance: f32) -> Result<Repairs, RMeshError> {
+    let mut repairs = Repairs::default();
+
+    for mesh in &mut header.meshes {
+        repairs.clamped_indices += clamp_indices(&mut mesh.triangles, mesh.vertices.len());
+        repairs.welded_vertices += mesh.weld_with(&WeldOptions {
+            tolerance: weld_tolerance,
+            ..Default::default()
+        })?;
+        let positions = mesh.positions();
+        repairs.degenerate_triangles += remove_degenerate(&mut mesh.triangles, &positions);
+
+        for texture in &mut mesh.textures {
+            let Some(path) = &texture.path else {
+                continue;
+            };
+            let original = String::from(path);
+            let normalized = normalize_path(&original);
+            if normalized.is_empty() {
+                texture.path = None;
+                texture.blend_type = TextureBlendType::None;
+            } else if normalized != original {
+                texture.path = Some(FixedLengthString::from(normalized));
+            } else {
+                continue;
+            }
+            repairs.texture_paths += 1;
+        }
+    }
+
+    let simple_meshes = header.colliders.iter_mut().chain(
+        header
+            .trigger_boxes
+            .iter_mut()
+            .flat_map(|trigger_box| &mut trigger_box.meshes),
+    );
+    for mesh in simple_meshes {
+        repairs.clamped_indices += clamp_indices(&mut mesh.triangles, mesh.vertices.len());
+        repairs.degenerate_triangles += remove_degenerate(&mut mesh.triangles, &mesh.vertices);
+        sync_counts(mesh);
+    }
+
+    Ok(repairs)
+}
+
+/// Points out of range indices at the last vertex.
+fn clamp_indices(triangles: &mut [[u32; 3]], vertex_count: usize) -> usize {
+    let last = vertex_count.saturating_sub(1) as u32;
+    let mut clamped = 0;
+    for index in triangles.iter_mut().flatten() {
+        if *index as usize >= vertex_count {
+            *index = last;
+            clamped += 1;
+        }
+    }
+    clamped
+}
+
+/// Drops triangles with repeated indices or no area.
+fn remove_degenerate(triangles: &mut Vec<[u32; 3]>, positions: &[[f32; 3]]) -> usize {
+    let before = triangles.len();
+    triangles.retain(|&[a, b, c]| {
+        let (Some(pa), Some(pb), Some(pc)) = (
+            positions.get(a as usize),
+            positions.get(b as usize),
+            positions.get(c as usize),
+        ) else {
+            return false;
+        };
+        a != b && b != c && a != c && cross(sub(*pb, *pa), sub(*pc, *pa)) != [0.0; 3]
+    });
+    before - triangles.len()
+}
+
+fn sync_counts(mesh: &mut SimpleMesh) {
+    mesh.vertex_count = mesh.vertices.len() as u32;
+    mesh.triangle_count = mesh.triangles.len() as u32;
+}
+
+/// Trims whitespace, uses `/` separators and drops empty and `.` components.
+fn normalize_path(path: &str) -> String {
+    path.trim()
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{ComplexMesh, Texture, Vertex};
+
+    #[test]
+    fn paths_are_normalized() {
+        assert_eq!(normalize_path(" GFX\\map\\./wall.jpg "), "GFX/map/wall.jpg");
+        assert_eq!(normalize_path("GFX//map/"), "GFX/map");
+        assert_eq!(normalize_path(" . "), "");
+    }
+
+    #[test]
+    fn clamped_triangles_are_removed_as_degenerate() {
+        let mut triangles = vec![[0, 1, 2], [0, 1, 9]];
+        assert_eq!(clamp_indices(&mut triangles, 3), 1);
+        assert_eq!(triangles, [[0, 1, 2], [0, 1, 2]]);
+
+        let mut triangles = vec![[0, 1, 2], [0, 2, 2], [0, 1, 3]];
+        let positions = [[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [2.0, 0.0, 0.0]];
+        assert_eq!(remove_degenerate(&mut triangles, &positions), 2);
+        assert_eq!(triangles, [[0, 1, 2]]);
+    }
+
+    #[test]
+    fn fix_repairs_meshes_and_colliders() {
+        let corners = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        let mut header = Header {
+            meshes: vec![ComplexMesh {
+                textures: [
+                    Texture {
+                        blend_type: TextureBlendType::Lightmap,
+                        path: Some(" ".into()),
+                    },
+                    Texture {
+                        blend_type: TextureBlendType::Visible,
+                        path: Some("GFX\\wall.jpg".into()),
+                    },
+                ],
+                // The first corner twice, welded into one
+                vertices: [corners[0], corners[0], corners[1], corners[2]]
+                    .map(|position| Vertex {
+                        position,
+                        ..Default::default()
+                    })
+                    .to_vec(),
+                triangles: vec![[0, 2, 3], [1, 2, 3], [0, 2, 7]],
+                ..Default::default()
+            }],
+            colliders: vec![SimpleMesh::new(
+                corners.to_vec(),
+                vec![[0, 1, 2], [1, 2, 4]],
+            )],
+            ..Default::default()
+        };
+
+        let repairs = fix(&mut header, 0.0).unwrap();
+
+        assert_eq!(repairs.clamped_indices, 2);
+        assert_eq!(repairs.welded_vertices, 1);
+        assert_eq!(repairs.degenerate_triangles, 1);
+        assert_eq!(repairs.texture_paths, 2);
+        let mesh = &header.meshes[0];
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.textures[0].path, None);
+        assert_eq!(mesh.textures[0].blend_type, TextureBlendType::None);
+        assert_eq!(mesh.textures[1].path, Some("GFX/wall.jpg".into()));
+        let collider = &header.colliders[0];
+        assert_eq!(collider.triangles, [[0, 1, 2]]);
+        assert_eq!(collider.triangle_count, 1);
+    }
+}
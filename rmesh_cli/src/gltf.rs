@@ -0,0 +1,297 @@
+//! Minimal glTF 2.0 writer.
+//!
+//! Every visible mesh becomes a node with one primitive. The diffuse texture is the base
+//! color, glTF has no lightmap slot so lightmap coordinates are kept in `TEXCOORD_1` only.
+
+use anyhow::bail;
+use rmesh::color::color_to_linear;
+use rmesh::convert::CoordinateTransform;
+use rmesh::{ExtMesh, Header, TextureBlendType};
+use serde_json::{json, Value};
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+/// A `.gltf` file with the buffer embedded as a data URI.
+pub fn to_gltf(header: &Header) -> anyhow::Result<String> {
+    let (mut document, buffer) = build(header)?;
+    document["buffers"] = json!([{
+        "byteLength": buffer.len(),
+        "uri": format!("data:application/octet-stream;base64,{}", base64(&buffer)),
+    }]);
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// A binary `.glb` file.
+pub fn to_glb(header: &Header) -> anyhow::Result<Vec<u8>> {
+    let (mut document, mut buffer) = build(header)?;
+    document["buffers"] = json!([{ "byteLength": buffer.len() }]);
+
+    let mut json = serde_json::to_vec(&document)?;
+    json.resize(json.len().next_multiple_of(4), b' ');
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+    let mut glb = vec![];
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&((12 + 8 + json.len() + 8 + buffer.len()) as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&buffer);
+    Ok(glb)
+}
+
+/// Accessors and buffer views appended to one binary buffer.
+#[derive(Default)]
+struct Builder {
+    buffer: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Builder {
+    fn push(&mut self, bytes: &[u8], target: u32, accessor: Value) -> usize {
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+
+        let mut accessor = accessor;
+        accessor["bufferView"] = json!(self.views.len() - 1);
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn floats<const N: usize>(&mut self, data: &[[f32; N]], kind: &str) -> usize {
+        let bytes: Vec<u8> = data
+            .iter()
+            .flatten()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        let accessor = json!({
+            "componentType": FLOAT,
+            "count": data.len(),
+            "type": kind,
+        });
+        self.push(&bytes, ARRAY_BUFFER, accessor)
+    }
+}
+
+fn build(header: &Header) -> anyhow::Result<(Value, Vec<u8>)> {
+    // glTF is right-handed with Y up, in meters
+    let mut header = header.clone();
+    CoordinateTransform::BLITZ3D_TO_RIGHT_HANDED.apply(&mut header);
+
+    let mut builder = Builder::default();
+    let mut images: Vec<String> = vec![];
+    let mut materials = vec![];
+    let mut meshes = vec![];
+
+    for (index, mesh) in header.meshes.iter().enumerate() {
+        if mesh.is_invisible() || mesh.triangles.is_empty() {
+            continue;
+        }
+        if let Some(Err(error)) = mesh.triangles_resolved().find(Result::is_err) {
+            bail!("mesh {index}: {error}");
+        }
+
+        let arrays = mesh.vertex_arrays();
+        let positions = arrays.positions();
+        let bounds = mesh.bounding_box();
+        let position = builder.push(
+            &positions
+                .iter()
+                .flatten()
+                .flat_map(|c| c.to_le_bytes())
+                .collect::<Vec<_>>(),
+            ARRAY_BUFFER,
+            json!({
+                "componentType": FLOAT,
+                "count": positions.len(),
+                "type": "VEC3",
+                "min": bounds.min,
+                "max": bounds.max,
+            }),
+        );
+        let normal = builder.floats(&mesh.calculate_normals(), "VEC3");
+        let diffuse = builder.floats(arrays.uv0(), "VEC2");
+        let lightmap = builder.floats(arrays.uv1(), "VEC2");
+        let colors: Vec<_> = mesh
+            .vertices
+            .iter()
+            .map(|v| color_to_linear(v.color))
+            .collect();
+        let color = builder.floats(&colors, "VEC4");
+
+        let indices: Vec<u8> = mesh
+            .triangles
+            .iter()
+            .flatten()
+            .flat_map(|i| i.to_le_bytes())
+            .collect();
+        let indices = builder.push(
+            &indices,
+            ELEMENT_ARRAY_BUFFER,
+            json!({
+                "componentType": UNSIGNED_INT,
+                "count": mesh.triangles.len() * 3,
+                "type": "SCALAR",
+            }),
+        );
+
+        let texture = &mesh.textures[1];
+        let mut material = json!({
+            "name": format!("mesh{index}"),
+            "pbrMetallicRoughness": { "metallicFactor": 0.0, "roughnessFactor": 1.0 },
+        });
+        if let Some(path) = &texture.path {
+            let path = String::from(path).replace('\\', "/");
+            let image = images.iter().position(|p| *p == path).unwrap_or_else(|| {
+                images.push(path);
+                images.len() - 1
+            });
+            material["pbrMetallicRoughness"]["baseColorTexture"] = json!({ "index": image });
+        }
+        if texture.blend_type == TextureBlendType::Transparent {
+            material["alphaMode"] = json!("BLEND");
+        }
+        materials.push(material);
+
+        meshes.push(json!({
+            "name": format!("mesh{index}"),
+            "primitives": [{
+                "attributes": {
+                    "POSITION": position,
+                    "NORMAL": normal,
+                    "TEXCOORD_0": diffuse,
+                    "TEXCOORD_1": lightmap,
+                    "COLOR_0": color,
+                },
+                "indices": indices,
+                "material": materials.len() - 1,
+            }],
+        }));
+    }
+
+    let nodes: Vec<_> = (0..meshes.len()).map(|i| json!({ "mesh": i })).collect();
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": "rmesh" },
+        "scene": 0,
+        "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+        "nodes": nodes,
+        "meshes": meshes,
+        "materials": materials,
+        "bufferViews": builder.views,
+        "accessors": builder.accessors,
+    });
+    if !images.is_empty() {
+        document["samplers"] = json!([{}]);
+        document["images"] = images.iter().map(|uri| json!({ "uri": uri })).collect();
+        document["textures"] = (0..images.len())
+            .map(|i| json!({ "source": i, "sampler": 0 }))
+            .collect();
+    }
+    Ok((document, builder.buffer))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [0, 1, 2].map(|i| chunk.get(i).copied().unwrap_or(0) as u32);
+        let n = (b[0] << 16) | (b[1] << 8) | b[2];
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{ComplexMesh, Texture, Vertex};
+
+    fn room(blend_type: TextureBlendType) -> Header {
+        Header {
+            meshes: vec![ComplexMesh {
+                textures: [
+                    Texture::default(),
+                    Texture {
+                        blend_type,
+                        path: Some("GFX\\wall.jpg".into()),
+                    },
+                ],
+                vertices: (0..3)
+                    .map(|i| Vertex {
+                        position: [i as f32, (i % 2) as f32, 0.0],
+                        ..Default::default()
+                    })
+                    .collect(),
+                triangles: vec![[0, 1, 2]],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn base64_pads_partial_chunks() {
+        assert_eq!(base64(b"rmesh"), "cm1lc2g=");
+        assert_eq!(base64(b"rm"), "cm0=");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    fn visible_meshes_become_textured_nodes() {
+        let (document, buffer) = build(&room(TextureBlendType::Transparent)).unwrap();
+
+        assert_eq!(document["nodes"], json!([{ "mesh": 0 }]));
+        assert_eq!(document["images"], json!([{ "uri": "GFX/wall.jpg" }]));
+        assert_eq!(document["materials"][0]["alphaMode"], "BLEND");
+        let byte_length: u64 = document["bufferViews"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|view| view["byteLength"].as_u64().unwrap())
+            .sum();
+        assert_eq!(byte_length, buffer.len() as u64);
+
+        let (document, _) = build(&room(TextureBlendType::None)).unwrap();
+        assert_eq!(document["nodes"], json!([]));
+    }
+
+    #[test]
+    fn glb_chunks_are_aligned() {
+        let glb = to_glb(&room(TextureBlendType::Visible)).unwrap();
+        let word = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(word(8) as usize, glb.len());
+        let json_len = word(12) as usize;
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(&glb[24 + json_len..28 + json_len], b"BIN\0");
+    }
+
+    #[test]
+    fn out_of_range_triangles_fail() {
+        let mut header = room(TextureBlendType::Visible);
+        header.meshes[0].triangles.push([0, 1, 7]);
+
+        assert!(build(&header).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use rmesh::{EntityType, Header};
+
+pub fn inspect(header: &Header) {
+    let stats = header.stats();
+    println!("Variant:       {:?}", header.variant);
+    println!("Meshes:        {}", stats.mesh_count);
+    println!("Vertices:      {}", stats.vertex_count);
+    println!("Triangles:     {}", stats.triangle_count);
+    println!("Surface area:  {:.1}", stats.surface_area);
+    println!("Colliders:     {}", stats.collider_count);
+    println!("Trigger boxes: {}", stats.trigger_box_count);
+    if let Some(bounds) = stats.bounds {
+        println!("Bounds:        {:?} to {:?}", bounds.min, bounds.max);
+    }
+    if !header.extra.is_empty() {
+        println!("Trailing data: {} bytes", header.extra.len());
+    }
+
+    println!();
+    println!("Textures:");
+    for texture in &stats.textures {
+        println!("  {texture}");
+    }
+
+    println!();
+    println!("Entities:");
+    for (name, count) in &stats.entity_counts {
+        println!("  {name}: {count}");
+    }
+    for (index, entity) in header.entity_types().enumerate() {
+        let [x, y, z] = entity.position();
+        let detail = match entity {
+            EntityType::Screen(screen) => String::from(&screen.name),
+            EntityType::Model(model) => String::from(&model.name),
+            EntityType::Light(light) => format!("range {}", light.range),
+            EntityType::SpotLight(light) => format!("range {}", light.range),
+            EntityType::SoundEmitter(sound) => format!("sound {}", sound.idk0),
+            _ => String::new(),
+        };
+        println!("  [{index}] {} at ({x}, {y}, {z}) {detail}", entity.name());
+    }
+
+    if !header.trigger_boxes.is_empty() {
+        println!();
+        println!("Trigger boxes:");
+        for trigger_box in &header.trigger_boxes {
+            println!("  {}", String::from(&trigger_box.name));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use rmesh::{EntityType, Header, SimpleMesh, Texture, TextureBlendType};
+use serde_json::{json, Value};
+
+/// Dumps the whole room as JSON.
+pub fn to_json(header: &Header) -> Value {
+    json!({
+        "variant": format!("{:?}", header.variant),
+        "meshes": header.meshes.iter().map(|mesh| json!({
+            "textures": mesh.textures.iter().map(texture).collect::<Vec<_>>(),
+            "vertices": mesh.vertices.iter().map(|vertex| json!({
+                "position": vertex.position,
+                "tex_coords": vertex.tex_coords,
+                "color": vertex.color,
+            })).collect::<Vec<_>>(),
+            "triangles": mesh.triangles,
+        })).collect::<Vec<_>>(),
+        "colliders": header.colliders.iter().map(simple_mesh).collect::<Vec<_>>(),
+        "trigger_boxes": header.trigger_boxes.iter().map(|trigger_box| json!({
+            "name": String::from(&trigger_box.name),
+            "meshes": trigger_box.meshes.iter().map(simple_mesh).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+        "entities": header.entity_types().map(entity).collect::<Vec<_>>(),
+    })
+}
+
+fn texture(texture: &Texture) -> Value {
+    let blend_type = match texture.blend_type {
+        TextureBlendType::None => "none",
+        TextureBlendType::Visible => "visible",
+        TextureBlendType::Lightmap => "lightmap",
+        TextureBlendType::Transparent => "transparent",
+    };
+    json!({
+        "blend_type": blend_type,
+        "path": texture.path.as_ref().map(String::from),
+    })
+}
+
+fn simple_mesh(mesh: &SimpleMesh) -> Value {
+    json!({
+        "vertices": mesh.vertices,
+        "triangles": mesh.triangles,
+    })
+}
+
+fn entity(entity: &EntityType) -> Value {
+    let mut value = match entity {
+        EntityType::Screen(screen) => json!({
+            "name": String::from(&screen.name),
+        }),
+        EntityType::WayPoint(_) => json!({}),
+        EntityType::Light(light) => json!({
+            "range": light.range,
+            "color": light.color.0,
+            "intensity": light.intensity,
+        }),
+        EntityType::SpotLight(light) => json!({
+            "range": light.range,
+            "color": light.color.0,
+            "intensity": light.intensity,
+            "angles": light.angles.0,
+            "inner_cone_angle": light.inner_cone_angle,
+            "outer_cone_angle": light.outer_cone_angle,
+        }),
+        EntityType::SoundEmitter(sound) => json!({
+            "sound": sound.idk0,
+            "range": sound.idk1,
+        }),
+        EntityType::PlayerStart(start) => json!({
+            "angles": start.angles.0,
+        }),
+        EntityType::Model(model) => json!({
+            "name": String::from(&model.name),
+            "rotation": model.rotation,
+            "scale": model.scale,
+        }),
+    };
+    value["type"] = entity.name().into();
+    value["position"] = json!(entity.position());
+    value
+}
@@ -0,0 +1,210 @@
+mod convert;
+mod fix;
+mod gltf;
+mod inspect;
+mod json;
+mod math;
+mod optimize;
+mod scan;
+mod thumbnail;
+mod transform;
+mod validate;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use rmesh::{read_rmesh, write_rmesh, Header};
+
+#[derive(Parser)]
+#[command(
+    name = "rmesh",
+    version,
+    about = "Inspect, validate and convert rmesh files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print statistics, textures and entities of a room
+    Inspect { file: PathBuf },
+    /// Check triangle indices, geometry and referenced files
+    Validate {
+        file: PathBuf,
+        /// Directory prop models are looked up in, defaults to `props` next to the room
+        #[arg(long)]
+        props: Option<PathBuf>,
+    },
+    /// Convert a room to glTF, OBJ, PLY, JSON or a Valve 220 map, picked by extension
+    Convert { input: PathBuf, output: PathBuf },
+    /// Repair broken geometry and texture paths, rewriting the file
+    Fix {
+        file: PathBuf,
+        /// Write the repaired room here instead of overwriting the input
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Maximum distance between vertices merged by welding
+        #[arg(long, default_value_t = 0.0)]
+        weld_tolerance: f32,
+    },
+    /// Reorder triangles and vertices of every mesh for the GPU vertex cache and fetch
+    Optimize {
+        input: PathBuf,
+        /// Write the optimized room here instead of overwriting the input
+        output: Option<PathBuf>,
+    },
+    /// Validate every room of a game or mod directory and report problems as JSON
+    Scan {
+        dir: PathBuf,
+        /// Write the report here instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Render a preview image of the room without a GPU
+    Thumbnail {
+        input: PathBuf,
+        output: PathBuf,
+        #[arg(long, default_value_t = 512)]
+        width: u32,
+        #[arg(long, default_value_t = 512)]
+        height: u32,
+    },
+    /// Scale, rotate and move all geometry and entities, writing a new file
+    Transform {
+        input: PathBuf,
+        output: PathBuf,
+        /// Uniform factor, or `x,y,z` factors
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "1",
+            allow_negative_numbers = true
+        )]
+        scale: Vec<f32>,
+        /// Offset `x,y,z` applied last
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "0,0,0",
+            allow_negative_numbers = true
+        )]
+        translate: Vec<f32>,
+        /// Rotation around the vertical axis in degrees
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        rotate_y: f32,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> anyhow::Result<ExitCode> {
+    match cli.command {
+        Command::Inspect { file } => {
+            inspect::inspect(&load(&file)?);
+        }
+        Command::Validate { file, props } => {
+            let header = load(&file)?;
+            let props = props.unwrap_or_else(|| rmesh::resolve::props_dir(&file));
+            let issues = validate::validate(&header, room_dir(&file), &props, None);
+            for issue in &issues {
+                println!("{issue}");
+            }
+            if issues.iter().any(|issue| issue.is_error()) {
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("{}: ok", file.display());
+        }
+        Command::Convert { input, output } => {
+            convert::convert(&load(&input)?, &output)?;
+        }
+        Command::Fix {
+            file,
+            output,
+            weld_tolerance,
+        } => {
+            let mut header = load(&file)?;
+            let repairs = fix::fix(&mut header, weld_tolerance)?;
+            println!("{repairs}");
+            if repairs.is_empty() && output.is_none() {
+                println!("{}: nothing to fix", file.display());
+            } else {
+                let output = output.unwrap_or(file);
+                std::fs::write(&output, write_rmesh(&header)?)
+                    .with_context(|| format!("writing {}", output.display()))?;
+            }
+        }
+        Command::Optimize { input, output } => {
+            let mut header = load(&input)?;
+            println!("{}", optimize::optimize(&mut header));
+            let output = output.unwrap_or(input);
+            std::fs::write(&output, write_rmesh(&header)?)
+                .with_context(|| format!("writing {}", output.display()))?;
+        }
+        Command::Scan { dir, output } => {
+            let (report, failed) = scan::scan(&dir)?;
+            let report = serde_json::to_string_pretty(&report)?;
+            match output {
+                Some(output) => std::fs::write(&output, report)
+                    .with_context(|| format!("writing {}", output.display()))?,
+                None => println!("{report}"),
+            }
+            if failed {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Thumbnail {
+            input,
+            output,
+            width,
+            height,
+        } => {
+            let header = load(&input)?;
+            thumbnail::render(&header, room_dir(&input), width, height)
+                .save(&output)
+                .with_context(|| format!("writing {}", output.display()))?;
+        }
+        Command::Transform {
+            input,
+            output,
+            scale,
+            translate,
+            rotate_y,
+        } => {
+            let mut header = load(&input)?;
+            let scale = match scale[..] {
+                [s] => [s; 3],
+                [x, y, z] => [x, y, z],
+                _ => anyhow::bail!("--scale takes one or three values"),
+            };
+            let [x, y, z] = translate[..] else {
+                anyhow::bail!("--translate takes three values");
+            };
+            header.transform(&transform::matrix(scale, rotate_y, [x, y, z]));
+            std::fs::write(&output, write_rmesh(&header)?)
+                .with_context(|| format!("writing {}", output.display()))?;
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn load(path: &Path) -> anyhow::Result<Header> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    read_rmesh(&bytes).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Directory room-relative paths are resolved against.
+fn room_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
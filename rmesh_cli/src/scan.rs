@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use serde_json::{json, Value};
+
+use rmesh::resolve::{self, find_path};
+
+use crate::validate;
+
+/// Validates every room under `GFX/map` of a game or mod directory.
+///
+/// Returns the JSON report and whether any room had errors.
+pub fn scan(root: &Path) -> anyhow::Result<(Value, bool)> {
+    let Some(map_dir) = find_path(&root.join("GFX/map")).filter(|path| path.is_dir()) else {
+        bail!("{} has no GFX/map directory", root.display());
+    };
+    // The game loads props from one shared directory
+    let props_dir = find_path(&map_dir.join("Props")).unwrap_or_else(|| map_dir.join("Props"));
+    let screens_dir = root.join(resolve::SCREENS_DIR);
+
+    let mut files = vec![];
+    collect_rmesh_files(&map_dir, &mut files)
+        .with_context(|| format!("reading {}", map_dir.display()))?;
+    files.sort();
+
+    let mut rooms = vec![];
+    let mut parse_failures = vec![];
+    let mut missing_assets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut failed = false;
+
+    for file in &files {
+        let name = relative(root, file);
+        let header = match rmesh::read_rmesh_mmap(file) {
+            Ok(header) => header,
+            Err(error) => {
+                failed = true;
+                let error = strip_ansi(&error.to_string());
+                parse_failures.push(json!({ "path": name, "error": error.trim() }));
+                continue;
+            }
+        };
+
+        let room_dir = file.parent().unwrap_or(root);
+        let missing = validate::missing_files(&header, room_dir, &props_dir, Some(&screens_dir));
+        for (_, path) in &missing {
+            missing_assets
+                .entry(relative(root, path))
+                .or_default()
+                .push(name.clone());
+        }
+
+        let issues = validate::validate(&header, room_dir, &props_dir, Some(&screens_dir));
+        failed |= issues.iter().any(|issue| issue.is_error());
+        let messages = |errors: bool| -> Vec<_> {
+            issues
+                .iter()
+                .filter(|issue| issue.is_error() == errors)
+                .map(|issue| issue.message.clone())
+                .collect()
+        };
+        rooms.push(json!({
+            "path": name,
+            "variant": format!("{:?}", header.variant),
+            "errors": messages(true),
+            "warnings": messages(false),
+            "missing": missing
+                .iter()
+                .map(|(kind, path)| json!({ "kind": kind, "path": relative(root, path) }))
+                .collect::<Vec<_>>(),
+        }));
+    }
+
+    let report = json!({
+        "root": root.display().to_string(),
+        "room_count": files.len(),
+        "rooms": rooms,
+        "parse_failures": parse_failures,
+        "missing_assets": missing_assets,
+    });
+    Ok((report, failed))
+}
+
+fn collect_rmesh_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_rmesh_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("rmesh"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Path relative to the scanned directory with `/` separators, for stable reports.
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Removes terminal color codes binrw puts in its error messages.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip until the final byte of the escape sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{write_rmesh, Header};
+
+    #[test]
+    fn color_codes_are_stripped() {
+        assert_eq!(strip_ansi("\u{1b}[31merror\u{1b}[0m: bad"), "error: bad");
+    }
+
+    #[test]
+    fn reports_list_rooms_and_failures() {
+        let root = std::env::temp_dir().join(format!("rmesh_scan_{}", std::process::id()));
+        let map_dir = root.join("GFX/map/lcz");
+        fs::create_dir_all(&map_dir).unwrap();
+        fs::write(
+            map_dir.join("room.rmesh"),
+            write_rmesh(&Header::default()).unwrap(),
+        )
+        .unwrap();
+        fs::write(map_dir.join("broken.RMESH"), b"broken").unwrap();
+        fs::write(map_dir.join("notes.txt"), b"").unwrap();
+
+        let result = scan(&root);
+        fs::remove_dir_all(&root).unwrap();
+        let (report, failed) = result.unwrap();
+
+        assert!(failed);
+        assert_eq!(report["room_count"], 2);
+        assert_eq!(report["rooms"][0]["path"], "GFX/map/lcz/room.rmesh");
+        assert_eq!(report["rooms"][0]["errors"], json!([]));
+        assert_eq!(
+            report["parse_failures"][0]["path"],
+            "GFX/map/lcz/broken.RMESH"
+        );
+    }
+
+    #[test]
+    fn directories_without_rooms_fail() {
+        assert!(scan(Path::new("no/such/game")).is_err());
+    }
+}
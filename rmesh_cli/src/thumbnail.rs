@@ -0,0 +1,262 @@
+//! Software rasterizer for room previews.
+//!
+//! The camera looks down at the room from above a corner. Ceilings face away from it and
+//! get culled, which leaves a cutaway view of the floors and walls.
+
+use std::path::Path;
+
+use image::{Rgb, RgbImage, RgbaImage};
+use rmesh::{Bounds, Header, TextureBlendType};
+
+use crate::math::{cross, dot, length, normalize, scale, sub};
+use rmesh::resolve::find_path;
+
+const BACKGROUND: Rgb<u8> = Rgb([32, 32, 32]);
+const UNTEXTURED: [f32; 3] = [0.8, 0.8, 0.8];
+
+pub fn render(header: &Header, room_dir: &Path, width: u32, height: u32) -> RgbImage {
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+    let Some(camera) = header
+        .stats()
+        .bounds
+        .and_then(|bounds| Camera::fit(&bounds, width, height))
+    else {
+        return image;
+    };
+    let mut depth = vec![f32::INFINITY; (width * height) as usize];
+
+    for mesh in &header.meshes {
+        if mesh.is_invisible() {
+            continue;
+        }
+        let [lightmap, diffuse] = &mesh.textures;
+        let diffuse_image = load_texture(room_dir, diffuse.path.as_ref().map(String::from));
+        let lightmap_image = load_texture(room_dir, lightmap.path.as_ref().map(String::from));
+        let alpha_test = diffuse.blend_type == TextureBlendType::Transparent;
+
+        for [v0, v1, v2] in mesh.triangles_resolved().flatten() {
+            let positions = [v0.position, v1.position, v2.position];
+            let normal = cross(
+                sub(positions[1], positions[0]),
+                sub(positions[2], positions[0]),
+            );
+            if dot(normal, sub(camera.eye, positions[0])) <= 0.0 {
+                continue;
+            }
+            // Fake sun so untextured and unlit rooms still show their shape
+            let sun = 0.4 + 0.6 * dot(normalize(normal), normalize([0.3, 1.0, -0.5])).abs();
+
+            let projected = positions.map(|p| camera.project(p));
+            let vertices = [v0, v1, v2];
+            rasterize(projected, width, height, |x, y, weights| {
+                let index = (y * width + x) as usize;
+                let z = 1.0
+                    / weights
+                        .iter()
+                        .zip(&projected)
+                        .map(|(w, p)| w / p[2])
+                        .sum::<f32>();
+                if z >= depth[index] {
+                    return;
+                }
+                // Perspective correct interpolation
+                let interpolate = |channel: usize| {
+                    let mut uv = [0.0; 2];
+                    for (k, vertex) in vertices.iter().enumerate() {
+                        let w = weights[k] / projected[k][2] * z;
+                        uv[0] += vertex.tex_coords[channel][0] * w;
+                        uv[1] += vertex.tex_coords[channel][1] * w;
+                    }
+                    uv
+                };
+
+                let mut color = UNTEXTURED;
+                if let Some(texture) = &diffuse_image {
+                    let [r, g, b, a] = sample(texture, interpolate(0));
+                    if alpha_test && a < 0.5 {
+                        return;
+                    }
+                    color = [r, g, b];
+                }
+                let light = match &lightmap_image {
+                    Some(texture) => {
+                        let [r, g, b, _] = sample(texture, interpolate(1));
+                        [r, g, b]
+                    }
+                    None => [sun; 3],
+                };
+
+                depth[index] = z;
+                let pixel = [0, 1, 2].map(|c| ((color[c] * light[c]).min(1.0) * 255.0) as u8);
+                image.put_pixel(x, y, Rgb(pixel));
+            });
+        }
+    }
+
+    image
+}
+
+struct Camera {
+    eye: [f32; 3],
+    right: [f32; 3],
+    up: [f32; 3],
+    forward: [f32; 3],
+    focal: f32,
+    center: [f32; 2],
+}
+
+impl Camera {
+    /// Places the camera so the bounding sphere of the room fills the view.
+    fn fit(bounds: &Bounds, width: u32, height: u32) -> Option<Self> {
+        if bounds.is_empty() {
+            return None;
+        }
+        let fov = 50f32.to_radians();
+        let radius = length(bounds.half_extents()).max(f32::EPSILON);
+        let distance = radius / (fov * 0.5).sin();
+
+        let forward = normalize([-1.0, -1.2, 1.0]);
+        let eye = sub(bounds.center(), scale(forward, distance));
+        // Blitz3D is left-handed
+        let right = normalize(cross([0.0, 1.0, 0.0], forward));
+        let up = cross(forward, right);
+
+        Some(Self {
+            eye,
+            right,
+            up,
+            forward,
+            focal: width.min(height) as f32 * 0.5 / (fov * 0.5).tan(),
+            center: [width as f32 * 0.5, height as f32 * 0.5],
+        })
+    }
+
+    /// Screen position and view depth of a point.
+    fn project(&self, point: [f32; 3]) -> [f32; 3] {
+        let relative = sub(point, self.eye);
+        let z = dot(relative, self.forward).max(f32::EPSILON);
+        [
+            self.center[0] + dot(relative, self.right) / z * self.focal,
+            self.center[1] - dot(relative, self.up) / z * self.focal,
+            z,
+        ]
+    }
+}
+
+/// Calls `shade` with the barycentric weights of every pixel center inside the triangle.
+fn rasterize(
+    points: [[f32; 3]; 3],
+    width: u32,
+    height: u32,
+    mut shade: impl FnMut(u32, u32, [f32; 3]),
+) {
+    let edge = |a: [f32; 3], b: [f32; 3], x: f32, y: f32| {
+        (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])
+    };
+    let area = edge(points[0], points[1], points[2][0], points[2][1]);
+    if area == 0.0 {
+        return;
+    }
+
+    let min = |c: usize| points.iter().map(|p| p[c]).fold(f32::INFINITY, f32::min);
+    let max = |c: usize| {
+        points
+            .iter()
+            .map(|p| p[c])
+            .fold(f32::NEG_INFINITY, f32::max)
+    };
+    let x0 = min(0).floor().max(0.0) as u32;
+    let y0 = min(1).floor().max(0.0) as u32;
+    let x1 = (max(0).ceil().max(0.0) as u32).min(width);
+    let y1 = (max(1).ceil().max(0.0) as u32).min(height);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [
+                edge(points[1], points[2], px, py) / area,
+                edge(points[2], points[0], px, py) / area,
+                edge(points[0], points[1], px, py) / area,
+            ];
+            if weights.iter().all(|w| *w >= 0.0) {
+                shade(x, y, weights);
+            }
+        }
+    }
+}
+
+fn load_texture(room_dir: &Path, path: Option<String>) -> Option<RgbaImage> {
+    let path = room_dir.join(path?.replace('\\', "/"));
+    Some(image::open(find_path(&path)?).ok()?.to_rgba8())
+}
+
+/// Nearest neighbour lookup with wrapping.
+fn sample(texture: &RgbaImage, [u, v]: [f32; 2]) -> [f32; 4] {
+    let (width, height) = texture.dimensions();
+    let x = (u.rem_euclid(1.0) * width as f32) as u32 % width;
+    let y = (v.rem_euclid(1.0) * height as f32) as u32 % height;
+    texture.get_pixel(x, y).0.map(|c| c as f32 / 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{ComplexMesh, Texture, Vertex};
+
+    fn floor(triangles: Vec<[u32; 3]>) -> Header {
+        Header {
+            meshes: vec![ComplexMesh {
+                textures: [
+                    Texture::default(),
+                    Texture {
+                        blend_type: TextureBlendType::Visible,
+                        path: None,
+                    },
+                ],
+                vertices: [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]
+                    .map(|[x, z]| Vertex {
+                        position: [x, 0.0, z],
+                        ..Default::default()
+                    })
+                    .to_vec(),
+                triangles,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rasterize_covers_pixel_centers_inside() {
+        let mut covered = 0;
+        rasterize(
+            [[0.0, 0.0, 1.0], [4.0, 0.0, 1.0], [0.0, 4.0, 1.0]],
+            8,
+            8,
+            |_, _, weights| {
+                assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+                covered += 1;
+            },
+        );
+        // The pixels below the diagonal of the 4 by 4 corner
+        assert_eq!(covered, 10);
+    }
+
+    #[test]
+    fn floors_show_and_ceilings_are_culled() {
+        let dir = Path::new(".");
+        let image = render(&floor(vec![[0, 1, 2], [2, 1, 3]]), dir, 32, 32);
+        assert_ne!(*image.get_pixel(16, 16), BACKGROUND);
+
+        let image = render(&floor(vec![[0, 2, 1], [2, 3, 1]]), dir, 32, 32);
+        assert!(image.pixels().all(|pixel| *pixel == BACKGROUND));
+    }
+
+    #[test]
+    fn samples_wrap() {
+        let mut texture = RgbaImage::new(2, 2);
+        texture.put_pixel(1, 0, image::Rgba([255, 0, 0, 255]));
+
+        assert_eq!(sample(&texture, [1.75, -1.0]), [1.0, 0.0, 0.0, 1.0]);
+    }
+}
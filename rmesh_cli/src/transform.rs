@@ -0,0 +1,38 @@
+/// Column-major affine matrix scaling, then turning around Y, then translating.
+///
+/// Positive angles turn the same way as Blitz3D yaw.
+pub fn matrix(scale: [f32; 3], rotate_y: f32, translate: [f32; 3]) -> [[f32; 4]; 4] {
+    let (sin, cos) = rotate_y.to_radians().sin_cos();
+    let [sx, sy, sz] = scale;
+    let [tx, ty, tz] = translate;
+    [
+        [cos * sx, 0.0, -sin * sx, 0.0],
+        [0.0, sy, 0.0, 0.0],
+        [sin * sz, 0.0, cos * sz, 0.0],
+        [tx, ty, tz, 1.0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{Header, SimpleMesh};
+
+    #[test]
+    fn scales_then_turns_then_moves() {
+        let mut header = Header {
+            colliders: vec![SimpleMesh::new(
+                vec![[1.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+                vec![],
+            )],
+            ..Default::default()
+        };
+
+        header.transform(&matrix([2.0; 3], 90.0, [10.0, 0.0, 0.0]));
+
+        let rounded = |position: [f32; 3]| position.map(|c| (c * 1000.0).round() / 1000.0);
+        let vertices = &header.colliders[0].vertices;
+        assert_eq!(rounded(vertices[0]), [10.0, 2.0, -2.0]);
+        assert_eq!(rounded(vertices[1]), [12.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use rmesh::resolve::{self, find_path};
+use rmesh::{Dependency, Header};
+
+use crate::math::{cross, sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// Checks the geometry of a room and that every file it references exists.
+///
+/// Screens are only checked with a `screens_dir`.
+pub fn validate(
+    header: &Header,
+    room_dir: &Path,
+    props_dir: &Path,
+    screens_dir: Option<&Path>,
+) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    for (index, mesh) in header.meshes.iter().enumerate() {
+        let positions = mesh.positions();
+        check_geometry(
+            &mut issues,
+            &format!("mesh {index}"),
+            &positions,
+            &mesh.triangles,
+        );
+    }
+    for (index, collider) in header.colliders.iter().enumerate() {
+        let name = format!("collider {index}");
+        check_geometry(&mut issues, &name, &collider.vertices, &collider.triangles);
+    }
+    for trigger_box in &header.trigger_boxes {
+        for (index, mesh) in trigger_box.meshes.iter().enumerate() {
+            let name = format!(
+                "trigger box {:?} mesh {index}",
+                String::from(&trigger_box.name)
+            );
+            check_geometry(&mut issues, &name, &mesh.vertices, &mesh.triangles);
+        }
+    }
+
+    for (kind, path) in missing_files(header, room_dir, props_dir, screens_dir) {
+        issues.push(Issue::error(format!("missing {kind} {}", path.display())));
+    }
+
+    issues
+}
+
+/// Lists referenced files that don't exist, with the kind of file each one is.
+pub fn missing_files(
+    header: &Header,
+    room_dir: &Path,
+    props_dir: &Path,
+    screens_dir: Option<&Path>,
+) -> Vec<(&'static str, PathBuf)> {
+    header
+        .dependencies()
+        .into_iter()
+        .filter_map(|dependency| {
+            let (kind, candidates) = match &dependency {
+                Dependency::Texture(path) => {
+                    ("texture", vec![room_dir.join(resolve::normalize(path))])
+                }
+                Dependency::Lightmap(path) => {
+                    ("lightmap", vec![room_dir.join(resolve::normalize(path))])
+                }
+                Dependency::Model(name) => ("model", resolve::model_candidates(name, &[props_dir])),
+                Dependency::Screen(name) => {
+                    ("screen", resolve::screen_candidates(name, &[screens_dir?]))
+                }
+                Dependency::Sound(_) => return None,
+            };
+            let found = candidates
+                .iter()
+                .any(|candidate| find_path(candidate).is_some_and(|path| path.is_file()));
+            (!found).then(|| (kind, candidates[0].clone()))
+        })
+        .collect()
+}
+
+fn check_geometry(
+    issues: &mut Vec<Issue>,
+    name: &str,
+    positions: &[[f32; 3]],
+    triangles: &[[u32; 3]],
+) {
+    if positions.iter().flatten().any(|c| !c.is_finite()) {
+        issues.push(Issue::error(format!("{name} has non-finite positions")));
+    }
+
+    let mut degenerate = 0;
+    for (index, triangle) in triangles.iter().enumerate() {
+        let corner = |i: u32| positions.get(i as usize).copied();
+        let (Some(a), Some(b), Some(c)) = (
+            corner(triangle[0]),
+            corner(triangle[1]),
+            corner(triangle[2]),
+        ) else {
+            issues.push(Issue::error(format!(
+                "{name} triangle {index} indexes past its {} vertices: {triangle:?}",
+                positions.len()
+            )));
+            continue;
+        };
+        if cross(sub(b, a), sub(c, a)) == [0.0; 3] {
+            degenerate += 1;
+        }
+    }
+    if degenerate > 0 {
+        issues.push(Issue::warning(format!(
+            "{name} has {degenerate} degenerate triangles"
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{ComplexMesh, SimpleMesh, Texture, TextureBlendType, Vertex};
+
+    fn messages(issues: &[Issue], severity: Severity) -> Vec<&str> {
+        issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .map(|issue| issue.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn geometry_problems_are_reported() {
+        let header = Header {
+            colliders: vec![
+                SimpleMesh::new(
+                    vec![[0.0; 3], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]],
+                    vec![[0, 1, 2], [0, 1, 5]],
+                ),
+                SimpleMesh::new(vec![[f32::NAN; 3]], vec![]),
+            ],
+            ..Default::default()
+        };
+
+        let issues = validate(&header, Path::new("."), Path::new("."), None);
+        assert_eq!(
+            messages(&issues, Severity::Error),
+            [
+                "collider 0 triangle 1 indexes past its 3 vertices: [0, 1, 5]",
+                "collider 1 has non-finite positions",
+            ]
+        );
+        assert_eq!(
+            messages(&issues, Severity::Warning),
+            ["collider 0 has 1 degenerate triangles"]
+        );
+    }
+
+    #[test]
+    fn missing_textures_are_errors() {
+        let room_dir = std::env::temp_dir().join(format!("rmesh_validate_{}", std::process::id()));
+        std::fs::create_dir_all(&room_dir).unwrap();
+        std::fs::write(room_dir.join("found.jpg"), []).unwrap();
+        let mesh = |path: &str| ComplexMesh {
+            textures: [
+                Texture::default(),
+                Texture {
+                    blend_type: TextureBlendType::Visible,
+                    path: Some(path.into()),
+                },
+            ],
+            vertices: vec![Vertex::default(); 3],
+            ..Default::default()
+        };
+        let header = Header {
+            meshes: vec![mesh("found.jpg"), mesh("lost.jpg")],
+            ..Default::default()
+        };
+
+        let missing = missing_files(&header, &room_dir, &room_dir, None);
+        std::fs::remove_dir_all(&room_dir).unwrap();
+
+        assert_eq!(missing, [("texture", room_dir.join("lost.jpg"))]);
+    }
+}
@@ -0,0 +1,103 @@
+/* C bindings for the rmesh parser, see rmesh_ffi/src/lib.rs for the details of each function.
+ *
+ * Rooms are opaque RMeshHeader pointers owned by the caller until rmesh_free. Functions taking
+ * an index return zero, false or NULL when it's out of range. */
+
+#ifndef RMESH_H
+#define RMESH_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct RMeshHeader RMeshHeader;
+
+typedef struct RMeshVertex {
+    float position[3];
+    /* Diffuse texture coordinates. */
+    float uv0[2];
+    /* Lightmap texture coordinates. */
+    float uv1[2];
+    uint8_t color[3];
+} RMeshVertex;
+
+typedef enum RMeshEntityType {
+    /* The index is out of range, or the entity type is unknown. */
+    RMESH_ENTITY_NONE,
+    RMESH_ENTITY_SCREEN,
+    RMESH_ENTITY_WAYPOINT,
+    RMESH_ENTITY_LIGHT,
+    RMESH_ENTITY_SPOTLIGHT,
+    RMESH_ENTITY_SOUND_EMITTER,
+    RMESH_ENTITY_PLAYER_START,
+    RMESH_ENTITY_MODEL,
+} RMeshEntityType;
+
+/* A light or spotlight, the cone is left zero for lights. */
+typedef struct RMeshLight {
+    float range;
+    uint8_t color[3];
+    float intensity;
+    /* Pitch, yaw and roll in degrees. */
+    float angles[3];
+    float inner_cone_angle;
+    float outer_cone_angle;
+} RMeshLight;
+
+/* The message of the last failed call on this thread, empty when none failed. */
+const char *rmesh_last_error(void);
+
+/* Parses a room, returning NULL on failure. */
+RMeshHeader *rmesh_read(const uint8_t *bytes, size_t len);
+/* An empty room, to be filled with rmesh_add_mesh and written. */
+RMeshHeader *rmesh_new(void);
+void rmesh_free(RMeshHeader *header);
+/* Serializes a room, returning NULL on failure. Free the bytes with rmesh_free_bytes. */
+uint8_t *rmesh_write(const RMeshHeader *header, size_t *len);
+void rmesh_free_bytes(uint8_t *bytes, size_t len);
+
+size_t rmesh_mesh_count(const RMeshHeader *header);
+size_t rmesh_mesh_vertex_count(const RMeshHeader *header, size_t mesh);
+/* Copies up to capacity vertices into out, returning how many were copied. */
+size_t rmesh_mesh_vertices(const RMeshHeader *header, size_t mesh, RMeshVertex *out,
+                           size_t capacity);
+size_t rmesh_mesh_triangle_count(const RMeshHeader *header, size_t mesh);
+/* Three indices per triangle, valid until the room is changed or freed. */
+const uint32_t *rmesh_mesh_triangles(const RMeshHeader *header, size_t mesh);
+/* Slot 0 is the lightmap, 1 the diffuse texture. 0 none, 1 visible, 2 lightmap, 3 transparent. */
+uint8_t rmesh_mesh_texture_blend(const RMeshHeader *header, size_t mesh, size_t slot);
+/* Copies the texture path into out like snprintf, returning its full length. */
+size_t rmesh_mesh_texture_path(const RMeshHeader *header, size_t mesh, size_t slot, char *out,
+                               size_t capacity);
+/* Appends a mesh, returning its index. NULL texture paths leave the slot empty. */
+size_t rmesh_add_mesh(RMeshHeader *header, const RMeshVertex *vertices, size_t vertex_count,
+                      const uint32_t *triangles, size_t triangle_count, const char *diffuse,
+                      const char *lightmap);
+
+size_t rmesh_collider_count(const RMeshHeader *header);
+size_t rmesh_collider_vertex_count(const RMeshHeader *header, size_t collider);
+/* Three floats per vertex, valid until the room is changed or freed. */
+const float *rmesh_collider_vertices(const RMeshHeader *header, size_t collider);
+size_t rmesh_collider_triangle_count(const RMeshHeader *header, size_t collider);
+const uint32_t *rmesh_collider_triangles(const RMeshHeader *header, size_t collider);
+
+size_t rmesh_entity_count(const RMeshHeader *header);
+RMeshEntityType rmesh_entity_type(const RMeshHeader *header, size_t entity);
+bool rmesh_entity_position(const RMeshHeader *header, size_t entity, float out[3]);
+/* Copies the image of a screen or the file of a model into out like snprintf. */
+size_t rmesh_entity_name(const RMeshHeader *header, size_t entity, char *out, size_t capacity);
+bool rmesh_entity_light(const RMeshHeader *header, size_t entity, RMeshLight *out);
+/* Pitch, yaw and roll of a player start, or the rotation of a model. */
+bool rmesh_entity_angles(const RMeshHeader *header, size_t entity, float out[3]);
+bool rmesh_entity_scale(const RMeshHeader *header, size_t entity, float out[3]);
+bool rmesh_entity_sound(const RMeshHeader *header, size_t entity, uint32_t *sound, float *range);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
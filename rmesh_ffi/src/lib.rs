@@ -0,0 +1,698 @@
+//! C bindings for reading and writing rmesh files, see `include/rmesh.h`.
+//!
+//! Rooms are opaque [`RMeshHeader`] pointers owned by the caller until [`rmesh_free`]. Functions
+//! taking an index return zero, `false` or null when it's out of range.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use rmesh::{
+    read_rmesh, write_rmesh, ComplexMesh, EntityType, FixedLengthString, Header, Texture,
+    TextureBlendType, Vertex,
+};
+
+/// A parsed room.
+pub struct RMeshHeader(Header);
+
+/// A room mesh vertex.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct RMeshVertex {
+    pub position: [f32; 3],
+    /// Diffuse texture coordinates.
+    pub uv0: [f32; 2],
+    /// Lightmap texture coordinates.
+    pub uv1: [f32; 2],
+    pub color: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RMeshEntityType {
+    /// The index is out of range, or the entity type is unknown.
+    None,
+    Screen,
+    Waypoint,
+    Light,
+    Spotlight,
+    SoundEmitter,
+    PlayerStart,
+    Model,
+}
+
+/// A light or spotlight, the cone is left zero for lights.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct RMeshLight {
+    pub range: f32,
+    pub color: [u8; 3],
+    pub intensity: f32,
+    /// Pitch, yaw and roll in degrees.
+    pub angles: [f32; 3],
+    pub inner_cone_angle: f32,
+    pub outer_cone_angle: f32,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(error: impl ToString) {
+    let message = CString::new(error.to_string().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with_borrow_mut(|last_error| *last_error = message);
+}
+
+/// Copies a string NUL-terminated into `out`, truncated to `capacity`, returning its full length.
+unsafe fn copy_string(bytes: &[u8], out: *mut c_char, capacity: usize) -> usize {
+    if !out.is_null() && capacity > 0 {
+        let len = bytes.len().min(capacity - 1);
+        ptr::copy_nonoverlapping(bytes.as_ptr(), out.cast(), len);
+        *out.add(len) = 0;
+    }
+    bytes.len()
+}
+
+unsafe fn texture_path(path: *const c_char) -> Option<FixedLengthString> {
+    if path.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(path).to_string_lossy().into_owned().into())
+}
+
+unsafe fn header<'a>(header: *const RMeshHeader) -> Option<&'a Header> {
+    header.as_ref().map(|header| &header.0)
+}
+
+unsafe fn mesh<'a>(header: *const RMeshHeader, mesh: usize) -> Option<&'a ComplexMesh> {
+    self::header(header)?.meshes.get(mesh)
+}
+
+unsafe fn entity<'a>(header: *const RMeshHeader, entity: usize) -> Option<&'a EntityType> {
+    self::header(header)?
+        .entities
+        .get(entity)?
+        .entity_type
+        .as_ref()
+}
+
+/// The message of the last failed call on this thread, empty when none failed.
+#[no_mangle]
+pub extern "C" fn rmesh_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|last_error| last_error.as_ptr())
+}
+
+/// Parses a room, returning null on failure, see [`rmesh_last_error`].
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_read(bytes: *const u8, len: usize) -> *mut RMeshHeader {
+    if bytes.is_null() {
+        set_last_error("bytes is null");
+        return ptr::null_mut();
+    }
+    match read_rmesh(std::slice::from_raw_parts(bytes, len)) {
+        Ok(header) => Box::into_raw(Box::new(RMeshHeader(header))),
+        Err(error) => {
+            set_last_error(error);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// An empty room, to be filled with [`rmesh_add_mesh`] and written.
+#[no_mangle]
+pub extern "C" fn rmesh_new() -> *mut RMeshHeader {
+    Box::into_raw(Box::new(RMeshHeader(Header::default())))
+}
+
+/// Frees a room from [`rmesh_read`] or [`rmesh_new`].
+///
+/// # Safety
+///
+/// `header` must be null or a room that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_free(header: *mut RMeshHeader) {
+    if !header.is_null() {
+        drop(Box::from_raw(header));
+    }
+}
+
+/// Serializes a room, returning null on failure. The length is stored in `len`, free the bytes
+/// with [`rmesh_free_bytes`].
+///
+/// # Safety
+///
+/// `header` must be a valid room and `len` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_write(header: *const RMeshHeader, len: *mut usize) -> *mut u8 {
+    let (Some(header), Some(len)) = (self::header(header), len.as_mut()) else {
+        set_last_error("header or len is null");
+        return ptr::null_mut();
+    };
+    match write_rmesh(header) {
+        Ok(bytes) => {
+            *len = bytes.len();
+            Box::into_raw(bytes.into_boxed_slice()).cast()
+        }
+        Err(error) => {
+            set_last_error(error);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees bytes from [`rmesh_write`].
+///
+/// # Safety
+///
+/// `bytes` and `len` must come from the same [`rmesh_write`] call, and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_free_bytes(bytes: *mut u8, len: usize) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)));
+    }
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_count(header: *const RMeshHeader) -> usize {
+    self::header(header).map_or(0, |header| header.meshes.len())
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_vertex_count(header: *const RMeshHeader, mesh: usize) -> usize {
+    self::mesh(header, mesh).map_or(0, |mesh| mesh.vertices.len())
+}
+
+/// Copies up to `capacity` vertices of a mesh into `out`, returning how many were copied.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must have room for `capacity` vertices.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_vertices(
+    header: *const RMeshHeader,
+    mesh: usize,
+    out: *mut RMeshVertex,
+    capacity: usize,
+) -> usize {
+    let Some(mesh) = self::mesh(header, mesh) else {
+        return 0;
+    };
+    if out.is_null() {
+        return 0;
+    }
+    let count = mesh.vertices.len().min(capacity);
+    for (i, vertex) in mesh.vertices[..count].iter().enumerate() {
+        *out.add(i) = RMeshVertex {
+            position: vertex.position,
+            uv0: vertex.tex_coords[0],
+            uv1: vertex.tex_coords[1],
+            color: vertex.color,
+        };
+    }
+    count
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_triangle_count(
+    header: *const RMeshHeader,
+    mesh: usize,
+) -> usize {
+    self::mesh(header, mesh).map_or(0, |mesh| mesh.triangles.len())
+}
+
+/// Three vertex indices per triangle, valid until the room is changed or freed.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_triangles(
+    header: *const RMeshHeader,
+    mesh: usize,
+) -> *const u32 {
+    self::mesh(header, mesh).map_or(ptr::null(), |mesh| mesh.triangles.as_ptr().cast())
+}
+
+/// Blend type of texture slot `0` (lightmap) or `1` (diffuse): 0 none, 1 visible, 2 lightmap,
+/// 3 transparent.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_texture_blend(
+    header: *const RMeshHeader,
+    mesh: usize,
+    slot: usize,
+) -> u8 {
+    self::mesh(header, mesh)
+        .and_then(|mesh| mesh.textures.get(slot))
+        .map_or(0, |texture| texture.blend_type as u8)
+}
+
+/// Copies the path of texture slot `0` (lightmap) or `1` (diffuse) into `out`, like
+/// `snprintf`. Returns the full length, zero without a texture.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must have room for `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_mesh_texture_path(
+    header: *const RMeshHeader,
+    mesh: usize,
+    slot: usize,
+    out: *mut c_char,
+    capacity: usize,
+) -> usize {
+    let path = self::mesh(header, mesh)
+        .and_then(|mesh| mesh.textures.get(slot))
+        .and_then(|texture| texture.path.as_ref());
+    copy_string(path.map_or(&[], |path| &path.values), out, capacity)
+}
+
+/// Appends a mesh, returning its index. Null texture paths leave the slot empty.
+///
+/// # Safety
+///
+/// `header` must be a valid room, `vertices` must point to `vertex_count` vertices,
+/// `triangles` to `3 * triangle_count` indices and the paths must be null or NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_add_mesh(
+    header: *mut RMeshHeader,
+    vertices: *const RMeshVertex,
+    vertex_count: usize,
+    triangles: *const u32,
+    triangle_count: usize,
+    diffuse: *const c_char,
+    lightmap: *const c_char,
+) -> usize {
+    let Some(header) = header.as_mut() else {
+        return 0;
+    };
+    let vertices = match vertices.is_null() {
+        true => &[],
+        false => std::slice::from_raw_parts(vertices, vertex_count),
+    };
+    let triangles = match triangles.is_null() {
+        true => &[],
+        false => std::slice::from_raw_parts(triangles.cast::<[u32; 3]>(), triangle_count),
+    };
+    let texture = |path: Option<FixedLengthString>, blend_type| Texture {
+        blend_type: path.as_ref().map_or(TextureBlendType::None, |_| blend_type),
+        path,
+    };
+    header.0.meshes.push(ComplexMesh {
+        textures: [
+            texture(texture_path(lightmap), TextureBlendType::Lightmap),
+            texture(texture_path(diffuse), TextureBlendType::Visible),
+        ],
+        vertices: vertices
+            .iter()
+            .map(|vertex| Vertex {
+                position: vertex.position,
+                tex_coords: [vertex.uv0, vertex.uv1],
+                color: vertex.color,
+            })
+            .collect(),
+        triangles: triangles.to_vec(),
+        extension: None,
+    });
+    header.0.meshes.len() - 1
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_collider_count(header: *const RMeshHeader) -> usize {
+    self::header(header).map_or(0, |header| header.colliders.len())
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_collider_vertex_count(
+    header: *const RMeshHeader,
+    collider: usize,
+) -> usize {
+    self::header(header)
+        .and_then(|header| header.colliders.get(collider))
+        .map_or(0, |collider| collider.vertices.len())
+}
+
+/// Three floats per vertex, valid until the room is changed or freed.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_collider_vertices(
+    header: *const RMeshHeader,
+    collider: usize,
+) -> *const f32 {
+    self::header(header)
+        .and_then(|header| header.colliders.get(collider))
+        .map_or(ptr::null(), |collider| collider.vertices.as_ptr().cast())
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_collider_triangle_count(
+    header: *const RMeshHeader,
+    collider: usize,
+) -> usize {
+    self::header(header)
+        .and_then(|header| header.colliders.get(collider))
+        .map_or(0, |collider| collider.triangles.len())
+}
+
+/// Three vertex indices per triangle, valid until the room is changed or freed.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_collider_triangles(
+    header: *const RMeshHeader,
+    collider: usize,
+) -> *const u32 {
+    self::header(header)
+        .and_then(|header| header.colliders.get(collider))
+        .map_or(ptr::null(), |collider| collider.triangles.as_ptr().cast())
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_count(header: *const RMeshHeader) -> usize {
+    self::header(header).map_or(0, |header| header.entities.len())
+}
+
+/// # Safety
+///
+/// `header` must be null or a valid room.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_type(
+    header: *const RMeshHeader,
+    entity: usize,
+) -> RMeshEntityType {
+    match self::entity(header, entity) {
+        Some(EntityType::Screen(_)) => RMeshEntityType::Screen,
+        Some(EntityType::WayPoint(_)) => RMeshEntityType::Waypoint,
+        Some(EntityType::Light(_)) => RMeshEntityType::Light,
+        Some(EntityType::SpotLight(_)) => RMeshEntityType::Spotlight,
+        Some(EntityType::SoundEmitter(_)) => RMeshEntityType::SoundEmitter,
+        Some(EntityType::PlayerStart(_)) => RMeshEntityType::PlayerStart,
+        Some(EntityType::Model(_)) => RMeshEntityType::Model,
+        None => RMeshEntityType::None,
+    }
+}
+
+/// Copies the position of an entity in room space into `out`.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must have room for three floats.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_position(
+    header: *const RMeshHeader,
+    entity: usize,
+    out: *mut f32,
+) -> bool {
+    let Some(entity) = self::entity(header, entity) else {
+        return false;
+    };
+    if out.is_null() {
+        return false;
+    }
+    ptr::copy_nonoverlapping(entity.position().as_ptr(), out, 3);
+    true
+}
+
+/// Copies the image of a screen or the file of a model into `out`, like `snprintf`. Returns
+/// the full length, zero for other entities.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must have room for `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_name(
+    header: *const RMeshHeader,
+    entity: usize,
+    out: *mut c_char,
+    capacity: usize,
+) -> usize {
+    let name = match self::entity(header, entity) {
+        Some(EntityType::Screen(screen)) => &screen.name.values[..],
+        Some(EntityType::Model(model)) => &model.name.values[..],
+        _ => &[],
+    };
+    copy_string(name, out, capacity)
+}
+
+/// Fills `out` for lights and spotlights.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_light(
+    header: *const RMeshHeader,
+    entity: usize,
+    out: *mut RMeshLight,
+) -> bool {
+    let color = |color: &[u8]| [0, 1, 2].map(|i| color.get(i).copied().unwrap_or(0));
+    let light = match self::entity(header, entity) {
+        Some(EntityType::Light(light)) => RMeshLight {
+            range: light.range,
+            color: color(&light.color.0),
+            intensity: light.intensity,
+            ..Default::default()
+        },
+        Some(EntityType::SpotLight(light)) => RMeshLight {
+            range: light.range,
+            color: color(&light.color.0),
+            intensity: light.intensity,
+            angles: light.angles.0,
+            inner_cone_angle: light.inner_cone_angle,
+            outer_cone_angle: light.outer_cone_angle,
+        },
+        _ => return false,
+    };
+    match out.as_mut() {
+        Some(out) => {
+            *out = light;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Copies the pitch, yaw and roll in degrees of a player start, or the rotation of a model,
+/// into `out`.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must have room for three floats.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_angles(
+    header: *const RMeshHeader,
+    entity: usize,
+    out: *mut f32,
+) -> bool {
+    let angles = match self::entity(header, entity) {
+        Some(EntityType::PlayerStart(start)) => start.angles.0,
+        Some(EntityType::Model(model)) => model.rotation,
+        _ => return false,
+    };
+    if out.is_null() {
+        return false;
+    }
+    ptr::copy_nonoverlapping(angles.as_ptr(), out, 3);
+    true
+}
+
+/// Copies the scale of a model into `out`.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `out` must have room for three floats.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_scale(
+    header: *const RMeshHeader,
+    entity: usize,
+    out: *mut f32,
+) -> bool {
+    let Some(EntityType::Model(model)) = self::entity(header, entity) else {
+        return false;
+    };
+    if out.is_null() {
+        return false;
+    }
+    ptr::copy_nonoverlapping(model.scale.as_ptr(), out, 3);
+    true
+}
+
+/// Gets the ambience sound index and range of a sound emitter.
+///
+/// # Safety
+///
+/// `header` must be null or a valid room, `sound` and `range` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn rmesh_entity_sound(
+    header: *const RMeshHeader,
+    entity: usize,
+    sound: *mut u32,
+    range: *mut f32,
+) -> bool {
+    let Some(EntityType::SoundEmitter(emitter)) = self::entity(header, entity) else {
+        return false;
+    };
+    let (Some(sound), Some(range)) = (sound.as_mut(), range.as_mut()) else {
+        return false;
+    };
+    *sound = emitter.idk0;
+    *range = emitter.idk1;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmesh::{EntityPlayerStart, EntitySoundEmitter};
+
+    fn vertex(x: f32) -> RMeshVertex {
+        RMeshVertex {
+            position: [x, 1.0, 2.0],
+            uv0: [x, 0.0],
+            uv1: [0.0, x],
+            color: [255, 128, 0],
+        }
+    }
+
+    #[test]
+    fn written_meshes_read_back() {
+        unsafe {
+            let room = rmesh_new();
+            let vertices = [vertex(0.0), vertex(1.0), vertex(2.0)];
+            let triangles = [0u32, 1, 2];
+            let index = rmesh_add_mesh(
+                room,
+                vertices.as_ptr(),
+                vertices.len(),
+                triangles.as_ptr(),
+                1,
+                c"wall.jpg".as_ptr(),
+                ptr::null(),
+            );
+            assert_eq!(index, 0);
+
+            let mut len = 0;
+            let bytes = rmesh_write(room, &mut len);
+            rmesh_free(room);
+            let read = rmesh_read(bytes, len);
+            rmesh_free_bytes(bytes, len);
+            assert!(!read.is_null());
+
+            assert_eq!(rmesh_mesh_count(read), 1);
+            assert_eq!(rmesh_mesh_vertex_count(read, 0), 3);
+            let mut out = [RMeshVertex::default(); 2];
+            assert_eq!(rmesh_mesh_vertices(read, 0, out.as_mut_ptr(), out.len()), 2);
+            assert_eq!(out[1].position, [1.0, 1.0, 2.0]);
+            assert_eq!(out[1].uv1, [0.0, 1.0]);
+            assert_eq!(out[1].color, [255, 128, 0]);
+
+            assert_eq!(rmesh_mesh_triangle_count(read, 0), 1);
+            let read_triangles = std::slice::from_raw_parts(rmesh_mesh_triangles(read, 0), 3);
+            assert_eq!(read_triangles, triangles);
+
+            assert_eq!(rmesh_mesh_texture_blend(read, 0, 0), 0);
+            assert_eq!(rmesh_mesh_texture_blend(read, 0, 1), 1);
+            let mut path = [0 as c_char; 5];
+            assert_eq!(
+                rmesh_mesh_texture_path(read, 0, 1, path.as_mut_ptr(), path.len()),
+                8
+            );
+            // Truncated and still NUL-terminated
+            assert_eq!(CStr::from_ptr(path.as_ptr()), c"wall");
+            assert_eq!(rmesh_mesh_texture_path(read, 0, 0, ptr::null_mut(), 0), 0);
+
+            rmesh_free(read);
+        }
+    }
+
+    #[test]
+    fn out_of_range_indices_and_bad_files_fail_softly() {
+        unsafe {
+            let room = rmesh_new();
+            assert_eq!(rmesh_mesh_vertex_count(room, 3), 0);
+            assert!(rmesh_mesh_triangles(room, 3).is_null());
+            assert!(rmesh_collider_vertices(room, 0).is_null());
+            assert_eq!(rmesh_entity_type(room, 0), RMeshEntityType::None);
+            assert_eq!(rmesh_mesh_count(ptr::null()), 0);
+            rmesh_free(room);
+
+            let bytes = b"not a room";
+            assert!(rmesh_read(bytes.as_ptr(), bytes.len()).is_null());
+            assert!(!CStr::from_ptr(rmesh_last_error()).is_empty());
+        }
+    }
+
+    #[test]
+    fn entities_expose_their_fields() {
+        let header = Header {
+            entities: vec![
+                EntityType::PlayerStart(EntityPlayerStart {
+                    position: [1.0, 2.0, 3.0],
+                    angles: [0.0, 90.0, 0.0].into(),
+                })
+                .into(),
+                EntityType::SoundEmitter(EntitySoundEmitter {
+                    position: [0.0; 3],
+                    idk0: 4,
+                    idk1: 500.0,
+                })
+                .into(),
+            ],
+            ..Default::default()
+        };
+        let room = Box::into_raw(Box::new(RMeshHeader(header)));
+
+        unsafe {
+            assert_eq!(rmesh_entity_count(room), 2);
+            assert_eq!(rmesh_entity_type(room, 0), RMeshEntityType::PlayerStart);
+            let mut position = [0.0; 3];
+            assert!(rmesh_entity_position(room, 0, position.as_mut_ptr()));
+            assert_eq!(position, [1.0, 2.0, 3.0]);
+            let mut angles = [0.0; 3];
+            assert!(rmesh_entity_angles(room, 0, angles.as_mut_ptr()));
+            assert_eq!(angles, [0.0, 90.0, 0.0]);
+            let mut light = RMeshLight::default();
+            assert!(!rmesh_entity_light(room, 0, &mut light));
+
+            let (mut sound, mut range) = (0, 0.0);
+            assert!(rmesh_entity_sound(room, 1, &mut sound, &mut range));
+            assert_eq!((sound, range), (4, 500.0));
+            assert!(!rmesh_entity_scale(room, 1, position.as_mut_ptr()));
+
+            rmesh_free(room);
+        }
+    }
+}
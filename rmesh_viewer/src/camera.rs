@@ -0,0 +1,152 @@
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::room::{to_world, RoomFile};
+
+/// Free flying camera, yaw and pitch in radians.
+#[derive(Component)]
+pub struct FlyCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl FlyCamera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+}
+
+#[derive(Clone)]
+pub struct Bookmark {
+    pub name: String,
+    pub translation: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Saved camera positions, the first nine are bound to the number keys.
+#[derive(Resource, Default)]
+pub struct Bookmarks(pub Vec<Bookmark>);
+
+pub fn spawn_camera(mut commands: Commands, room: Res<RoomFile>) {
+    // Start above a corner of the room, looking at its center
+    let (center, distance) = match room.header.stats().bounds {
+        Some(bounds) => {
+            let [min, max] = [bounds.min, bounds.max].map(to_world);
+            let center = (min + max) * 0.5;
+            (center, (max - min).length().max(1.0))
+        }
+        None => (Vec3::ZERO, 10.0),
+    };
+    let eye = center + Vec3::new(-0.5, 0.6, 0.5) * distance;
+    let direction = (center - eye).normalize();
+    let camera = FlyCamera {
+        yaw: (-direction.x).atan2(-direction.z),
+        pitch: direction.y.asin(),
+    };
+
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_translation(eye).with_rotation(camera.rotation()),
+            ..default()
+        },
+        camera,
+    ));
+}
+
+pub fn fly_camera(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut cameras: Query<(&mut Transform, &mut FlyCamera)>,
+) {
+    let delta: Vec2 = motion.read().map(|event| event.delta).sum();
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let looking = buttons.pressed(MouseButton::Right) && !ctx.wants_pointer_input();
+
+    for (mut transform, mut camera) in &mut cameras {
+        if looking {
+            camera.yaw -= delta.x * 0.003;
+            camera.pitch = (camera.pitch - delta.y * 0.003).clamp(-1.54, 1.54);
+            transform.rotation = camera.rotation();
+        }
+
+        let mut direction = Vec3::ZERO;
+        for (key, axis) in [
+            (KeyCode::KeyW, *transform.forward()),
+            (KeyCode::KeyS, *transform.back()),
+            (KeyCode::KeyA, *transform.left()),
+            (KeyCode::KeyD, *transform.right()),
+            (KeyCode::KeyE, Vec3::Y),
+            (KeyCode::KeyQ, Vec3::NEG_Y),
+        ] {
+            if keys.pressed(key) {
+                direction += axis;
+            }
+        }
+        let speed = if keys.pressed(KeyCode::ShiftLeft) {
+            12.0
+        } else {
+            3.0
+        };
+        transform.translation += direction.normalize_or_zero() * speed * time.delta_seconds();
+    }
+}
+
+/// Jumps to the first nine bookmarks with the number keys.
+pub fn bookmark_keys(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    bookmarks: Res<Bookmarks>,
+    mut cameras: Query<(&mut Transform, &mut FlyCamera)>,
+) {
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    const DIGITS: [KeyCode; 9] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for (key, bookmark) in DIGITS.iter().zip(&bookmarks.0) {
+        if keys.just_pressed(*key) {
+            for (mut transform, mut camera) in &mut cameras {
+                go_to(&mut transform, &mut camera, bookmark);
+            }
+        }
+    }
+}
+
+pub fn go_to(transform: &mut Transform, camera: &mut FlyCamera, bookmark: &Bookmark) {
+    camera.yaw = bookmark.yaw;
+    camera.pitch = bookmark.pitch;
+    transform.translation = bookmark.translation;
+    transform.rotation = camera.rotation();
+}
+
+/// Moves the camera back from `target` and turns it to look there.
+pub fn focus(transform: &mut Transform, camera: &mut FlyCamera, target: Vec3) {
+    let offset = transform.translation - target;
+    let offset = if offset.length() > 0.01 {
+        offset.normalize() * 2.0
+    } else {
+        Vec3::new(0.0, 1.0, 2.0)
+    };
+    transform.translation = target + offset;
+    let direction = -offset.normalize();
+    camera.yaw = (-direction.x).atan2(-direction.z);
+    camera.pitch = direction.y.asin();
+    transform.rotation = camera.rotation();
+}
@@ -0,0 +1,177 @@
+//! Translucent collider and trigger box meshes, since neither is visible in game.
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rmesh::rmesh::SimpleMesh;
+
+use crate::room::{to_world, RoomFile};
+use crate::ui::ViewerState;
+
+const COLLIDER_COLOR: Color = Color::srgba(0.2, 0.9, 0.4, 0.25);
+const TRIGGER_BOX_COLOR: Color = Color::srgba(1.0, 0.3, 0.8, 0.25);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugKind {
+    Collider,
+    TriggerBox,
+}
+
+/// A debug mesh with the label drawn at its center.
+#[derive(Component)]
+pub struct DebugShape {
+    pub kind: DebugKind,
+    pub label: String,
+    pub center: Vec3,
+}
+
+fn debug_mesh(meshes: &[SimpleMesh]) -> Mesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for mesh in meshes {
+        let offset = positions.len() as u32;
+        positions.extend(mesh.vertices.iter().map(|v| to_world(*v).to_array()));
+        // Reversed like the room meshes, the material is double sided anyway
+        indices.extend(
+            mesh.triangles
+                .iter()
+                .flat_map(|t| t.map(|i| i + offset))
+                .rev(),
+        );
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn center(meshes: &[SimpleMesh]) -> Vec3 {
+    let points: Vec<_> = meshes
+        .iter()
+        .flat_map(|mesh| mesh.vertices.iter().map(|v| to_world(*v)))
+        .collect();
+    let sum: Vec3 = points.iter().sum();
+    if points.is_empty() {
+        Vec3::ZERO
+    } else {
+        sum / points.len() as f32
+    }
+}
+
+pub fn spawn_debug_shapes(
+    mut commands: Commands,
+    room: Res<RoomFile>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        })
+    };
+    let collider_material = material(COLLIDER_COLOR);
+    let trigger_box_material = material(TRIGGER_BOX_COLOR);
+
+    let colliders = room
+        .header
+        .colliders
+        .iter()
+        .enumerate()
+        .map(|(index, collider)| {
+            let shapes = std::slice::from_ref(collider);
+            (DebugKind::Collider, format!("Collider #{index}"), shapes)
+        });
+    let trigger_boxes = room.header.trigger_boxes.iter().map(|trigger_box| {
+        let label = String::from(&trigger_box.name);
+        (DebugKind::TriggerBox, label, trigger_box.meshes.as_slice())
+    });
+    for (kind, label, shapes) in colliders.chain(trigger_boxes) {
+        let material = match kind {
+            DebugKind::Collider => collider_material.clone(),
+            DebugKind::TriggerBox => trigger_box_material.clone(),
+        };
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(debug_mesh(shapes)),
+                material,
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DebugShape {
+                kind,
+                label,
+                center: center(shapes),
+            },
+        ));
+    }
+}
+
+fn is_shown(state: &ViewerState, kind: DebugKind) -> bool {
+    match kind {
+        DebugKind::Collider => state.show_colliders,
+        DebugKind::TriggerBox => state.show_trigger_boxes,
+    }
+}
+
+pub fn toggle_debug_shapes(
+    state: Res<ViewerState>,
+    mut shapes: Query<(&DebugShape, &mut Visibility)>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for (shape, mut visibility) in &mut shapes {
+        *visibility = if is_shown(&state, shape.kind) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn draw_labels(
+    mut contexts: EguiContexts,
+    state: Res<ViewerState>,
+    shapes: Query<&DebugShape>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    if !state.show_colliders && !state.show_trigger_boxes {
+        return;
+    }
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    for shape in shapes.iter().filter(|shape| is_shown(&state, shape.kind)) {
+        let Some(position) = camera.world_to_viewport(camera_transform, shape.center) else {
+            continue;
+        };
+        let color = match shape.kind {
+            DebugKind::Collider => COLLIDER_COLOR,
+            DebugKind::TriggerBox => TRIGGER_BOX_COLOR,
+        }
+        .to_srgba();
+        painter.text(
+            egui::pos2(position.x, position.y),
+            egui::Align2::CENTER_CENTER,
+            &shape.label,
+            egui::FontId::proportional(14.0),
+            egui::Color32::from_rgb(
+                (color.red * 255.0) as u8,
+                (color.green * 255.0) as u8,
+                (color.blue * 255.0) as u8,
+            ),
+        );
+    }
+}
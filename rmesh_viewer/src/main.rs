@@ -0,0 +1,97 @@
+//! Interactive room inspector.
+//!
+//! Run with `rmesh-viewer path/to/room.rmesh [--props dir]`. Hold the right mouse button to look around,
+//! WASD, Q and E to fly, shift to go faster.
+
+mod camera;
+mod debug;
+mod overlay;
+mod room;
+mod ui;
+
+use std::path::PathBuf;
+
+use bevy::pbr::wireframe::WireframePlugin;
+use bevy::prelude::*;
+use bevy::render::settings::{RenderCreation, WgpuFeatures, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy_egui::EguiPlugin;
+use bevy_rmesh::RMeshPlugin;
+
+fn main() {
+    let mut path = None;
+    let mut props = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--props" => props = args.next().map(PathBuf::from),
+            _ => path = Some(PathBuf::from(arg)),
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("usage: rmesh-viewer <room.rmesh> [--props <dir>]");
+        std::process::exit(2);
+    };
+    let room = match room::RoomFile::open(&path, props) {
+        Ok(room) => room,
+        Err(error) => {
+            eprintln!("error: reading {}: {error}", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    App::new()
+        .add_plugins((
+            DefaultPlugins
+                .set(AssetPlugin {
+                    // Texture paths in rooms are relative to the room file
+                    file_path: room.dir.to_string_lossy().into_owned(),
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings {
+                        features: WgpuFeatures::POLYGON_MODE_LINE,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: format!("rmesh-viewer - {}", path.display()),
+                        ..default()
+                    }),
+                    ..default()
+                }),
+            RMeshPlugin,
+            WireframePlugin,
+            EguiPlugin,
+        ))
+        .insert_resource(room)
+        .init_resource::<ui::ViewerState>()
+        .init_resource::<camera::Bookmarks>()
+        .add_systems(
+            Startup,
+            (
+                room::spawn_room,
+                debug::spawn_debug_shapes,
+                camera::spawn_camera,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                ui::panels,
+                room::spawn_meshes,
+                camera::fly_camera.after(ui::panels),
+                camera::bookmark_keys,
+                room::toggle_lightmaps,
+                room::toggle_props,
+                room::toggle_wireframe,
+                debug::toggle_debug_shapes,
+                debug::draw_labels.after(ui::panels),
+                overlay::draw_normals,
+                overlay::draw_selection,
+            ),
+        )
+        .run();
+}
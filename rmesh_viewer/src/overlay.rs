@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_rmesh::rmesh::{ExtMesh, ROOM_SCALE};
+
+use crate::room::{to_world, RoomFile};
+use crate::ui::{Selection, ViewerState};
+
+const NORMAL_LENGTH: f32 = 0.1;
+const SELECTION_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+
+/// Vertex normals of every visible mesh as world space line segments.
+#[derive(Default)]
+pub struct NormalLines(Option<Vec<(Vec3, Vec3)>>);
+
+pub fn draw_normals(
+    mut gizmos: Gizmos,
+    room: Res<RoomFile>,
+    state: Res<ViewerState>,
+    mut lines: Local<NormalLines>,
+) {
+    if !state.normals {
+        return;
+    }
+    let lines = lines.0.get_or_insert_with(|| {
+        room.header
+            .meshes
+            .iter()
+            .filter(|mesh| !mesh.is_invisible())
+            .flat_map(|mesh| {
+                mesh.vertices
+                    .iter()
+                    .zip(mesh.calculate_normals())
+                    .map(|(vertex, [x, y, z])| {
+                        let start = to_world(vertex.position);
+                        (start, start + Vec3::new(x, y, -z) * NORMAL_LENGTH)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    });
+    for (start, end) in lines.iter() {
+        gizmos.line(*start, *end, Color::srgb(0.2, 0.6, 1.0));
+    }
+}
+
+pub fn draw_selection(mut gizmos: Gizmos, room: Res<RoomFile>, state: Res<ViewerState>) {
+    match state.selected {
+        Some(Selection::Mesh(index)) => {
+            if let Some(mesh) = room.header.meshes.get(index) {
+                for [a, b, c] in mesh.triangles_resolved().flatten() {
+                    let [a, b, c] = [a, b, c].map(|v| to_world(v.position));
+                    gizmos.linestrip([a, b, c, a], SELECTION_COLOR);
+                }
+            }
+        }
+        Some(Selection::Entity(index)) => {
+            let Some(entity) = room
+                .header
+                .entities
+                .get(index)
+                .and_then(|e| e.entity_type.as_ref())
+            else {
+                return;
+            };
+            let position = to_world(entity.position());
+            gizmos.sphere(position, Quat::IDENTITY, 0.15, SELECTION_COLOR);
+            gizmos.line(position, position + Vec3::Y * 0.5, SELECTION_COLOR);
+        }
+        Some(Selection::TriggerBox(index)) => {
+            if let Some(trigger_box) = room.header.trigger_boxes.get(index) {
+                let bounds = trigger_box.bounding_box();
+                let size = Vec3::from(bounds.size()) * ROOM_SCALE;
+                gizmos.cuboid(
+                    Transform::from_translation(to_world(bounds.center())).with_scale(size),
+                    SELECTION_COLOR,
+                );
+            }
+        }
+        None => {}
+    }
+}
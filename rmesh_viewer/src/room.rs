@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+
+use bevy::pbr::wireframe::WireframeConfig;
+use bevy::pbr::Lightmap;
+use bevy::prelude::*;
+use bevy_rmesh::rmesh::{read_rmesh, EntityType, Header, RMeshError, ROOM_SCALE};
+use bevy_rmesh::{model_transform, RMeshLoaderSettings, Room};
+
+use crate::ui::ViewerState;
+
+/// The room being viewed, parsed directly for the entity tree and overlays.
+#[derive(Resource)]
+pub struct RoomFile {
+    pub header: Header,
+    pub dir: PathBuf,
+    pub file_name: String,
+    /// Where prop models are read from, `props` next to the room when unset.
+    pub props_dir: Option<PathBuf>,
+}
+
+impl RoomFile {
+    pub fn open(path: &Path, props_dir: Option<PathBuf>) -> Result<Self, RMeshError> {
+        let header = read_rmesh(&std::fs::read(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self {
+            header,
+            dir,
+            file_name,
+            // The asset root is the room directory, so keep the props absolute
+            props_dir: props_dir.map(|dir| dir.canonicalize().unwrap_or(dir)),
+        })
+    }
+}
+
+/// Converts a room space position the same way the loader does.
+pub fn to_world(position: [f32; 3]) -> Vec3 {
+    Vec3::new(position[0], position[1], -position[2]) * ROOM_SCALE
+}
+
+/// Lightmap of a room mesh, applied while lightmaps are enabled.
+#[derive(Component)]
+pub struct LightmapImage(pub Handle<Image>);
+
+/// Keeps the room asset, and with it the loader settings, alive.
+#[derive(Resource)]
+pub struct RoomHandle(pub Handle<Room>);
+
+/// A prop model placed by a `Model` entity.
+#[derive(Component)]
+pub struct Prop;
+
+pub fn spawn_room(
+    mut commands: Commands,
+    room: Res<RoomFile>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Load the room first so the labeled assets below come from these settings
+    let props_root = room
+        .props_dir
+        .as_ref()
+        .map(|dir| dir.to_string_lossy().into_owned());
+    let handle = asset_server.load_with_settings(
+        room.file_name.clone(),
+        move |settings: &mut RMeshLoaderSettings| settings.props_root = props_root.clone(),
+    );
+    commands.insert_resource(RoomHandle(handle));
+
+    // Props have no materials of their own yet
+    let prop_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.6, 0.6, 0.6),
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    for entity in &room.header.entities {
+        if let Some(EntityType::Model(model)) = &entity.entity_type {
+            let name = String::from(&model.name);
+            commands.spawn((
+                PbrBundle {
+                    mesh: asset_server.load(format!("{}#EntityMesh{name}", room.file_name)),
+                    material: prop_material.clone(),
+                    transform: model_transform(model, ROOM_SCALE),
+                    ..default()
+                },
+                Prop,
+            ));
+        }
+    }
+
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 300.0,
+    });
+}
+
+/// Spawns the room meshes once the room asset has loaded, materials are shared between them.
+pub fn spawn_meshes(
+    mut commands: Commands,
+    room: Res<RoomFile>,
+    handle: Res<RoomHandle>,
+    rooms: Res<Assets<Room>>,
+    mut spawned: Local<bool>,
+) {
+    if *spawned {
+        return;
+    }
+    let Some(loaded) = rooms.get(&handle.0) else {
+        return;
+    };
+    for room_mesh in &loaded.meshes {
+        let mesh = &room.header.meshes[room_mesh.source];
+        let mut entity = commands.spawn(PbrBundle {
+            mesh: room_mesh.mesh.clone(),
+            material: room_mesh.material.clone(),
+            visibility: if mesh.is_invisible() {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            },
+            ..default()
+        });
+        if let Some(lightmap) = &room_mesh.lightmap {
+            entity.insert(LightmapImage(lightmap.clone()));
+        }
+    }
+    *spawned = true;
+}
+
+pub fn toggle_lightmaps(
+    mut commands: Commands,
+    state: Res<ViewerState>,
+    meshes: Query<(Entity, Ref<LightmapImage>)>,
+) {
+    for (entity, lightmap) in &meshes {
+        // Meshes spawn once the room has loaded, after the first state change
+        if !state.is_changed() && !lightmap.is_added() {
+            continue;
+        }
+        if state.show_lightmaps {
+            commands.entity(entity).insert(Lightmap {
+                image: lightmap.0.clone(),
+                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            });
+        } else {
+            commands.entity(entity).remove::<Lightmap>();
+        }
+    }
+}
+
+pub fn toggle_props(state: Res<ViewerState>, mut props: Query<&mut Visibility, With<Prop>>) {
+    if !state.is_changed() {
+        return;
+    }
+    for mut visibility in &mut props {
+        *visibility = if state.show_props {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn toggle_wireframe(state: Res<ViewerState>, mut config: ResMut<WireframeConfig>) {
+    if state.is_changed() {
+        config.global = state.wireframe;
+    }
+}
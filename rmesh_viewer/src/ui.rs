@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rmesh::rmesh::{EntityType, TriggerBox};
+
+use crate::camera::{self, Bookmark, Bookmarks, FlyCamera};
+use crate::room::{to_world, RoomFile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Mesh(usize),
+    Entity(usize),
+    TriggerBox(usize),
+}
+
+#[derive(Resource, Clone, PartialEq)]
+pub struct ViewerState {
+    pub show_lightmaps: bool,
+    pub show_props: bool,
+    pub show_colliders: bool,
+    pub show_trigger_boxes: bool,
+    pub wireframe: bool,
+    pub normals: bool,
+    pub selected: Option<Selection>,
+}
+
+impl Default for ViewerState {
+    fn default() -> Self {
+        Self {
+            show_lightmaps: true,
+            show_props: true,
+            show_colliders: false,
+            show_trigger_boxes: false,
+            wireframe: false,
+            normals: false,
+            selected: None,
+        }
+    }
+}
+
+/// Where a selection is in world space, for focusing the camera on it.
+pub fn selection_center(room: &RoomFile, selection: Selection) -> Option<Vec3> {
+    match selection {
+        Selection::Mesh(index) => {
+            let bounds = room.header.meshes.get(index)?.vertices.iter();
+            let points: Vec<_> = bounds.map(|v| to_world(v.position)).collect();
+            let sum: Vec3 = points.iter().sum();
+            (!points.is_empty()).then(|| sum / points.len() as f32)
+        }
+        Selection::Entity(index) => {
+            let entity = room.header.entities.get(index)?.entity_type.as_ref()?;
+            Some(to_world(entity.position()))
+        }
+        Selection::TriggerBox(index) => {
+            let bounds = room.header.trigger_boxes.get(index)?.bounding_box();
+            Some(to_world(bounds.center()))
+        }
+    }
+}
+
+fn entity_label(index: usize, entity: &EntityType) -> String {
+    let [x, y, z] = entity.position();
+    let name = match entity {
+        EntityType::Screen(screen) => format!(" {}", String::from(&screen.name)),
+        EntityType::Model(model) => format!(" {}", String::from(&model.name)),
+        _ => String::new(),
+    };
+    format!("#{index}{name} ({x:.0}, {y:.0}, {z:.0})")
+}
+
+fn trigger_box_label(index: usize, trigger_box: &TriggerBox) -> String {
+    format!("#{index} {}", String::from(&trigger_box.name))
+}
+
+pub fn panels(
+    mut contexts: EguiContexts,
+    room: Res<RoomFile>,
+    mut state: ResMut<ViewerState>,
+    mut bookmarks: ResMut<Bookmarks>,
+    mut cameras: Query<(&mut Transform, &mut FlyCamera)>,
+) {
+    let ctx = contexts.ctx_mut();
+    let mut focus = None;
+
+    egui::SidePanel::left("room").show(ctx, |ui| {
+        ui.heading("View");
+        // Only write through to the state when something changed, systems watch it
+        let mut view = state.clone();
+        ui.checkbox(&mut view.show_lightmaps, "Lightmaps");
+        ui.checkbox(&mut view.show_props, "Props");
+        ui.checkbox(&mut view.wireframe, "Wireframe");
+        ui.checkbox(&mut view.normals, "Normals");
+        ui.label("Debug");
+        ui.checkbox(&mut view.show_colliders, "Colliders");
+        ui.checkbox(&mut view.show_trigger_boxes, "Trigger boxes");
+        if view != *state {
+            *state = view;
+        }
+
+        ui.separator();
+        ui.heading("Room");
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let mut selected = state.selected;
+
+            egui::CollapsingHeader::new(format!("Meshes ({})", room.header.meshes.len())).show(
+                ui,
+                |ui| {
+                    for (index, mesh) in room.header.meshes.iter().enumerate() {
+                        let texture = mesh.textures[1]
+                            .path
+                            .as_ref()
+                            .map(String::from)
+                            .unwrap_or_else(|| "invisible".into());
+                        let label = format!("#{index} {texture}");
+                        if ui
+                            .selectable_label(selected == Some(Selection::Mesh(index)), label)
+                            .clicked()
+                        {
+                            selected = Some(Selection::Mesh(index));
+                        }
+                    }
+                },
+            );
+
+            for kind in [
+                "light",
+                "spotlight",
+                "screen",
+                "waypoint",
+                "soundemitter",
+                "playerstart",
+                "model",
+            ] {
+                let entities: Vec<_> = room
+                    .header
+                    .entities
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, e)| Some((i, e.entity_type.as_ref()?)))
+                    .filter(|(_, e)| e.name() == kind)
+                    .collect();
+                if entities.is_empty() {
+                    continue;
+                }
+                egui::CollapsingHeader::new(format!("{kind} ({})", entities.len())).show(
+                    ui,
+                    |ui| {
+                        for (index, entity) in entities {
+                            let is_selected = selected == Some(Selection::Entity(index));
+                            if ui
+                                .selectable_label(is_selected, entity_label(index, entity))
+                                .clicked()
+                            {
+                                selected = Some(Selection::Entity(index));
+                            }
+                        }
+                    },
+                );
+            }
+
+            if !room.header.trigger_boxes.is_empty() {
+                let title = format!("Trigger boxes ({})", room.header.trigger_boxes.len());
+                egui::CollapsingHeader::new(title).show(ui, |ui| {
+                    for (index, trigger_box) in room.header.trigger_boxes.iter().enumerate() {
+                        let is_selected = selected == Some(Selection::TriggerBox(index));
+                        if ui
+                            .selectable_label(is_selected, trigger_box_label(index, trigger_box))
+                            .clicked()
+                        {
+                            selected = Some(Selection::TriggerBox(index));
+                        }
+                    }
+                });
+            }
+
+            if selected != state.selected {
+                state.selected = selected;
+                focus = selected.and_then(|selection| selection_center(&room, selection));
+            }
+        });
+    });
+
+    egui::Window::new("Bookmarks")
+        .default_pos([ctx.screen_rect().right() - 220.0, 20.0])
+        .show(ctx, |ui| {
+            if ui.button("Add current view").clicked() {
+                if let Ok((transform, camera)) = cameras.get_single() {
+                    let name = format!("View {}", bookmarks.0.len() + 1);
+                    bookmarks.0.push(Bookmark {
+                        name,
+                        translation: transform.translation,
+                        yaw: camera.yaw,
+                        pitch: camera.pitch,
+                    });
+                }
+            }
+            let mut remove = None;
+            for (index, bookmark) in bookmarks.0.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    if index < 9 {
+                        ui.label(format!("{}", index + 1));
+                    }
+                    ui.text_edit_singleline(&mut bookmark.name);
+                    if ui.button("Go").clicked() {
+                        for (mut transform, mut camera) in &mut cameras {
+                            camera::go_to(&mut transform, &mut camera, bookmark);
+                        }
+                    }
+                    if ui.button("x").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                bookmarks.0.remove(index);
+            }
+        });
+
+    if let Some(target) = focus {
+        for (mut transform, mut camera) in &mut cameras {
+            camera::focus(&mut transform, &mut camera, target);
+        }
+    }
+}
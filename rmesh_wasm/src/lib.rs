@@ -0,0 +1,6 @@
+//! Bindings for browsers.
+//!
+//! The bindings live in `rmesh::wasm` behind the `wasm-bindgen` feature, this crate only
+//! packages them: `wasm-pack build rmesh_wasm --target web`, see the web viewer in `web`.
+
+pub use rmesh::wasm::*;